@@ -0,0 +1,79 @@
+#![feature (test)]
+
+extern crate test;
+extern crate time_steward;
+
+use test::{Bencher, black_box};
+
+use time_steward::support::rounding_error_tolerant_math::*;
+use time_steward::support::time_functions::QuadraticTrajectory;
+
+// The solvers return RootSets, which live on the stack, so none of these allocate
+// (in release builds; debug builds run extra self-checks that do).
+// That only goes for the solvers and the quadratic trajectory functions: the
+// degree-general polynomial_* trajectory functions, which the cubic trajectories use,
+// still build their proxy polynomials in Vecs.
+// The *_collected benchmarks copy the results into a Vec, as the solvers used to,
+// to show what the allocation cost.
+
+fn quadratic_terms() -> [Range; 3] {
+  [Range::new(-900, -800), Range::new(500, 501), Range::exactly(50)]
+}
+
+#[bench]
+fn roots_quadratic_stack(bencher: &mut Bencher) {
+  bencher.iter(|| {
+    let roots = roots_quadratic(black_box(quadratic_terms()), -i64::max_value(), i64::max_value());
+    black_box(roots.len())
+  })
+}
+
+#[bench]
+fn roots_quadratic_collected(bencher: &mut Bencher) {
+  bencher.iter(|| {
+    let roots = roots_quadratic(black_box(quadratic_terms()), -i64::max_value(), i64::max_value());
+    let collected: Vec<Range> = roots.iter().cloned().collect();
+    black_box(collected.len())
+  })
+}
+
+#[bench]
+fn roots_quartic_stack(bencher: &mut Bencher) {
+  // (x + 8)(x - 55)(x - 999)(x + 84)
+  let mut polynomial = vec![Range::exactly(1)];
+  for root in [-8, 55, 999, -84].iter() {
+    polynomial = multiply_polynomials(&polynomial, &[Range::exactly(-root), Range::exactly(1)]);
+  }
+  let terms = [polynomial[0], polynomial[1], polynomial[2], polynomial[3], polynomial[4]];
+  bencher.iter(|| {
+    let roots = roots_quartic(black_box(terms), -i64::max_value(), i64::max_value());
+    black_box(roots.len())
+  })
+}
+
+#[bench]
+fn distance_crossing_intervals_stack(bencher: &mut Bencher) {
+  let first = [[0, 1 << 10, 3], [0, 0, -2]];
+  let second = [[1 << 20, -(1 << 10), 0], [1000, 5, 0]];
+  let max_error = max_error_for_distance_traveled(1 << 24);
+  bencher.iter(|| {
+    let intervals = quadratic_trajectories_possible_distance_crossing_intervals(black_box(5000),
+                                                                                (0, &first),
+                                                                                (0, &second),
+                                                                                10,
+                                                                                max_error);
+    black_box(intervals.len())
+  })
+}
+
+#[bench]
+fn approximately_when_distance_passes(bencher: &mut Bencher) {
+  let first = QuadraticTrajectory::new(10, 1 << 24, [0, 0, 1 << 10, 0, 3, -2]);
+  let second = QuadraticTrajectory::new(10, 1 << 24, [1 << 20, 1000, -(1 << 10), 5, 0, 0]);
+  bencher.iter(|| {
+    black_box(QuadraticTrajectory::approximately_when_distance_passes(black_box(5000),
+                                                                      -1,
+                                                                      (0, &first),
+                                                                      (0, &second)))
+  })
+}
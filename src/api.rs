@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, BTreeMap};
 use std::hash::Hash;
 use std::any::Any;
 use std::sync::Arc;
@@ -10,6 +10,7 @@ use rand::{Rng};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use bincode;
+use serde_json;
 
 use implementation_support::list_of_types::{ColumnList, EventList, PredictorList};
 use implementation_support::data_structures::BuildTrivialU64Hasher;
@@ -333,12 +334,163 @@ pub fn deserialize_snapshot<B: Basics, R: Any + Read, S: Any + Copy + bincode::S
   })
 }
 
+/**
+Writes a snapshot as a human-readable JSON document, for debugging.
+
+Fields are grouped by row, and rows and columns are sorted by id, so two exports of the same world state are textually identical and can be diffed. Each field lists its column id, its data, and the ExtendedTime of its last change.
+
+`import_snapshot_json` reads the same format back into a FiatSnapshot, so you can hand-edit a world state and then load it using `TimeStewardFromSnapshot::from_snapshot`.
+*/
+pub fn export_snapshot_json<'a, B: Basics, Shot: Snapshot<Basics = B>, W: Write>
+  (snapshot: &'a Shot,
+   writer: &mut W)
+   -> serde_json::Result<()>
+  where &'a Shot: IntoIterator<Item = SnapshotEntry<'a, B>>
+{
+  use serde_json::{Value, Map, to_value};
+  let mut rows: BTreeMap<RowId, BTreeMap<ColumnId, Value>> = BTreeMap::new();
+  for (id, (data, changed)) in snapshot {
+    let mut field = Map::new();
+    field.insert ("column_id".to_string(), try! (to_value (id.column_id)));
+    field.insert ("data".to_string(), try! (::implementation_support::common::field_to_json::<B> (id.column_id, data)));
+    field.insert ("last_change".to_string(), try! (to_value (changed)));
+    rows.entry (id.row_id).or_insert (BTreeMap::new()).insert (id.column_id, Value::Object (field));
+  }
+  let mut rows_json = Vec::with_capacity (rows.len());
+  for (row_id, columns) in rows {
+    let mut row = Map::new();
+    row.insert ("row_id".to_string(), try! (to_value (row_id)));
+    row.insert ("columns".to_string(), Value::Array (columns.into_iter().map (| (_, field) | field).collect()));
+    rows_json.push (Value::Object (row));
+  }
+  let mut document = Map::new();
+  document.insert ("now".to_string(), try! (to_value (snapshot.now())));
+  document.insert ("constants".to_string(), try! (to_value (snapshot.constants())));
+  document.insert ("rows".to_string(), Value::Array (rows_json));
+  serde_json::to_writer_pretty (writer, &Value::Object (document))
+}
+
+/**
+Reads a snapshot written by `export_snapshot_json` (or written by hand in the same format).
+
+The document is an object with "now", "constants", and "rows". Each row has a "row_id" and a list of "columns". Each column entry has a "column_id", the field's "data", and the ExtendedTime of its "last_change". Rows and columns may appear in any order.
+
+Returns an error, instead of a partial snapshot, if the JSON is malformed, a key is missing, a column id isn't listed in Basics::IncludedTypes, any value doesn't deserialize to the type its column expects, or the same field appears twice.
+*/
+pub fn import_snapshot_json<B: Basics, R: Read>(reader: &mut R) -> serde_json::Result<FiatSnapshot<B>> {
+  use serde_json::{Value, from_value};
+  fn malformed (message: String)->serde_json::Error {
+    <serde_json::Error as ::serde::de::Error>::custom (message)
+  }
+  fn take (object: &mut Value, key: &str)->serde_json::Result<Value> {
+    object.as_object_mut().and_then (| map | map.remove (key)).ok_or_else (|| malformed (format! ("missing \"{}\" in snapshot document", key)))
+  }
+  fn take_array (object: &mut Value, key: &str)->serde_json::Result<Vec<Value>> {
+    match try! (take (object, key)) {
+      Value::Array (array) => Ok (array),
+      _ => Err (malformed (format! ("\"{}\" in snapshot document must be an array", key))),
+    }
+  }
+
+  let known_columns = ::implementation_support::list_of_types::column_ids::<B>();
+  let mut document: Value = try! (serde_json::from_reader (reader));
+  let now = try! (from_value (try! (take (&mut document, "now"))));
+  let constants = try! (from_value (try! (take (&mut document, "constants"))));
+  let mut fields = HashMap::default();
+  for mut row in try! (take_array (&mut document, "rows")) {
+    let row_id: RowId = try! (from_value (try! (take (&mut row, "row_id"))));
+    for mut field in try! (take_array (&mut row, "columns")) {
+      let column_id: ColumnId = try! (from_value (try! (take (&mut field, "column_id"))));
+      if !known_columns.contains (&column_id) {
+        return Err (malformed (format! ("{:?} in snapshot document isn't in Basics::IncludedTypes", column_id)));
+      }
+      let data = try! (::implementation_support::common::field_from_json::<B> (column_id, try! (take (&mut field, "data"))));
+      let changed: ExtendedTime<B> = try! (from_value (try! (take (&mut field, "last_change"))));
+      let id = FieldId::new (row_id, column_id);
+      if fields.insert (id, (data, changed)).is_some() {
+        return Err (malformed (format! ("{:?} appears more than once in snapshot document", id)));
+      }
+    }
+  }
+  Ok(FiatSnapshot {
+    now: now,
+    constants: constants,
+    fields: fields,
+  })
+}
+
 
 
 #[derive (Copy, Clone, PartialEq, Eq, Debug)]
 pub enum FiatEventOperationError {
   InvalidInput,
   InvalidTime,
+  /// The time is valid for the steward, but further in the past than the steward
+  /// has been configured to allow (see simply_synchronized::Steward::set_maximum_lateness()).
+  TooLate,
+}
+
+/// What created a scheduled event.
+#[derive (Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum EventSource {
+  Fiat,
+  /// The event was predicted by this predictor, running on this row.
+  Predicted { predictor_id: PredictorId, row_id: RowId },
+}
+
+/// An event that a TimeSteward expects to happen, as returned by TimeSteward::scheduled_events().
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct ScheduledEvent<B: Basics> {
+  pub time: ExtendedTime<B>,
+  pub event_id: EventId,
+  pub source: EventSource,
+}
+
+/// Counts of the work a steward has done, for profiling.
+///
+/// Not every steward does every kind of work. For instance, flat stewards never invalidate events,
+/// and stewards that don't keep snapshots up to date eagerly never update them.
+#[derive (Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct StewardStatistics {
+  /// Events executed for the first time.
+  pub events_executed: u64,
+  /// Executions that became wrong because something they depended on changed in the past,
+  /// or because the event was unscheduled.
+  pub events_invalidated: u64,
+  /// Events executed again after being invalidated.
+  pub events_rerun: u64,
+  pub predictions_made: u64,
+  /// Predictions thrown away (in whole or in part) because something they depended on changed.
+  pub predictions_discarded: u64,
+  /// The number of times a field's old value was copied into a snapshot because the field was about to change.
+  pub snapshots_updated: u64,
+  /// An estimate of the memory the steward uses to remember the past, not counting the field data itself.
+  /// Flat stewards remember no past, so for them, this only counts the present.
+  pub history_bytes: usize,
+}
+
+#[derive (Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StewardAction {
+  EventExecuted,
+  EventInvalidated,
+  EventRerun,
+  PredictionMade,
+  PredictionDiscarded,
+  SnapshotUpdated,
+}
+
+/// Receives a notification every time a steward does some work.
+/// The statistics have already been updated to include the action.
+///
+/// A sudden flood of EventInvalidated usually means a retroactive change
+/// is cascading through a long chain of dependent events.
+pub trait StewardObserver {
+  fn observe(&mut self, action: StewardAction, statistics: &StewardStatistics);
+}
+impl<F: FnMut(StewardAction, &StewardStatistics)> StewardObserver for F {
+  fn observe(&mut self, action: StewardAction, statistics: &StewardStatistics) {
+    (self)(action, statistics)
+  }
 }
 
 // This exists to support a variety of time stewards
@@ -484,6 +636,26 @@ pub trait TimeSteward: Any {
   steward.snapshot_before(time) may not increase steward.valid_since() beyond Before(time).
   */
   fn snapshot_before(&mut self, time: &<<Self as TimeSteward>::Basics as Basics>::Time) -> Option<Self::Snapshot>;
+
+  /**
+  Lists the events currently scheduled at times >= start and < end, in order of their ExtendedTime.
+  
+  This is a read-only view of what the TimeSteward knows right now. Fiat events are always included, but predicted events are only included once the TimeSteward has made the prediction, which it normally does when it computes up to the time when the predictor last had to be run. Events earlier in the window may also change which events are predicted later. (Call snapshot_before() first to bring the TimeSteward up to date.)
+  
+  TimeStewards that don't remember the past (the flat ones) forget events once they execute them, so for them, the result only includes events that haven't happened yet.
+  
+  TimeStewards that can't list their events return an empty list, and lists_scheduled_events() returns false for them.
+  */
+  fn scheduled_events(&self,
+                      _start: &<<Self as TimeSteward>::Basics as Basics>::Time,
+                      _end: &<<Self as TimeSteward>::Basics as Basics>::Time)
+                      -> Vec<ScheduledEvent<Self::Basics>> {
+    Vec::new()
+  }
+  /// Whether scheduled_events() is implemented. If not, it always returns an empty list.
+  fn lists_scheduled_events(&self) -> bool {
+    false
+  }
 }
 
 /// A TimeSteward that can be constructed empty, given only the simulation constants.
@@ -535,11 +707,10 @@ pub trait IncrementalTimeSteward: TimeSteward {
   fn updated_until_before(&self) -> Option<<<Self as TimeSteward>::Basics as Basics>::Time>;
 }
 
-use std::collections::BTreeMap;
 
 /// A protocol used by stewards::simply_synchronized.
 ///
-/// The current protocol only supports synchronizing two clients at a time, and
+/// The current protocol requires every client to be connected directly to every other client, and
 /// has no resilience against malicious input. It will likely be replaced with something
 /// more refined, so it should be considered unstable.
 pub trait SimpleSynchronizableTimeSteward: TimeStewardFromConstants + FullTimeSteward {
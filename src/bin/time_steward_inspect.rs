@@ -0,0 +1,22 @@
+// Inspects snapshots and fiat event logs of the bouncy circles demo.
+//
+// See time_steward::support::inspection for how to make the same tool for your own simulation.
+
+#[macro_use]
+extern crate time_steward;
+
+extern crate nalgebra;
+
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+use time_steward::stewards::amortized;
+use time_steward::support::inspection;
+
+#[path = "../../dev-shared/bouncy_circles.rs"] mod bouncy_circles;
+use bouncy_circles::Basics;
+
+fn main() {
+  inspection::main::<Basics, amortized::Steward<Basics>>();
+}
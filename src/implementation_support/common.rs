@@ -4,10 +4,11 @@ use std::cmp::Ordering;
 use std::io::{Read, Write};
 use std::any::Any;
 use std::borrow::Borrow;
+use std::fmt;
 use rand::{ChaChaRng, SeedableRng};
 use {DeterministicRandomId, PredictorId, EventId, TimeId, RowId, ColumnId, FieldId, SiphashIdGenerator,
      IterationType, Basics, ExtendedTime, Column, Predictor, Event,
-     PredictorAccessor, Mutator, FieldRc, StewardRc,};
+     PredictorAccessor, Mutator, FieldRc, StewardRc, StewardStatistics, StewardAction, StewardObserver};
 use std::marker::PhantomData;
 
 // https://github.com/rust-lang/rfcs/issues/1485
@@ -49,6 +50,37 @@ pub fn split_off_greater_set<K: Ord + Borrow<Q>, Q: Ord + ?Sized>(input: &mut BT
   result
 }
 
+/// Keeps a steward's StewardStatistics, and tells its observer (if any) about each action.
+#[derive (Default)]
+pub struct StatisticsRecorder {
+  pub statistics: StewardStatistics,
+  pub observer: Option<Box<StewardObserver>>,
+}
+impl StatisticsRecorder {
+  pub fn record(&mut self, action: StewardAction) {
+    {
+      let counter = match action {
+        StewardAction::EventExecuted => &mut self.statistics.events_executed,
+        StewardAction::EventInvalidated => &mut self.statistics.events_invalidated,
+        StewardAction::EventRerun => &mut self.statistics.events_rerun,
+        StewardAction::PredictionMade => &mut self.statistics.predictions_made,
+        StewardAction::PredictionDiscarded => &mut self.statistics.predictions_discarded,
+        StewardAction::SnapshotUpdated => &mut self.statistics.snapshots_updated,
+      };
+      *counter += 1;
+    }
+    if let Some(observer) = self.observer.as_mut() {
+      observer.observe(action, &self.statistics);
+    }
+  }
+}
+impl fmt::Debug for StatisticsRecorder {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "StatisticsRecorder {{ statistics: {:?}, observer: {} }}", self.statistics,
+           if self.observer.is_some() { "Some(..)" } else { "None" })
+  }
+}
+
 type EventRng = ChaChaRng;
 fn generator_for_event(id: TimeId) -> EventRng {
   EventRng::from_seed(&[(id.data()[0] >> 32) as u32,
@@ -415,3 +447,12 @@ time_steward_dynamic_fn! (pub fn serialize_field <B: Basics, [W: Any + Write], [
 time_steward_dynamic_fn! (pub fn deserialize_field <B: Basics, [R: Any + Read], [S: Any + bincode::SizeLimit]> (id: ColumnId of <C: Column>, reader: &mut R, size_limit: S) ->bincode::internal::Result<FieldRc> {
   Ok (StewardRc::new (try! (bincode::deserialize_from::<R, C::FieldType, S> (reader, size_limit))))
 });
+
+use serde_json;
+time_steward_dynamic_fn! (pub fn field_to_json <B: Basics> (id: ColumnId of <C: Column>, data: & FieldRc) ->serde_json::Result<serde_json::Value> {
+  serde_json::to_value (::unwrap_field::<C>(data))
+});
+
+time_steward_dynamic_fn! (pub fn field_from_json <B: Basics> (id: ColumnId of <C: Column>, data: serde_json::Value) ->serde_json::Result<FieldRc> {
+  Ok (StewardRc::new (try! (serde_json::from_value::<C::FieldType> (data))))
+});
@@ -1,5 +1,5 @@
 use std::marker::PhantomData;
-use {Column, Event, Predictor, Basics};
+use {Column, Event, Predictor, Basics, ColumnId, EventId};
 
 //pub trait Contains <T> {}
 pub trait AmI <T> {fn am_i()->bool;}
@@ -395,6 +395,37 @@ pub fn contains_predictor <B: Basics, P: Predictor>()->bool {
   checker.0
 }
 
+/// Lists the ColumnIds of all columns in Basics::IncludedTypes.
+pub fn column_ids <B: Basics>()->Vec<ColumnId> {
+  struct Collector <B: Basics>(Vec<ColumnId>, PhantomData<B>);
+
+  impl<B: Basics> column_list::User for Collector <B> {
+    fn apply<T: Column>(&mut self) {
+      self.0.push (T::column_id());
+    }
+  }
+
+  let mut collector = Collector::<B>(Vec::new(), PhantomData);
+  <B::IncludedTypes as ColumnList>::apply(&mut collector);
+  collector.0
+}
+
+/// Lists the EventIds of all events in Basics::IncludedTypes,
+/// for checking ids that arrive from outside the program before passing them to a dynamic fn.
+pub fn event_ids <B: Basics>()->Vec<EventId> {
+  struct Collector <B: Basics>(Vec<EventId>, PhantomData<B>);
+
+  impl<B: Basics> event_list::User <B> for Collector <B> {
+    fn apply<T: Event>(&mut self) {
+      self.0.push (T::event_id());
+    }
+  }
+
+  let mut collector = Collector::<B>(Vec::new(), PhantomData);
+  <B::IncludedTypes as EventList <B>>::apply(&mut collector);
+  collector.0
+}
+
 pub fn assert_contains_column<B: Basics, T: Column>() {
   assert! (contains_column::<B, T>(), "Type with {:?} missing from Basics::IncludedTypes", T::column_id());
 }
@@ -20,7 +20,7 @@
 //
 //
 
-#![feature(unboxed_closures, fn_traits, specialization, never_type, shared)]
+#![feature(unboxed_closures, fn_traits, specialization, never_type, shared, i128_type)]
 // #![feature (plugin, custom_derive)]
 // #![plugin (serde_macros)]
 // #![plugin (quickcheck_macros)]
@@ -32,11 +32,14 @@ extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate bincode;
+extern crate serde_json;
 extern crate crossbeam;
 #[macro_use]
 extern crate quickcheck;
 #[macro_use]
 extern crate derivative;
+#[cfg(feature = "compression")]
+extern crate flate2;
 
 macro_rules! printlnerr(
     ($($arg:tt)*) => { {use std::io::Write;
@@ -88,15 +91,22 @@ pub mod stewards {
   pub mod flat_to_inefficient_full;
   pub mod crossverified;
   pub mod simply_synchronized;
+  pub mod authoritative;
+  pub mod recorded;
 }
 
 #[macro_use]
 pub mod support {
   pub mod rounding_error_tolerant_math;
   pub mod time_functions;
+  pub mod rational;
   pub mod collision_detection;
+  pub mod inspection;
+  pub mod transport;
 }
 
+pub mod testing;
+
 
 pub mod rowless {
   #[macro_use]
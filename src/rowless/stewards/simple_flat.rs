@@ -13,8 +13,8 @@ use rand::Rng;
 
 use super::super::api::*;
 use super::super::implementation_support::common::*;
-use implementation_support::common::split_off_greater_set;
-use {DeterministicRandomId};
+use implementation_support::common::{split_off_greater_set, StatisticsRecorder};
+use {DeterministicRandomId, StewardStatistics, StewardAction, StewardObserver};
 
 time_steward_steward_specific_api!();
 
@@ -300,10 +300,12 @@ impl <'a, B: Basics> EventAccessor for EventAccessorStruct <'a, B> {
       })
     };
     self.steward.existent_predictions.insert (handle.clone().as_dynamic_event());
+    self.steward.statistics.record (StewardAction::PredictionMade);
     handle
   }
   fn destroy_prediction <E: Event <Steward = Self::Steward>> (&mut self, prediction: &PredictionHandle<E>) {
     assert!(self.steward.existent_predictions.remove (& prediction.clone().as_dynamic_event()));
+    self.steward.statistics.record (StewardAction::PredictionDiscarded);
   }
   
   fn invalidate <F: FnOnce(&<Self::Steward as TimeSteward>::InvalidationAccessor)> (&self, _: F) {
@@ -351,6 +353,7 @@ pub struct Steward <B: Basics> {
   existent_predictions: BTreeSet <DynamicEventHandle<B>>,
   snapshots: BTreeMap<usize, SnapshotHandle <B>>,
   next_snapshot_index: usize,
+  statistics: StatisticsRecorder,
 }
 
 
@@ -367,6 +370,20 @@ impl<B: Basics> Steward<B> {
     // if it was a fiat event, clean it up:
     self.upcoming_fiat_events.remove(event);
     self.last_event = Some(event.extended_time().clone());
+    self.statistics.record (StewardAction::EventExecuted);
+  }
+  
+  pub fn statistics (&self)->StewardStatistics {
+    use std::mem::size_of;
+    let mut result = self.statistics.statistics.clone();
+    result.history_bytes = (self.upcoming_fiat_events.len() + self.existent_predictions.len())*size_of::<DynamicEventHandle <B>>()
+      + self.snapshots.len()*size_of::<SnapshotHandle <B>>();
+    result
+  }
+  
+  /// Sets an observer to be notified of every action counted by statistics().
+  pub fn set_observer (&mut self, observer: Option <Box <StewardObserver>>) {
+    self.statistics.observer = observer;
   }
 }
 
@@ -443,6 +460,7 @@ impl <B: Basics> ConstructibleTimeSteward for Steward <B> {
       existent_predictions: BTreeSet::new(),
       snapshots: BTreeMap::new(),
       next_snapshot_index: 0,
+      statistics: StatisticsRecorder::default(),
     }
   }
   
@@ -2,8 +2,8 @@ use super::types::*;
 // use stewards::amortized::{EventExecutionState, StewardOwned, StewardShared, FieldHistory, StewardEventsInfo, EventValidity, Field, limit_option_by_value_with_none_representing_positive_infinity, SnapshotsData, Prediction, PredictionHistory, PredictorAccessorResults, DependenciesMap, DynamicEvent, EventState};
 
 use {SiphashIdGenerator, RowId, FieldId, PredictorId, TimeId,
-     FieldRc, ExtendedTime, Basics, TimeSteward};
-use implementation_support::common::{self, field_options_are_equal, split_off_greater, split_off_greater_set};
+     FieldRc, ExtendedTime, Basics, TimeSteward, StewardAction};
+use implementation_support::common::{self, field_options_are_equal, split_off_greater, split_off_greater_set, StatisticsRecorder};
 use implementation_support::data_structures::BuildTrivialU64Hasher;
 use std::collections::{HashMap, BTreeMap, HashSet, BTreeSet, btree_map};
 use std::collections::hash_map::Entry;
@@ -16,9 +16,11 @@ pub fn invalidate_execution<B: Basics>(time: &ExtendedTime<B>,
                                        execution: &mut EventExecutionState,
                                        already_dealt_with: Option<FieldId>,
                                        events_needing_attention: &mut BTreeSet<ExtendedTime<B>>,
-                                       steward_dependencies: &mut DependenciesMap<B>) {
+                                       steward_dependencies: &mut DependenciesMap<B>,
+                                       statistics: &mut StatisticsRecorder) {
   if let EventValidity::ValidWithDependencies(dependencies) =
          mem::replace(&mut execution.validity, EventValidity::Invalid) {
+    statistics.record(StewardAction::EventInvalidated);
     events_needing_attention.insert(time.clone());
     for dependency in dependencies {
       if Some(dependency) == already_dealt_with {
@@ -81,7 +83,8 @@ impl<B: Basics> StewardEventsInfo<B> {
           invalidate_execution::<B>(time,
                                     execution_state, None,
                                     &mut self.events_needing_attention,
-                                    &mut self.dependencies);
+                                    &mut self.dependencies,
+                                    &mut self.statistics);
         }
         if entry.get().execution_state.is_none() {
           self.events_needing_attention.remove(time);
@@ -202,6 +205,7 @@ impl<B: Basics> StewardOwned<B> {
 
         self.events
           .unrecord_prediction_dependencies(row_id, predictor_id, already_dealt_with, &prediction);
+        self.events.statistics.record(StewardAction::PredictionDiscarded);
 
         if prediction.made_at < *time {
           prediction.valid_until = Some(time.clone());
@@ -270,7 +274,8 @@ impl<B: Basics> StewardOwned<B> {
                                     .expect("event that accessed this field not marked executed"),
                                   Some(id),
                                   &mut self.events.events_needing_attention,
-                                  &mut self.events.dependencies)
+                                  &mut self.events.dependencies,
+                                  &mut self.events.statistics)
       }
       let mut already_handled = HashSet::with_capacity(bounded.len());
       for (_, list) in bounded {
@@ -298,7 +303,7 @@ impl<B: Basics> StewardOwned<B> {
     if index == history.changes.len() {
       return;
     }
-    history.update_snapshots(id, snapshots);
+    history.update_snapshots(id, snapshots, &mut self.events.statistics);
     self.invalidate_dependencies(id, &history.changes[index].last_change);
     let is_none_previously =
       history.changes.get(index.wrapping_sub(1)).map_or(true, |previous| previous.data.is_none());
@@ -334,7 +339,8 @@ impl<B: Basics> StewardOwned<B> {
                                   .expect("event that created this change not marked executed"),
                                 None,
                                 &mut self.events.events_needing_attention,
-                                &mut self.events.dependencies);
+                                &mut self.events.dependencies,
+                                &mut self.events.statistics);
     }
   }
 
@@ -345,7 +351,7 @@ impl<B: Basics> StewardOwned<B> {
                     snapshots: &mut SnapshotsData<B>,
                     shared: &StewardShared<B>) {
     history.changes.last().map(|last_change| assert!(last_change.last_change <change.last_change));
-    history.update_snapshots(id, snapshots);
+    history.update_snapshots(id, snapshots, &mut self.events.statistics);
     self.invalidate_dependencies(id, &change.last_change);
     if history.changes.last().map_or(true, |previous| previous.data.is_none()) {
       assert!(change.data.is_some(), "a change from nonexistent to nonexistent shouldn't be recorded");
@@ -576,8 +582,10 @@ impl<B: Basics> Steward<B> {
       state.schedule = Some(event);
       if let Some(ref mut execution) = state.execution_state {
         self.replace_execution(&time, execution, results);
+        self.owned.events.statistics.record(StewardAction::EventRerun);
       } else {
         state.execution_state = Some(self.create_execution(time, results));
+        self.owned.events.statistics.record(StewardAction::EventExecuted);
       }
       self.owned.events.event_states.insert(time.id, state);
     } else {
@@ -684,6 +692,7 @@ impl<B: Basics> Steward<B> {
                                 predictor_id: PredictorId,
                                 time: &ExtendedTime<B>) {
     let (prediction, next_needed, used_unsafe_now) = self.make_prediction (row_id, predictor_id, time);
+    self.owned.events.statistics.record(StewardAction::PredictionMade);
     
     if cfg! (debug_assertions) && false {
       self.test_prediction (row_id, predictor_id, time, &prediction);
@@ -819,12 +828,15 @@ impl<B: Basics> FieldHistory<B> {
       change.data.as_ref().map(|data| (data.clone(), change.last_change.clone()))
     })
   }
-  pub fn update_snapshots(&mut self, my_id: FieldId, snapshots: &SnapshotsData<B>) {
+  pub fn update_snapshots(&mut self, my_id: FieldId, snapshots: &SnapshotsData<B>, statistics: &mut StatisticsRecorder) {
     for (index, &(ref time, ref snapshot_map)) in snapshots.iter().rev() {
       if *index < self.first_snapshot_not_updated {
         break;
       }
-      snapshot_map.get_default(my_id, || self.previous_change_for_snapshot(time));
+      snapshot_map.get_default(my_id, || {
+        statistics.record(StewardAction::SnapshotUpdated);
+        self.previous_change_for_snapshot(time)
+      });
     }
     if let Some((index, _)) = snapshots.iter().rev().next() {
       self.first_snapshot_not_updated = index + 1;
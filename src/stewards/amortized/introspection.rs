@@ -0,0 +1,208 @@
+// Introspection of the amortized steward's dependency records, for debugging invalidation cascades.
+//
+// Internally, the steward keeps track of which fields each event execution and each prediction
+// examined (so that it can invalidate them when those fields change retroactively).
+// Steward::dependency_graph() copies the part of those records that falls within a time window
+// into plain data, which can be inspected directly or exported to Graphviz with write_dot().
+
+use super::types::*;
+use {RowId, FieldId, PredictorId, EventId, ExtendedTime, Basics};
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EventStatus {
+  /// The event is scheduled, but hasn't been executed yet.
+  NotExecuted,
+  /// The event was executed, but something it depended on has changed,
+  /// so it is waiting to be re-run (or undone, if it is no longer scheduled).
+  Invalid,
+  Valid,
+}
+
+#[derive(Clone, Debug)]
+pub struct EventNode<B: Basics> {
+  pub time: ExtendedTime<B>,
+  /// None if the event has been unscheduled, but its execution hasn't been undone yet.
+  pub event_id: Option<EventId>,
+  /// The predictor that scheduled this event, or None for fiat events.
+  pub scheduled_by: Option<(RowId, PredictorId)>,
+  pub status: EventStatus,
+  /// The fields this event examined. Only recorded for valid executions,
+  /// because an invalid execution's dependencies are discarded when it is invalidated.
+  pub accessed: Vec<FieldId>,
+  /// The fields this event modified, for any execution, valid or not.
+  /// An invalid execution's changes are still in place until it is re-run or undone.
+  pub changed: Vec<FieldId>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PredictionNode<B: Basics> {
+  pub row_id: RowId,
+  pub predictor_id: PredictorId,
+  pub made_at: ExtendedTime<B>,
+  /// The time when one of the accessed fields changed, if any has.
+  pub valid_until: Option<ExtendedTime<B>>,
+  pub accessed: Vec<FieldId>,
+  pub what_will_happen: Option<(ExtendedTime<B>, EventId)>,
+}
+
+/// Which events and predictions depend on which fields, within a window of time.
+#[derive(Clone, Debug)]
+pub struct DependencyGraph<B: Basics> {
+  /// Every field accessed or changed by one of the events or predictions, sorted by (row, column).
+  pub fields: Vec<FieldId>,
+  /// Sorted by time.
+  pub events: Vec<EventNode<B>>,
+  /// Sorted by the time the prediction was made.
+  pub predictions: Vec<PredictionNode<B>>,
+}
+
+fn sorted_fields<I: IntoIterator<Item = FieldId>>(fields: I) -> Vec<FieldId> {
+  let mut result: Vec<FieldId> = fields.into_iter().collect();
+  result.sort_by_key(|id| (id.row_id, id.column_id));
+  result
+}
+
+impl<B: Basics> Steward<B> {
+  /// Collects the events whose times are in [start, end),
+  /// and the predictions that were valid at some point in [start, end).
+  ///
+  /// This only reports what the steward has computed so far;
+  /// call step() or snapshot_before() first if you need the window to be up to date.
+  pub fn dependency_graph(&self, start: &B::Time, end: &B::Time) -> DependencyGraph<B> {
+    let mut fields = HashSet::new();
+    let mut events = Vec::new();
+    let mut predictions = Vec::new();
+
+    for state in self.owned.events.event_states.values() {
+      if state.time.base < *start || state.time.base >= *end {
+        continue;
+      }
+      let (status, accessed, changed) = match state.execution_state {
+        None => (EventStatus::NotExecuted, Vec::new(), Vec::new()),
+        Some(ref execution) => {
+          match execution.validity {
+            EventValidity::Invalid => (EventStatus::Invalid, Vec::new(), sorted_fields(execution.fields_changed.iter().cloned())),
+            EventValidity::ValidWithDependencies(ref dependencies) => {
+              (EventStatus::Valid,
+               sorted_fields(dependencies.iter().cloned()),
+               sorted_fields(execution.fields_changed.iter().cloned()))
+            }
+          }
+        }
+      };
+      fields.extend(accessed.iter().cloned());
+      fields.extend(changed.iter().cloned());
+      events.push(EventNode {
+        time: state.time.clone(),
+        event_id: state.schedule.as_ref().map(|event| event.event_id()),
+        scheduled_by: state.scheduled_by,
+        status: status,
+        accessed: accessed,
+        changed: changed,
+      });
+    }
+
+    for (&(row_id, predictor_id), history) in self.owned.predictions_by_id.iter() {
+      for prediction in history.predictions.iter() {
+        if prediction.made_at.base >= *end ||
+           prediction.valid_until.as_ref().map_or(false, |limit| limit.base < *start) {
+          continue;
+        }
+        let accessed = sorted_fields(prediction.predictor_accessed.iter().cloned());
+        fields.extend(accessed.iter().cloned());
+        predictions.push(PredictionNode {
+          row_id: row_id,
+          predictor_id: predictor_id,
+          made_at: prediction.made_at.clone(),
+          valid_until: prediction.valid_until.clone(),
+          accessed: accessed,
+          what_will_happen: prediction.what_will_happen
+            .as_ref()
+            .map(|&(ref time, ref event)| (time.clone(), event.event_id())),
+        });
+      }
+    }
+
+    events.sort_by(|a, b| a.time.cmp(&b.time));
+    predictions.sort_by(|a, b| (&a.made_at, a.row_id, a.predictor_id).cmp(&(&b.made_at, b.row_id, b.predictor_id)));
+    DependencyGraph {
+      fields: sorted_fields(fields),
+      events: events,
+      predictions: predictions,
+    }
+  }
+}
+
+fn field_node(id: FieldId) -> String {
+  format!("field {} {:?}", id.row_id, id.column_id)
+}
+fn event_node<B: Basics>(time: &ExtendedTime<B>) -> String {
+  format!("event {}", time.id)
+}
+fn prediction_node<B: Basics>(prediction: &PredictionNode<B>) -> String {
+  format!("prediction {} {:?} {}", prediction.row_id, prediction.predictor_id, prediction.made_at.id)
+}
+fn quote(text: &str) -> String {
+  format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl<B: Basics> DependencyGraph<B> {
+  /// The events and predictions that examined a field.
+  pub fn dependents(&self, id: FieldId) -> (Vec<&EventNode<B>>, Vec<&PredictionNode<B>>) {
+    (self.events.iter().filter(|event| event.accessed.contains(&id)).collect(),
+     self.predictions.iter().filter(|prediction| prediction.accessed.contains(&id)).collect())
+  }
+
+  /// Writes the graph in Graphviz DOT format.
+  ///
+  /// Fields are ellipses, events are boxes (dashed if invalid, dotted if not executed yet),
+  /// and predictions are diamonds. Edges run from each field to the things that examined it,
+  /// from each event to the fields it changed, and from each prediction to the event it predicted
+  /// (if that event is in the window).
+  pub fn write_dot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+    try!(writeln!(writer, "digraph dependencies {{"));
+    try!(writeln!(writer, "  rankdir=LR;"));
+    for &id in self.fields.iter() {
+      try!(writeln!(writer, "  {} [shape=ellipse, label={}];", quote(&field_node(id)),
+                    quote(&format!("{}\n{:?}", id.row_id, id.column_id))));
+    }
+    let mut event_times = HashSet::new();
+    for event in self.events.iter() {
+      event_times.insert(event.time.id);
+      let style = match event.status {
+        EventStatus::NotExecuted => "dotted",
+        EventStatus::Invalid => "dashed",
+        EventStatus::Valid => "solid",
+      };
+      let label = match event.event_id {
+        Some(event_id) => format!("{:?}\n{:?}", event.time.base, event_id),
+        None => format!("{:?}\n(unscheduled)", event.time.base),
+      };
+      try!(writeln!(writer, "  {} [shape=box, style={}, label={}];", quote(&event_node(&event.time)), style, quote(&label)));
+      for &id in event.accessed.iter() {
+        try!(writeln!(writer, "  {} -> {};", quote(&field_node(id)), quote(&event_node(&event.time))));
+      }
+      for &id in event.changed.iter() {
+        try!(writeln!(writer, "  {} -> {} [color=red];", quote(&event_node(&event.time)), quote(&field_node(id))));
+      }
+    }
+    for prediction in self.predictions.iter() {
+      let label = match prediction.valid_until {
+        Some(ref limit) => format!("{:?}\n{:?} to {:?}", prediction.predictor_id, prediction.made_at.base, limit.base),
+        None => format!("{:?}\nfrom {:?}", prediction.predictor_id, prediction.made_at.base),
+      };
+      try!(writeln!(writer, "  {} [shape=diamond, label={}];", quote(&prediction_node(prediction)), quote(&label)));
+      for &id in prediction.accessed.iter() {
+        try!(writeln!(writer, "  {} -> {};", quote(&field_node(id)), quote(&prediction_node(prediction))));
+      }
+      if let Some((ref time, _)) = prediction.what_will_happen {
+        if event_times.contains(&time.id) {
+          try!(writeln!(writer, "  {} -> {} [style=dashed];", quote(&prediction_node(prediction)), quote(&event_node(time))));
+        }
+      }
+    }
+    writeln!(writer, "}}")
+  }
+}
@@ -35,4 +35,6 @@
 
 mod impls;
 mod types;
+mod introspection;
 pub use self::types::{Steward, Snapshot, SnapshotIter};
+pub use self::introspection::{DependencyGraph, EventNode, PredictionNode, EventStatus};
@@ -35,8 +35,8 @@
 
 use {DeterministicRandomId, SiphashIdGenerator, RowId, FieldId, PredictorId, TimeId, Column, StewardRc,
      FieldRc, ExtendedTime, Basics, Accessor, FiatEventOperationError, ValidSince, TimeSteward,
-     IncrementalTimeSteward, TimeStewardFromConstants};
-use implementation_support::common::{self, DynamicEventFn};
+     IncrementalTimeSteward, TimeStewardFromConstants, StewardStatistics, StewardObserver, ScheduledEvent, EventSource};
+use implementation_support::common::{self, DynamicEventFn, StatisticsRecorder};
 use std::collections::{HashMap, BTreeMap, HashSet, BTreeSet};
 // use std::collections::Bound::{Included, Excluded, Unbounded};
 use std::rc::Rc;
@@ -159,6 +159,8 @@ pub struct StewardEventsInfo<B: Basics> {
   pub event_states: HashMap<TimeId, EventState<B>, BuildTrivialU64Hasher>,
   pub events_needing_attention: BTreeSet<ExtendedTime<B>>,
   pub dependencies: DependenciesMap<B>,
+  // This lives here, rather than in StewardOwned, because events are invalidated from StewardEventsInfo methods.
+  pub statistics: StatisticsRecorder,
 }
 
 pub struct StewardOwned<B: Basics> {
@@ -406,6 +408,36 @@ impl<B: Basics> Steward<B> {
       self.do_next();
     }
   }
+
+  pub fn statistics(&self) -> StewardStatistics {
+    use std::mem::size_of;
+    let mut result = self.owned.events.statistics.statistics.clone();
+    let fields = self.shared.fields.borrow();
+    for history in fields.field_states.values() {
+      result.history_bytes += size_of::<FieldHistory<B>>() + history.changes.len() * size_of::<Field<B>>();
+    }
+    for state in self.owned.events.event_states.values() {
+      result.history_bytes += size_of::<EventState<B>>();
+      if let Some(ref execution) = state.execution_state {
+        result.history_bytes += execution.fields_changed.len() * size_of::<FieldId>();
+        if let EventValidity::ValidWithDependencies(ref dependencies) = execution.validity {
+          result.history_bytes += dependencies.len() * size_of::<FieldId>();
+        }
+      }
+    }
+    for history in self.owned.predictions_by_id.values() {
+      result.history_bytes += size_of::<PredictionHistory<B>>();
+      for prediction in history.predictions.iter() {
+        result.history_bytes += size_of::<Prediction<B>>() + prediction.predictor_accessed.len() * size_of::<FieldId>();
+      }
+    }
+    result
+  }
+
+  /// Sets an observer to be notified of every action counted by statistics().
+  pub fn set_observer(&mut self, observer: Option<Box<StewardObserver>>) {
+    self.owned.events.statistics.observer = observer;
+  }
 }
 
 
@@ -471,6 +503,38 @@ impl<B: Basics> TimeSteward for Steward<B> {
     self.owned.next_snapshot += 1;
     result
   }
+
+  fn scheduled_events(&self, start: &B::Time, end: &B::Time) -> Vec<ScheduledEvent<B>> {
+    let mut result: Vec<ScheduledEvent<B>> = self.owned
+      .events
+      .event_states
+      .values()
+      .filter(|state| state.time.base >= *start && state.time.base < *end)
+      .filter_map(|state| {
+        // events that are no longer scheduled may still be waiting to have their executions undone
+        state.schedule.as_ref().map(|event| {
+          ScheduledEvent {
+            time: state.time.clone(),
+            event_id: event.event_id(),
+            source: match state.scheduled_by {
+              None => EventSource::Fiat,
+              Some((row_id, predictor_id)) => {
+                EventSource::Predicted {
+                  predictor_id: predictor_id,
+                  row_id: row_id,
+                }
+              }
+            },
+          }
+        })
+      })
+      .collect();
+    result.sort_by(|a, b| a.time.cmp(&b.time));
+    result
+  }
+  fn lists_scheduled_events(&self) -> bool {
+    true
+  }
 }
 
 impl<B: Basics> IncrementalTimeSteward for Steward<B> {
@@ -500,6 +564,7 @@ impl<B: Basics> TimeStewardFromConstants for Steward<B> {
           events_needing_attention: BTreeSet::new(),
           event_states: HashMap::default(),
           dependencies: HashMap::default(),
+          statistics: StatisticsRecorder::default(),
         },
         invalid_before: ValidSince::TheBeginning,
         next_snapshot: 0,
@@ -0,0 +1,469 @@
+// A client/server alternative to simply_synchronized.
+//
+// In simply_synchronized, every peer applies every other peer's fiat events unconditionally.
+// Here, clients only submit candidate fiat events. The server checks each one
+// using a policy supplied by the game, picks its final time and id, and sends it to every client.
+// Clients never apply a fiat event until the server has sent it back to them,
+// so a cheating client can't insert events that the game doesn't allow.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::any::Any;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use {Basics, TimeSteward, TimeStewardFromConstants, DeterministicRandomId, EventId, Event, FiatEventOperationError, ValidSince, ScheduledEvent};
+use implementation_support::list_of_types::event_ids;
+use bincode;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+
+// serde(deserialize_with is a hacky workaround for https://github.com/rust-lang/rust/issues/41617 (see https://github.com/serde-rs/serde/issues/943)
+#[derive (Clone, Serialize, Deserialize)]
+enum ToServer<B: Basics> {
+  InsertFiatEvent(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time, #[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId, #[serde(deserialize_with = "Deserialize::deserialize")] EventId, #[serde(deserialize_with = "Deserialize::deserialize")] Vec<u8>),
+  RemoveFiatEvent(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time, #[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId),
+}
+
+#[derive (Clone, Serialize, Deserialize)]
+enum ToClient<B: Basics> {
+  InsertFiatEvent(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time, #[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId, #[serde(deserialize_with = "Deserialize::deserialize")] EventId, #[serde(deserialize_with = "Deserialize::deserialize")] Vec<u8>),
+  RemoveFiatEvent(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time, #[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId),
+  // the time and id that the client submitted, not the final ones
+  Rejected(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time, #[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId),
+  Settled(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time),
+}
+
+/// A fiat event operation that a client asked the server to perform.
+#[derive (Clone, Debug)]
+pub struct Submission<B: Basics> {
+  /// The client that submitted it, as identified by the server in add_client().
+  pub client: DeterministicRandomId,
+  /// The time the client asked for.
+  pub time: B::Time,
+  /// The id the client used. If the submission is accepted, the event's final id is
+  /// derived from this and the client's id, so clients can't insert or remove events using each other's ids.
+  pub id: DeterministicRandomId,
+  /// The type of the submitted event, or None if this is a removal.
+  pub event_id: Option<EventId>,
+  data: Vec<u8>,
+}
+
+impl<B: Basics> Submission<B> {
+  /// Returns the submitted event, if this is an insertion of an event of type E.
+  pub fn event<E: Event<Basics = B>>(&self)->Option<E> {
+    match self.event_id {
+      Some (event_id) if event_id == E::event_id() => bincode::deserialize (&self.data).ok(),
+      _ => None,
+    }
+  }
+}
+
+/// The server's decision about a Submission.
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub enum Verdict<B: Basics> {
+  Accept,
+  /// Accepts an insertion, but at a different time than the client asked for.
+  /// For removals, this is the same as Accept, because the event already has its time.
+  AcceptAt(B::Time),
+  Reject,
+}
+
+fn qualified_id (client: DeterministicRandomId, id: DeterministicRandomId)->DeterministicRandomId {
+  DeterministicRandomId::new (& (id, client))
+}
+
+time_steward_dynamic_fn! (fn insert_serialized_fiat_event <B: Basics, [Steward0: Any + TimeSteward<Basics = B>]> (event_id: EventId of <E: Event <Basics = B>>, steward: &mut Steward0, time: B::Time, id: DeterministicRandomId, data: &[u8])->Result <(), FiatEventOperationError> {
+  let event = try! (bincode::deserialize:: <E> (data).map_err (| _ | FiatEventOperationError::InvalidInput));
+  steward.insert_fiat_event (time, id, event)
+});
+
+fn spawn_connection <Incoming: Send + DeserializeOwned + 'static, Outgoing: Send + Serialize + 'static, Reader: Any + Read + Send, Writer: Any + Write + Send> (index: usize, send_back: Sender <(usize, Incoming)>, mut reader: Reader, mut writer: Writer)->Sender <Outgoing> {
+  let (send_away, receive_away) = channel::<Outgoing>();
+  ::std::thread::spawn (move | | {
+    loop {
+      let message: Incoming = match bincode::deserialize_from (&mut reader, bincode::Infinite) {
+        Err (_) => return,
+        Ok (message) => message,
+      };
+      if send_back.send ((index, message)).is_err() {return;}
+    }
+  });
+  ::std::thread::spawn (move | | {
+    while let Ok (message) = receive_away.recv() {
+      if bincode::serialize_into (&mut writer, &message, bincode::Infinite).is_err() || writer.flush().is_err() {
+        return;
+      }
+    }
+  });
+  send_away
+}
+
+struct Connection<B: Basics> {
+  id: DeterministicRandomId,
+  sender: Sender<ToClient<B>>,
+}
+
+/// The authoritative side of a client/server session.
+///
+/// Every submission from a client is passed to the policy, along with the server's current valid_since(),
+/// before it is applied. For instance, a policy might only accept events that refer to the submitting
+/// client's own player row, or reject events that are too far in the past.
+/// Submissions are processed when snapshot_before() or step() is called.
+///
+/// The server can also insert fiat events of its own, using the TimeSteward methods.
+/// Those are not checked by the policy.
+pub struct Server<B: Basics, Steward0: TimeStewardFromConstants<Basics = B>> {
+  steward: Steward0,
+  clients: Vec<Connection<B>>,
+  incoming_sender: Sender<(usize, ToServer<B>)>,
+  receiver: Receiver<(usize, ToServer<B>)>,
+  policy: Box<FnMut (&Submission <B>, &ValidSince <B::Time>)->Verdict <B>>,
+  valid_since: ValidSince <B::Time>,
+  // Every fiat event that is currently inserted, so that clients added later can catch up.
+  fiat_events: BTreeMap<(B::Time, DeterministicRandomId), (EventId, Vec<u8>)>,
+  // The final times of the events that clients inserted, by final id,
+  // so that clients can remove their events even if the policy moved them.
+  final_times: HashMap<DeterministicRandomId, B::Time>,
+}
+
+impl <B: Basics, Steward0: TimeStewardFromConstants<Basics = B>> Server <B, Steward0> {
+  pub fn new <Policy: FnMut (&Submission <B>, &ValidSince <B::Time>)->Verdict <B> + 'static> (constants: B::Constants, policy: Policy)->Self {
+    let (incoming_sender, receiver) = channel();
+    Server {
+      steward: Steward0::from_constants (constants),
+      clients: Vec::new(),
+      incoming_sender: incoming_sender,
+      receiver: receiver,
+      policy: Box::new (policy),
+      valid_since: ValidSince::TheBeginning,
+      fiat_events: BTreeMap::new(),
+      final_times: HashMap::new(),
+    }
+  }
+
+  /// Connects to a client. The id is how the policy will identify the client, so it should come from
+  /// whatever the game uses to authenticate players, not from the client itself.
+  ///
+  /// Clients can be added at any time; they are sent every fiat event that is currently inserted.
+  pub fn add_client <Reader: Any + Read + Send, Writer: Any + Write + Send> (&mut self, id: DeterministicRandomId, reader: Reader, writer: Writer) {
+    let sender = spawn_connection (self.clients.len(), self.incoming_sender.clone(), reader, writer);
+    for (&(ref time, id), &(event_id, ref data)) in self.fiat_events.iter() {
+      let _ = sender.send (ToClient::InsertFiatEvent (time.clone(), id, event_id, data.clone()));
+    }
+    if let ValidSince::Before (ref time) = self.valid_since {
+      let _ = sender.send (ToClient::Settled (time.clone()));
+    }
+    self.clients.push (Connection {id: id, sender: sender});
+  }
+
+  /// Promises that no more fiat events will be accepted before the given time, and tells the clients so.
+  pub fn settle_before (&mut self, time: B::Time) {
+    if self.valid_since() > time {
+      return;
+    }
+    self.valid_since = ValidSince::Before (time.clone());
+    self.broadcast (ToClient::Settled (time));
+  }
+
+  pub fn steward (&self)->&Steward0 {
+    &self.steward
+  }
+
+  fn broadcast (&self, message: ToClient <B>) {
+    // a client that has disconnected just stops receiving messages
+    for client in self.clients.iter() {
+      let _ = client.sender.send (message.clone());
+    }
+  }
+
+  fn receive_once (&mut self)->bool {
+    match self.receiver.try_recv() {
+      Err (_) => false,
+      Ok ((index, message)) => {
+        let client = self.clients [index].id;
+        let (time, id) = match message {
+          ToServer::InsertFiatEvent (ref time, id, _, _) => (time.clone(), id),
+          ToServer::RemoveFiatEvent (ref time, id) => (time.clone(), id),
+        };
+        if !self.received (client, message) {
+          let _ = self.clients [index].sender.send (ToClient::Rejected (time, id));
+        }
+        true
+      }
+    }
+  }
+
+  fn received (&mut self, client: DeterministicRandomId, message: ToServer <B>)->bool {
+    let valid_since = self.valid_since();
+    match message {
+      ToServer::InsertFiatEvent (time, id, event_id, data) => {
+        // an unknown EventId would make insert_serialized_fiat_event() panic
+        if !event_ids::<B>().contains (&event_id) {
+          return false;
+        }
+        let final_id = qualified_id (client, id);
+        if self.final_times.contains_key (&final_id) {
+          return false;
+        }
+        let submission = Submission {client: client, time: time, id: id, event_id: Some (event_id), data: data};
+        let final_time = match (self.policy) (&submission, &valid_since) {
+          Verdict::Accept => submission.time,
+          Verdict::AcceptAt (time) => time,
+          Verdict::Reject => return false,
+        };
+        if valid_since > final_time {
+          return false;
+        }
+        let data = submission.data;
+        if insert_serialized_fiat_event (event_id, &mut self.steward, final_time.clone(), final_id, &data).is_err() {
+          return false;
+        }
+        self.final_times.insert (final_id, final_time.clone());
+        self.fiat_events.insert ((final_time.clone(), final_id), (event_id, data.clone()));
+        self.broadcast (ToClient::InsertFiatEvent (final_time, final_id, event_id, data));
+        true
+      },
+      ToServer::RemoveFiatEvent (time, id) => {
+        let final_id = qualified_id (client, id);
+        let final_time = match self.final_times.get (&final_id) {
+          Some (final_time) => final_time.clone(),
+          None => return false,
+        };
+        let submission = Submission {client: client, time: time, id: id, event_id: None, data: Vec::new()};
+        if let Verdict::Reject = (self.policy) (&submission, &valid_since) {
+          return false;
+        }
+        if valid_since > final_time || self.steward.remove_fiat_event (&final_time, final_id).is_err() {
+          return false;
+        }
+        self.final_times.remove (&final_id);
+        self.fiat_events.remove (&(final_time.clone(), final_id));
+        self.broadcast (ToClient::RemoveFiatEvent (final_time, final_id));
+        true
+      },
+    }
+  }
+}
+
+impl<B: Basics, Steward0: TimeStewardFromConstants<Basics = B>> TimeSteward for Server<B, Steward0> {
+  type Basics = B;
+  type Snapshot = Steward0::Snapshot;
+
+  fn valid_since(&self) -> ValidSince<B::Time> {
+    ::std::cmp::max (self.valid_since.clone(), self.steward.valid_since())
+  }
+
+  fn insert_fiat_event<E: ::Event<Basics = B>>(&mut self,
+                                        time: B::Time,
+                                        id: DeterministicRandomId,
+                                        event: E)
+                                        -> Result<(), FiatEventOperationError> {
+    time_steward_common_insert_fiat_event_prefix!(B, self, time, E);
+    let result = self.steward.insert_fiat_event (time.clone(), id, event.clone());
+    if result.is_ok() {
+      let data = bincode::serialize (&event, bincode::Infinite).unwrap();
+      self.fiat_events.insert ((time.clone(), id), (E::event_id(), data.clone()));
+      self.broadcast (ToClient::InsertFiatEvent (time, id, E::event_id(), data));
+    }
+    result
+  }
+
+  fn remove_fiat_event(&mut self,
+                      time: &B::Time,
+                      id: DeterministicRandomId)
+                      -> Result<(), FiatEventOperationError> {
+    if self.valid_since() > *time {
+      return Err(FiatEventOperationError::InvalidTime);
+    }
+    let result = self.steward.remove_fiat_event (time, id);
+    if result.is_ok() {
+      // the server may be removing an event that a client inserted
+      if self.final_times.get (&id) == Some (time) {
+        self.final_times.remove (&id);
+      }
+      self.fiat_events.remove (&(time.clone(), id));
+      self.broadcast (ToClient::RemoveFiatEvent (time.clone(), id));
+    }
+    result
+  }
+
+  fn snapshot_before<'b>(&'b mut self, time: &'b B::Time) -> Option<Steward0::Snapshot> {
+    while self.receive_once() {}
+    self.steward.snapshot_before (time)
+  }
+
+  fn scheduled_events (&self, start: &B::Time, end: &B::Time)->Vec <ScheduledEvent <B>> {
+    self.steward.scheduled_events (start, end)
+  }
+  fn lists_scheduled_events (&self)->bool {
+    self.steward.lists_scheduled_events()
+  }
+}
+
+impl<B: Basics, Steward0: ::IncrementalTimeSteward + TimeStewardFromConstants<Basics = B>> ::IncrementalTimeSteward for Server<B, Steward0> {
+  fn step(&mut self) {
+    if !self.receive_once() { self.steward.step(); }
+  }
+  fn updated_until_before (&self)->Option <B::Time> {
+    self.steward.updated_until_before()
+  }
+}
+
+
+/// A message from the server that the client couldn't apply.
+///
+/// The server never sends these, so they mean the connection is corrupt or isn't talking to a Server.
+#[derive (Debug)]
+pub enum ServerMessageError<B: Basics> {
+  /// The server sent an event of a type that isn't in Basics::IncludedTypes.
+  UnknownEvent (EventId),
+  /// The client's steward refused a fiat event operation that the server sent.
+  OperationRejected (B::Time, DeterministicRandomId, FiatEventOperationError),
+}
+
+/// The client side of a client/server session.
+///
+/// insert_fiat_event() and remove_fiat_event() only submit the operation to the server.
+/// They return Ok if the operation was submitted, but it takes effect only when the server sends it back,
+/// possibly at a different time than requested. If the server rejects it, the rejection handler is called instead.
+/// The final id of an inserted event is not the id passed to insert_fiat_event(),
+/// but remove_fiat_event() accepts the original time and id.
+pub struct Client<B: Basics, Steward0: TimeStewardFromConstants<Basics = B>> {
+  steward: Steward0,
+  sender: Sender<ToServer<B>>,
+  receiver: Receiver<(usize, ToClient<B>)>,
+  valid_since: ValidSince <B::Time>,
+  rejection_handler: Option<Box<FnMut (B::Time, DeterministicRandomId)>>,
+  // errors from messages handled by snapshot_before() or step(), which can't return them
+  errors: VecDeque<ServerMessageError<B>>,
+}
+
+impl <B: Basics, Steward0: TimeStewardFromConstants<Basics = B>> Client <B, Steward0> {
+  /// Creates a client connected to a server. The constants must be the same ones the server uses.
+  pub fn new <Reader: Any + Read + Send, Writer: Any + Write + Send> (constants: B::Constants, reader: Reader, writer: Writer)->Self {
+    let (incoming_sender, receiver) = channel();
+    Client {
+      steward: Steward0::from_constants (constants),
+      sender: spawn_connection (0, incoming_sender, reader, writer),
+      receiver: receiver,
+      valid_since: ValidSince::TheBeginning,
+      rejection_handler: None,
+      errors: VecDeque::new(),
+    }
+  }
+
+  /// Sets a function to be called with the time and id of each submission that the server rejects.
+  pub fn on_rejection <F: FnMut (B::Time, DeterministicRandomId) + 'static> (&mut self, handler: F) {
+    self.rejection_handler = Some (Box::new (handler));
+  }
+
+  pub fn steward (&self)->&Steward0 {
+    &self.steward
+  }
+
+  /// Handles every message that has arrived from the server.
+  ///
+  /// snapshot_before() and step() also handle messages, but can't report errors, so they keep them for this to return.
+  /// Returns the first error, if any; a message that caused an error is skipped.
+  pub fn receive (&mut self)->Result <(), ServerMessageError <B>> {
+    loop {
+      if let Some (error) = self.errors.pop_front() {
+        return Err (error);
+      }
+      if !try! (self.receive_once()) {
+        return Ok (());
+      }
+    }
+  }
+
+  // Handles a message, keeping any error for receive().
+  fn receive_once_keeping_errors (&mut self)->bool {
+    match self.receive_once() {
+      Ok (received) => received,
+      Err (error) => {self.errors.push_back (error); true},
+    }
+  }
+
+  fn receive_once (&mut self)->Result <bool, ServerMessageError <B>> {
+    match self.receiver.try_recv() {
+      Err (_) => Ok (false),
+      Ok ((_, message)) => {
+        match message {
+          ToClient::InsertFiatEvent (time, id, event_id, data) => {
+            // an unknown EventId would make insert_serialized_fiat_event() panic
+            if !event_ids::<B>().contains (&event_id) {
+              return Err (ServerMessageError::UnknownEvent (event_id));
+            }
+            if let Err (error) = insert_serialized_fiat_event (event_id, &mut self.steward, time.clone(), id, &data) {
+              return Err (ServerMessageError::OperationRejected (time, id, error));
+            }
+          },
+          ToClient::RemoveFiatEvent (time, id) => {
+            if let Err (error) = self.steward.remove_fiat_event (&time, id) {
+              return Err (ServerMessageError::OperationRejected (time, id, error));
+            }
+          },
+          ToClient::Rejected (time, id) => {
+            if let Some (handler) = self.rejection_handler.as_mut() {
+              handler (time, id);
+            }
+          },
+          ToClient::Settled (time) => {
+            self.valid_since = ::std::cmp::max (self.valid_since.clone(), ValidSince::Before (time));
+          },
+        }
+        Ok (true)
+      }
+    }
+  }
+}
+
+impl<B: Basics, Steward0: TimeStewardFromConstants<Basics = B>> TimeSteward for Client<B, Steward0> {
+  type Basics = B;
+  type Snapshot = Steward0::Snapshot;
+
+  fn valid_since(&self) -> ValidSince<B::Time> {
+    self.valid_since.clone()
+  }
+
+  fn insert_fiat_event<E: ::Event<Basics = B>>(&mut self,
+                                        time: B::Time,
+                                        id: DeterministicRandomId,
+                                        event: E)
+                                        -> Result<(), FiatEventOperationError> {
+    time_steward_common_insert_fiat_event_prefix!(B, self, time, E);
+    let data = bincode::serialize (&event, bincode::Infinite).unwrap();
+    self.sender.send (ToServer::InsertFiatEvent (time, id, E::event_id(), data)).map_err (| _ | FiatEventOperationError::InvalidInput)
+  }
+
+  fn remove_fiat_event(&mut self,
+                      time: &B::Time,
+                      id: DeterministicRandomId)
+                      -> Result<(), FiatEventOperationError> {
+    if self.valid_since() > *time {
+      return Err(FiatEventOperationError::InvalidTime);
+    }
+    self.sender.send (ToServer::RemoveFiatEvent (time.clone(), id)).map_err (| _ | FiatEventOperationError::InvalidInput)
+  }
+
+  fn snapshot_before<'b>(&'b mut self, time: &'b B::Time) -> Option<Steward0::Snapshot> {
+    while self.receive_once_keeping_errors() {}
+    self.steward.snapshot_before (time)
+  }
+
+  fn scheduled_events (&self, start: &B::Time, end: &B::Time)->Vec <ScheduledEvent <B>> {
+    self.steward.scheduled_events (start, end)
+  }
+  fn lists_scheduled_events (&self)->bool {
+    self.steward.lists_scheduled_events()
+  }
+}
+
+impl<B: Basics, Steward0: ::IncrementalTimeSteward + TimeStewardFromConstants<Basics = B>> ::IncrementalTimeSteward for Client<B, Steward0> {
+  fn step(&mut self) {
+    if !self.receive_once_keeping_errors() { self.steward.step(); }
+  }
+  fn updated_until_before (&self)->Option <B::Time> {
+    self.steward.updated_until_before()
+  }
+}
@@ -1,137 +1,409 @@
-//! A wrapper around two different TimeSteward types, to verify that they behave consistently.
+//! A wrapper around several different TimeSteward types, to verify that they behave consistently.
 //!
+//! `Many<B, (Steward0, Steward1, ...)>` runs every operation on each of the stewards in the tuple
+//! (2 to 6 of them), and panics as soon as they disagree. When snapshots disagree,
+//! the panic message describes every differing field, with each steward's version of it
+//! and the time of the event that wrote it.
 //!
+//! A differing field only shows where the disagreement ended up, not where it started.
+//! If every steward is a SimpleSynchronizableTimeSteward, call begin_checks() before inserting any fiat events,
+//! and first_divergence() can then compare the stewards' event checksums chunk by chunk,
+//! to find the earliest event that ran differently.
 //!
+//! `Steward<B, Steward0, Steward1>` is the same thing for exactly two stewards.
 
 
-use {DeterministicRandomId, FieldId, ExtendedTime, Basics, FieldRc, TimeSteward,
-     IncrementalTimeSteward, TimeStewardFromConstants, TimeStewardFromSnapshot, FullTimeSteward, CanonicalTimeSteward, FiatEventOperationError, ValidSince};
-use std::collections::HashMap;
-use std::cmp::max;
+use {DeterministicRandomId, FieldId, ExtendedTime, Basics, FieldRc, TimeSteward, Event, Accessor, SnapshotEntry,
+     IncrementalTimeSteward, TimeStewardFromConstants, TimeStewardFromSnapshot, FullTimeSteward, CanonicalTimeSteward, FiatEventOperationError, ValidSince,
+     ScheduledEvent, EventSource, SimpleSynchronizableTimeSteward};
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet};
+use std::any::Any;
+use std::fmt;
 use std::marker::PhantomData;
 use Snapshot as SuperSnapshot;
-use implementation_support::common::fields_are_equal;
+use implementation_support::common::{fields_are_equal, field_to_json};
 
-pub struct Steward<B: Basics, Steward0: TimeSteward<Basics = B>, Steward1: TimeSteward<Basics = B> > (
-  Steward0,
-  Steward1,
+/// A tuple of TimeStewards that can be verified against each other.
+///
+/// The methods return one result for each steward, in order.
+pub trait Stewards <B: Basics>: Any {
+  type Snapshots: Any;
+  fn valid_since (&self)->Vec <ValidSince <B::Time>>;
+  fn insert_fiat_event <E: Event <Basics = B>> (&mut self, time: B::Time, id: DeterministicRandomId, event: E)->Vec <Result <(), FiatEventOperationError>>;
+  fn remove_fiat_event (&mut self, time: &B::Time, id: DeterministicRandomId)->Vec <Result <(), FiatEventOperationError>>;
+  /// Returns the index of the first steward that didn't return a snapshot, if any.
+  fn snapshot_before (&mut self, time: &B::Time)->Result <Self::Snapshots, usize>;
+  fn scheduled_events (&self, start: &B::Time, end: &B::Time)->Vec <Vec <ScheduledEvent <B>>>;
+  fn lists_scheduled_events (&self)->Vec <bool>;
+  fn field <'a> (snapshots: &'a Self::Snapshots, id: FieldId)->Vec <Option <(&'a FieldRc, &'a ExtendedTime <B>)>>;
+  fn constants (snapshots: &Self::Snapshots)->&B::Constants;
+  fn now (snapshots: &Self::Snapshots)->Vec <&B::Time>;
+  fn num_fields (snapshots: &Self::Snapshots)->Vec <usize>;
+}
+pub trait StewardsFromConstants <B: Basics>: Stewards <B> {
+  fn from_constants (constants: B::Constants)->Self;
+}
+pub trait StewardsFromSnapshot <B: Basics>: Stewards <B> {
+  fn from_snapshot <'a, Shot: SuperSnapshot <Basics = B>> (snapshot: &'a Shot)->Self where &'a Shot: IntoIterator <Item = SnapshotEntry <'a, B>>;
+}
+pub trait IncrementalStewards <B: Basics>: Stewards <B> {
+  fn step (&mut self, index: usize);
+  fn updated_until_before (&self)->Vec <Option <B::Time>>;
+}
+pub trait IterableStewards <B: Basics>: Stewards <B> {
+  fn all_fields <'a> (snapshots: &'a Self::Snapshots)->Vec <HashMap <FieldId, (&'a FieldRc, &'a ExtendedTime <B>)>>;
+  /// The entries of Steward0's snapshot, in the order it iterates them.
+  fn first_entries <'a> (snapshots: &'a Self::Snapshots)->Vec <SnapshotEntry <'a, B>>;
+}
+pub trait ChecksummedStewards <B: Basics>: Stewards <B> {
+  fn begin_checks (&mut self, start: B::Time, stride: B::Time);
+  fn checksums (&mut self, chunk: i64)->Vec <u64>;
+  fn debug_dumps (&self, chunk: i64)->Vec <BTreeMap <ExtendedTime <B>, u64>>;
+  fn event_details (&self, index: usize, time: &ExtendedTime <B>)->String;
+}
+
+macro_rules! crossverified_stewards {
+  ($($Steward: ident $index: tt),*) => {
+    impl<B: Basics, $($Steward: TimeSteward <Basics = B>),*> Stewards <B> for ($($Steward,)*) {
+      type Snapshots = ($($Steward::Snapshot,)*);
+      fn valid_since (&self)->Vec <ValidSince <B::Time>> {
+        vec![$(self.$index.valid_since()),*]
+      }
+      fn insert_fiat_event <E: Event <Basics = B>> (&mut self, time: B::Time, id: DeterministicRandomId, event: E)->Vec <Result <(), FiatEventOperationError>> {
+        vec![$(self.$index.insert_fiat_event (time.clone(), id, event.clone())),*]
+      }
+      fn remove_fiat_event (&mut self, time: &B::Time, id: DeterministicRandomId)->Vec <Result <(), FiatEventOperationError>> {
+        vec![$(self.$index.remove_fiat_event (time, id)),*]
+      }
+      fn snapshot_before (&mut self, time: &B::Time)->Result <Self::Snapshots, usize> {
+        Ok (($(match self.$index.snapshot_before (time) {
+          Some (snapshot) => snapshot,
+          None => return Err ($index),
+        },)*))
+      }
+      fn scheduled_events (&self, start: &B::Time, end: &B::Time)->Vec <Vec <ScheduledEvent <B>>> {
+        vec![$(self.$index.scheduled_events (start, end)),*]
+      }
+      fn lists_scheduled_events (&self)->Vec <bool> {
+        vec![$(self.$index.lists_scheduled_events()),*]
+      }
+      fn field <'a> (snapshots: &'a Self::Snapshots, id: FieldId)->Vec <Option <(&'a FieldRc, &'a ExtendedTime <B>)>> {
+        vec![$(snapshots.$index.generic_data_and_extended_last_change (id)),*]
+      }
+      fn constants (snapshots: &Self::Snapshots)->&B::Constants {
+        // constants methods are usually implemented trivially; we don't bother checking them.
+        // Since the user only gives you one set of constants, it's hard to return a wrong value
+        snapshots.0.constants()
+      }
+      fn now (snapshots: &Self::Snapshots)->Vec <&B::Time> {
+        vec![$(snapshots.$index.unsafe_now()),*]
+      }
+      fn num_fields (snapshots: &Self::Snapshots)->Vec <usize> {
+        vec![$(snapshots.$index.num_fields()),*]
+      }
+    }
+
+    impl<B: Basics, $($Steward: TimeStewardFromConstants <Basics = B>),*> StewardsFromConstants <B> for ($($Steward,)*) {
+      fn from_constants (constants: B::Constants)->Self {
+        ($($Steward::from_constants (constants.clone()),)*)
+      }
+    }
+
+    impl<B: Basics, $($Steward: TimeStewardFromSnapshot <Basics = B>),*> StewardsFromSnapshot <B> for ($($Steward,)*) {
+      fn from_snapshot <'a, Shot: SuperSnapshot <Basics = B>> (snapshot: &'a Shot)->Self where &'a Shot: IntoIterator <Item = SnapshotEntry <'a, B>> {
+        ($($Steward::from_snapshot::<'a, Shot> (snapshot),)*)
+      }
+    }
+
+    impl<B: Basics, $($Steward: IncrementalTimeSteward <Basics = B>),*> IncrementalStewards <B> for ($($Steward,)*) {
+      fn step (&mut self, index: usize) {
+        match index {
+          $($index => self.$index.step(),)*
+          _ => panic! ("there is no Steward{}", index),
+        }
+      }
+      fn updated_until_before (&self)->Vec <Option <B::Time>> {
+        vec![$(self.$index.updated_until_before()),*]
+      }
+    }
+
+    impl<B: Basics, $($Steward: TimeSteward <Basics = B>),*> IterableStewards <B> for ($($Steward,)*)
+    where $(for <'a> &'a $Steward::Snapshot: IntoIterator <Item = SnapshotEntry <'a, B>>),* {
+      fn all_fields <'a> (snapshots: &'a Self::Snapshots)->Vec <HashMap <FieldId, (&'a FieldRc, &'a ExtendedTime <B>)>> {
+        vec![$((&snapshots.$index).into_iter().collect()),*]
+      }
+      fn first_entries <'a> (snapshots: &'a Self::Snapshots)->Vec <SnapshotEntry <'a, B>> {
+        (&snapshots.0).into_iter().collect()
+      }
+    }
+
+    impl<B: Basics, $($Steward: SimpleSynchronizableTimeSteward <Basics = B>),*> ChecksummedStewards <B> for ($($Steward,)*) {
+      fn begin_checks (&mut self, start: B::Time, stride: B::Time) {
+        $(self.$index.begin_checks (start.clone(), stride.clone());)*
+      }
+      fn checksums (&mut self, chunk: i64)->Vec <u64> {
+        vec![$(self.$index.checksum (chunk)),*]
+      }
+      fn debug_dumps (&self, chunk: i64)->Vec <BTreeMap <ExtendedTime <B>, u64>> {
+        vec![$(self.$index.debug_dump (chunk)),*]
+      }
+      fn event_details (&self, index: usize, time: &ExtendedTime <B>)->String {
+        match index {
+          $($index => self.$index.event_details (time),)*
+          _ => panic! ("there is no Steward{}", index),
+        }
+      }
+    }
+
+    impl<B: Basics, $($Steward: FullTimeSteward <Basics = B>),*> FullTimeSteward for Many <B, ($($Steward,)*)> {}
+    impl<B: Basics, $($Steward: CanonicalTimeSteward <Basics = B>),*> CanonicalTimeSteward for Many <B, ($($Steward,)*)> {}
+  }
+}
+
+crossverified_stewards! (Steward0 0, Steward1 1);
+crossverified_stewards! (Steward0 0, Steward1 1, Steward2 2);
+crossverified_stewards! (Steward0 0, Steward1 1, Steward2 2, Steward3 3);
+crossverified_stewards! (Steward0 0, Steward1 1, Steward2 2, Steward3 3, Steward4 4);
+crossverified_stewards! (Steward0 0, Steward1 1, Steward2 2, Steward3 3, Steward4 4, Steward5 5);
+
+
+/// One field that the stewards disagreed about.
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct FieldDifference <B: Basics> {
+  pub id: FieldId,
+  /// For each steward, in order: the field's data (as JSON) and its last change, which identifies the event that wrote it.
+  /// None if the field doesn't exist in that steward's snapshot.
+  pub versions: Vec <Option <(String, ExtendedTime <B>)>>,
+}
+
+/// A report that the stewards' snapshots disagreed.
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct Divergence <B: Basics> {
+  /// The time of the snapshot where the disagreement was noticed.
+  pub now: B::Time,
+  pub fields: Vec <FieldDifference <B>>,
+}
+
+/// The earliest event that ran differently in different stewards, or only ran in some of them.
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct EventDivergence <B: Basics> {
+  pub chunk: i64,
+  pub time: ExtendedTime <B>,
+  /// For each steward, in order: its event_details() for the event, or None if the event didn't run in that steward.
+  pub details: Vec <Option <String>>,
+}
+
+impl <B: Basics> fmt::Display for EventDivergence <B> {
+  fn fmt (&self, f: &mut fmt::Formatter)->fmt::Result {
+    try! (write! (f, "the stewards first diverged at {:?}, in chunk {}", self.time, self.chunk));
+    for (index, details) in self.details.iter().enumerate() {
+      match *details {
+        Some (ref details) => try! (write! (f, "\n  Steward{}: {}", index, details)),
+        None => try! (write! (f, "\n  Steward{}: the event did not run", index)),
+      }
+    }
+    Ok (())
+  }
+}
+
+impl <B: Basics> fmt::Display for Divergence <B> {
+  fn fmt (&self, f: &mut fmt::Formatter)->fmt::Result {
+    try! (write! (f, "snapshots at {:?} disagreed about {} fields; one or more of the stewards is buggy, or the caller submitted very nondeterministic event/predictor types", self.now, self.fields.len()));
+    for field in self.fields.iter() {
+      try! (write! (f, "\n{:?}:", field.id));
+      for (index, version) in field.versions.iter().enumerate() {
+        match *version {
+          Some ((ref data, ref changed)) => try! (write! (f, "\n  Steward{}: {}\n    written by the event at {:?}", index, data, changed)),
+          None => try! (write! (f, "\n  Steward{}: does not exist", index)),
+        }
+      }
+    }
+    Ok (())
+  }
+}
+
+fn describe_field <B: Basics> (id: FieldId, data: &FieldRc)->String {
+  match field_to_json::<B> (id.column_id, data) {
+    Ok (json) => json.to_string(),
+    Err (error) => format! ("(couldn't describe the field: {})", error),
+  }
+}
+
+fn field_difference <B: Basics> (id: FieldId, versions: &[Option <(&FieldRc, &ExtendedTime <B>)>])->Option <FieldDifference <B>> {
+  let first = versions [0];
+  let same = versions.iter().all (| version | match (first, *version) {
+    (None, None) => true,
+    (Some (first), Some (version)) => first.1 == version.1 && fields_are_equal::<B> (id.column_id, first.0, version.0),
+    _ => false,
+  });
+  if same {
+    return None;
+  }
+  Some (FieldDifference {
+    id: id,
+    versions: versions.iter().map (| version | version.map (| (data, changed) | (describe_field::<B> (id, data), changed.clone()))).collect(),
+  })
+}
+
+fn agreed_result (results: Vec <Result <(), FiatEventOperationError>>, operation: &str)->Result <(), FiatEventOperationError> {
+  for (index, result) in results.iter().enumerate() {
+    if *result == Err (FiatEventOperationError::InvalidTime) {
+      panic! ("Steward{} returned InvalidTime after its own ValidSince", index);
+    }
+  }
+  if results.iter().any (| result | *result != results [0]) {
+    panic! ("stewards returned different results for {} ({:?}); I believe this is ALWAYS a bug in one of the stewards (that is, it cannot be caused by invalid input)", operation, results);
+  }
+  results [0]
+}
+
+fn assert_valid_since_unchanged <Time: Ord> (old: Vec <ValidSince <Time>>, new: Vec <ValidSince <Time>>) {
+  for (index, (old, new)) in old.into_iter().zip (new.into_iter()).enumerate() {
+    assert! (old == new, "Steward{} broke the ValidSince rules", index);
+  }
+}
+
+
+pub struct Many <B: Basics, List: Stewards <B>> (
+  List,
   PhantomData <B::Constants>,
+  // The latest time that every steward was brought up to by snapshot_before(),
+  // or None if something has happened since then that may have moved them apart.
+  // Their scheduled events can only be compared while they are all at the same time.
+  Option <B::Time>,
 );
-pub struct Snapshot<B: Basics, Steward0: TimeSteward<Basics = B>, Steward1: TimeSteward<Basics = B> > (
-  <Steward0 as TimeSteward>::Snapshot,
-  <Steward1 as TimeSteward>::Snapshot,
+pub struct ManySnapshot <B: Basics, List: Stewards <B>> (
+  List::Snapshots,
+  PhantomData <B::Constants>,
 );
 
+pub type Steward <B, Steward0, Steward1> = Many <B, (Steward0, Steward1)>;
+pub type Snapshot <B, Steward0, Steward1> = ManySnapshot <B, (Steward0, Steward1)>;
+
+impl <B: Basics, List: Stewards <B>> Many <B, List> {
+  /// The stewards being verified.
+  pub fn stewards (&self)->&List {
+    &self.0
+  }
+}
+
+impl <B: Basics, List: ChecksummedStewards <B>> Many <B, List> {
+  /// Starts recording event checksums in chunks, like simply_synchronized does. This must be called
+  /// before any fiat events are inserted, for first_divergence() to work.
+  pub fn begin_checks (&mut self, start: B::Time, stride: B::Time) {
+    self.0.begin_checks (start, stride);
+  }
+
+  /// Compares the stewards' event checksums chunk by chunk, up to and including `last_chunk`,
+  /// and reports the earliest event that differed.
+  ///
+  /// Each steward is brought up to date through each chunk first, which can take a while.
+  pub fn first_divergence (&mut self, last_chunk: i64)->Option <EventDivergence <B>> {
+    for chunk in 0..last_chunk + 1 {
+      let checksums = self.0.checksums (chunk);
+      if checksums.iter().all (| checksum | *checksum == checksums [0]) {
+        continue;
+      }
+      let dumps = self.0.debug_dumps (chunk);
+      let times: BTreeSet <&ExtendedTime <B>> = dumps.iter().flat_map (| dump | dump.keys()).collect();
+      for time in times {
+        let versions: Vec <Option <&u64>> = dumps.iter().map (| dump | dump.get (time)).collect();
+        if versions.iter().all (| version | *version == versions [0]) {
+          continue;
+        }
+        return Some (EventDivergence {
+          chunk: chunk,
+          time: time.clone(),
+          details: versions.iter().enumerate().map (| (index, version) | version.map (| _ | self.0.event_details (index, time))).collect(),
+        });
+      }
+      // the chunk checksums differed even though every event matched, so there's no event to point to;
+      // keep looking, in case a later chunk has one
+    }
+    None
+  }
+}
 
-impl<B: Basics, Steward0: TimeSteward<Basics = B>, Steward1: TimeSteward<Basics = B> > ::Accessor for Snapshot<B, Steward0, Steward1> {
+impl<B: Basics, List: Stewards <B>> ::Accessor for ManySnapshot <B, List> {
   type Basics = B;
-// macro_rules! forward_snapshot_method ($method: ident ($self, $($argument_name: ident: $argument_type:ty),*)->$return_type:ty
-// TODO: forward all the methods properly
-// and check equality by serialization
   fn generic_data_and_extended_last_change (&self, id: FieldId)->Option <(& FieldRc, & ExtendedTime <B>)> {
-    match (
-      self.0.generic_data_and_extended_last_change (id),
-      self.1.generic_data_and_extended_last_change (id)
-    ) {
-      (None, None) => None,
-      (Some (value_0), Some (value_1)) => {
-        assert_eq!(value_0.1, value_1.1, "Snapshots returned different last change times for the same field; one or both of the stewards is buggy, or the caller submitted very nondeterministic event/predictor types");
-        assert!(fields_are_equal::<B> (id.column_id, value_0.0, value_1.0), "Snapshots returned the same field with the same last change times but different data; one or both of the stewards is buggy, or the caller submitted very nondeterministic event/predictor types");
-        Some (value_0)
-      },
-      _=> panic! ("One snapshot returned a value and the other didn't; one or both of the stewards is buggy, or the caller submitted very nondeterministic event/predictor types")
+    let versions = List::field (&self.0, id);
+    if let Some (difference) = field_difference (id, &versions) {
+      panic! ("{}", Divergence {now: self.unsafe_now().clone(), fields: vec![difference]});
     }
+    versions [0]
   }
   fn constants(&self) -> &B::Constants {
-// constants methods are usually implemented trivially; we don't bother checking them.
-// Since the user only gives you one set of constants, it's hard to return a wrong value
-    self.0.constants()
+    List::constants (&self.0)
   }
   fn unsafe_now(&self) -> &B::Time {
-    let result = (self.0.unsafe_now(), self.1.unsafe_now());
-    assert! (result.0 == result.1, "Snapshots returned different times; this is an egregious bug!");
-    result.0
+    let result = List::now (&self.0);
+    assert! (result.iter().all (| now | *now == result [0]), "Snapshots returned different times; this is an egregious bug!");
+    result [0]
   }
 }
 
-impl<B: Basics, Steward0: TimeSteward<Basics = B>, Steward1: TimeSteward<Basics = B> > ::MomentaryAccessor for Snapshot<B, Steward0, Steward1> {}
+impl<B: Basics, List: Stewards <B>> ::MomentaryAccessor for ManySnapshot <B, List> {}
 
-impl<B: Basics, Steward0: TimeSteward<Basics = B>, Steward1: TimeSteward<Basics = B> > ::Snapshot for Snapshot<B, Steward0, Steward1> {
+impl<B: Basics, List: Stewards <B>> ::Snapshot for ManySnapshot <B, List> {
   fn num_fields(&self) -> usize {
-    assert_eq!(self.0.num_fields(), self.1.num_fields());
-    self.0.num_fields()
+    let result = List::num_fields (&self.0);
+    assert! (result.iter().all (| count | *count == result [0]), "Snapshots had different numbers of fields ({:?}); use divergence() to find out which", result);
+    result [0]
   }
 }
 
-pub struct SnapshotIter <'a, B: Basics, Steward0: TimeSteward<Basics = B> + 'a, Steward1: TimeSteward<Basics = B> + 'a>
-where & 'a Steward0::Snapshot: IntoIterator <Item = ::SnapshotEntry <'a, B>>,
-& 'a Steward1::Snapshot: IntoIterator <Item = ::SnapshotEntry <'a, B>>
-{
-  iter: <& 'a Steward0::Snapshot as IntoIterator>::IntoIter,
-  #[allow (dead_code)]
-  snapshot: & 'a Snapshot <B, Steward0, Steward1>,
-}
-impl <'a, B: Basics, Steward0: TimeSteward<Basics = B>, Steward1: TimeSteward<Basics = B>> Iterator for SnapshotIter<'a, B, Steward0, Steward1>
-where & 'a Steward0::Snapshot: IntoIterator <Item = ::SnapshotEntry <'a, B>>,
-& 'a Steward1::Snapshot: IntoIterator <Item = ::SnapshotEntry <'a, B>> {
-  type Item = (FieldId, (& 'a FieldRc, & 'a ExtendedTime <B>));
-  fn next (&mut self)->Option <Self::Item> {
-    self. iter.next()
+impl <B: Basics, List: IterableStewards <B>> ManySnapshot <B, List> {
+  /// Compares every field of every steward's snapshot, and describes all the differences, instead of panicking.
+  pub fn divergence (&self)->Option <Divergence <B>> {
+    self.divergence_among (&List::all_fields (&self.0))
   }
-  fn size_hint (&self)->(usize, Option <usize>) {self. iter.size_hint()}
-}
-impl <'a, B: Basics, Steward0: TimeSteward<Basics = B>, Steward1: TimeSteward<Basics = B>> IntoIterator for & 'a Snapshot <B, Steward0, Steward1>
-where & 'a Steward0::Snapshot: IntoIterator <Item = ::SnapshotEntry <'a, B>>,
-& 'a Steward1::Snapshot: IntoIterator <Item = ::SnapshotEntry <'a, B>>,
 
- {
-  type Item = (FieldId, (& 'a FieldRc, & 'a ExtendedTime <B>));
-  type IntoIter = SnapshotIter <'a, B, Steward0, Steward1>;
-  fn into_iter (self)->Self::IntoIter {
-    assert_eq! (self.0.num_fields(), self.1.num_fields());
-    let mut fields = HashMap::new();
-    for (id, data) in & self.0 {
-      fields.insert (id, (data.0.clone(), data.1.clone()));
+  fn divergence_among (&self, all: &[HashMap <FieldId, (&FieldRc, &ExtendedTime <B>)>])->Option <Divergence <B>> {
+    let mut ids: Vec <FieldId> = all.iter().flat_map (| fields | fields.keys().cloned()).collect::<HashSet <_>>().into_iter().collect();
+    ids.sort_by_key (| id | (id.row_id, id.column_id));
+    let fields: Vec <FieldDifference <B>> = ids.into_iter().filter_map (| id | {
+      let versions: Vec <_> = all.iter().map (| fields | fields.get (&id).cloned()).collect();
+      field_difference (id, &versions)
+    }).collect();
+    if fields.is_empty() {
+      None
     }
-    for (id, data) in & self.1 {
-      let other_data = fields.get (& id).expect ("field existed in Steward1 snapshot but not Steward0 snapshot");
-      assert_eq!(*data .1, other_data .1, "Snapshots returned different last change times for the same field; one or both of the stewards is buggy, or the caller submitted very nondeterministic event/predictor types");
-      assert!(fields_are_equal::<B> (id.column_id, data .0, & other_data .0), "Snapshots returned the same field with the same last change times but different data; one or both of the stewards is buggy, or the caller submitted very nondeterministic event/predictor types");
+    else {
+      Some (Divergence {now: self.unsafe_now().clone(), fields: fields})
     }
-    SnapshotIter:: <'a, B, Steward0, Steward1> {
-      iter: (& self.0).into_iter(),
-      snapshot: self
+  }
+}
+
+impl <'a, B: Basics, List: IterableStewards <B>> IntoIterator for & 'a ManySnapshot <B, List> {
+  type Item = SnapshotEntry <'a, B>;
+  type IntoIter = ::std::vec::IntoIter <SnapshotEntry <'a, B>>;
+  fn into_iter (self)->Self::IntoIter {
+    if let Some (divergence) = self.divergence_among (&List::all_fields (&self.0)) {
+      panic! ("{}", divergence);
     }
+    List::first_entries (&self.0).into_iter()
   }
 }
 
-impl<B: Basics, Steward0: TimeSteward<Basics = B> , Steward1: TimeSteward<Basics = B> > TimeSteward for Steward<B, Steward0, Steward1> {
+impl<B: Basics, List: Stewards <B>> TimeSteward for Many <B, List> {
   type Basics = B;
-  type Snapshot = Snapshot<B, Steward0, Steward1>;
+  type Snapshot = ManySnapshot <B, List>;
 
   fn valid_since(&self) -> ValidSince<B::Time> {
-    max (self.0.valid_since(), self.1.valid_since())
+    self.0.valid_since().into_iter().max().unwrap()
   }
-  
+
   fn insert_fiat_event <E: ::Event <Basics = B>> (&mut self,
                        time: B::Time,
                        id: DeterministicRandomId,
                        event: E)
                        -> Result<(), FiatEventOperationError> {
     time_steward_common_insert_fiat_event_prefix!(B, self, time, E);
-    let old_valid_since = (self.0.valid_since(), self.1.valid_since());
-    let result = match (
-      self.0.insert_fiat_event (time.clone(), id, event.clone()),
-      self.1.insert_fiat_event (time, id, event)
-    ){
-      (Ok (()), Ok (())) => Ok (()),
-      (Err (FiatEventOperationError::InvalidTime),_) => panic!("Steward0 returned InvalidTime after its own ValidSince"),
-      (_,Err (FiatEventOperationError::InvalidTime)) => panic!("Steward1 returned InvalidTime after its own ValidSince"),
-      (Err (FiatEventOperationError::InvalidInput),Err (FiatEventOperationError::InvalidInput)) => Err (FiatEventOperationError::InvalidInput),
-      _=> panic!("stewards returned different results for insert_fiat_event; I believe this is ALWAYS a bug in one of the stewards (that is, it cannot be caused by invalid input)"),
-    };
-    assert!(self .0.valid_since() == old_valid_since.0, "Steward0 broke the ValidSince rules");
-    assert!(self .1.valid_since() == old_valid_since.1, "Steward1 broke the ValidSince rules");
+    let old_valid_since = self.0.valid_since();
+    self.2 = None;
+    let result = agreed_result (self.0.insert_fiat_event (time, id, event), "insert_fiat_event");
+    assert_valid_since_unchanged (old_valid_since, self.0.valid_since());
     result
   }
   fn remove_fiat_event(&mut self,
@@ -141,19 +413,10 @@ impl<B: Basics, Steward0: TimeSteward<Basics = B> , Steward1: TimeSteward<Basics
     if self.valid_since() > *time {
       return Err(FiatEventOperationError::InvalidTime);
     }
-    let old_valid_since = (self.0.valid_since(), self.1.valid_since());
-    let result = match (
-      self.0.remove_fiat_event (time, id),
-      self.1.remove_fiat_event (time, id)
-    ){
-      (Ok (()), Ok (())) => Ok (()),
-      (Err (FiatEventOperationError::InvalidTime),_) => panic!("Steward0 returned InvalidTime after its own ValidSince"),
-      (_,Err (FiatEventOperationError::InvalidTime)) => panic!("Steward1 returned InvalidTime after its own ValidSince"),
-      (Err (FiatEventOperationError::InvalidInput),Err (FiatEventOperationError::InvalidInput)) => Err (FiatEventOperationError::InvalidInput),
-      _=> panic!("stewards returned different results for insert_fiat_event; I believe this is ALWAYS a bug in one of the stewards (that is, it cannot be caused by invalid input)"),
-    };
-    assert!(self .0.valid_since() == old_valid_since.0, "Steward0 broke the ValidSince rules");
-    assert!(self .1.valid_since() == old_valid_since.1, "Steward1 broke the ValidSince rules");
+    let old_valid_since = self.0.valid_since();
+    self.2 = None;
+    let result = agreed_result (self.0.remove_fiat_event (time, id), "remove_fiat_event");
+    assert_valid_since_unchanged (old_valid_since, self.0.valid_since());
     result
   }
 
@@ -161,93 +424,93 @@ impl<B: Basics, Steward0: TimeSteward<Basics = B> , Steward1: TimeSteward<Basics
     if self.valid_since() > *time {
       return None;
     }
-    let result = match (
-      self.0.snapshot_before (time),
-      self.1.snapshot_before (time)
-    ) {
-      (Some (snapshot_0), Some (snapshot_1)) => Some (Snapshot (snapshot_0, snapshot_1)),
-      (None, _) => panic! ("Steward0 failed to return a snapshot at a time it claims to be valid"),
-      (_, None) => panic! ("Steward1 failed to return a snapshot at a time it claims to be valid"),
+    let result = match self.0.snapshot_before (time) {
+      Ok (snapshots) => ManySnapshot (snapshots, PhantomData),
+      Err (index) => panic! ("Steward{} failed to return a snapshot at a time it claims to be valid", index),
     };
-    assert!(self .0.valid_since() <*time, "Steward0 broke the ValidSince rules");
-    assert!(self .1.valid_since() <*time, "Steward1 broke the ValidSince rules");
-    result
+    for (index, valid_since) in self.0.valid_since().into_iter().enumerate() {
+      assert!(valid_since < *time, "Steward{} broke the ValidSince rules", index);
+    }
+    if self.2.as_ref().map_or (true, | synchronized | time > synchronized) {
+      self.2 = Some (time.clone());
+    }
+    Some (result)
+  }
+  /// Returns the events of the first steward that can list them.
+  ///
+  /// Stewards are allowed to differ in how far ahead they have predicted, and in whether they remember
+  /// executed events, so not everything can be verified. If all stewards were brought up to the same time
+  /// by the last snapshot_before(), nothing has happened since, and the window starts no later than that time,
+  /// they must agree about the events from that time up to and including the first predicted event
+  /// (after which, executing that event may change the predictions). Otherwise, the list is returned unchecked.
+  fn scheduled_events (&self, start: &B::Time, end: &B::Time)->Vec <ScheduledEvent <B>> {
+    let capable = self.0.lists_scheduled_events();
+    let lists: Vec <Vec <ScheduledEvent <B>>> = self.0.scheduled_events (start, end).into_iter().zip (capable.iter())
+      .filter (| &(_, capable) | *capable).map (| (list, _) | list).collect();
+    if lists.is_empty() {
+      return Vec::new();
+    }
+    if let Some (synchronized) = self.2.as_ref().and_then (| synchronized | if *start <= *synchronized {Some (synchronized)} else {None}) {
+      let horizon = lists.iter().filter_map (| list | list.iter().find (| event | event.time.base >= *synchronized && event.source != EventSource::Fiat).map (| event | event.time.base.clone())).min();
+      let comparable = | list: &Vec <ScheduledEvent <B>> | list.iter().filter (| event | event.time.base >= *synchronized && horizon.as_ref().map_or (true, | horizon | event.time.base <= *horizon)).cloned().collect::<Vec <_>>();
+      let expected = comparable (&lists [0]);
+      for (index, list) in lists.iter().enumerate().skip (1) {
+        let found = comparable (list);
+        assert! (found == expected, "stewards listed different scheduled events after {:?}: the first capable steward listed {:?}, but capable steward #{} listed {:?}", synchronized, expected, index, found);
+      }
+    }
+    lists.into_iter().next().unwrap()
+  }
+  fn lists_scheduled_events (&self)->bool {
+    self.0.lists_scheduled_events().into_iter().any (| capable | capable)
   }
 }
 
-impl<B: Basics, Steward0: TimeStewardFromConstants <Basics = B> , Steward1: TimeStewardFromConstants <Basics = B> > TimeStewardFromConstants for Steward<B, Steward0, Steward1> {
+impl<B: Basics, List: StewardsFromConstants <B>> TimeStewardFromConstants for Many <B, List> {
   fn from_constants(constants: B::Constants) -> Self {
-    let result = Steward::<B, Steward0, Steward1> (
-      Steward0::from_constants(constants.clone()),
-      Steward1::from_constants(constants),
-      PhantomData,
-    );
-    assert!(result.0.valid_since() == ValidSince::TheBeginning, "Steward0 broke the ValidSince rules");
-    assert!(result.1.valid_since() == ValidSince::TheBeginning, "Steward1 broke the ValidSince rules");
+    let result = Many (List::from_constants (constants), PhantomData, None);
+    for (index, valid_since) in result.0.valid_since().into_iter().enumerate() {
+      assert!(valid_since == ValidSince::TheBeginning, "Steward{} broke the ValidSince rules", index);
+    }
     result
   }
 }
-impl<B: Basics, Steward0: TimeStewardFromSnapshot <Basics = B> , Steward1: TimeStewardFromSnapshot <Basics = B> > TimeStewardFromSnapshot for Steward<B, Steward0, Steward1> {
+impl<B: Basics, List: StewardsFromSnapshot <B>> TimeStewardFromSnapshot for Many <B, List> {
   fn from_snapshot<'a, S: ::Snapshot<Basics = B>>(snapshot: & 'a S)
                                               -> Self
                                               where & 'a S: IntoIterator <Item = ::SnapshotEntry <'a, B>> {
-    let result = Steward (
-      Steward0::from_snapshot::<'a, S>(snapshot),
-      Steward1::from_snapshot::<'a, S>(snapshot),
-      PhantomData,
-    );
-    assert!(result.0.valid_since() == ValidSince::Before (snapshot.now().clone()), "Steward0 broke the ValidSince rules");
-    assert!(result.1.valid_since() == ValidSince::Before (snapshot.now().clone()), "Steward1 broke the ValidSince rules");
+    let result = Many (List::from_snapshot::<'a, S> (snapshot), PhantomData, None);
+    for (index, valid_since) in result.0.valid_since().into_iter().enumerate() {
+      assert!(valid_since == ValidSince::Before (snapshot.now().clone()), "Steward{} broke the ValidSince rules", index);
+    }
     result
   }
 }
 
 
-impl<B: Basics, Steward0: IncrementalTimeSteward <Basics = B>, Steward1: IncrementalTimeSteward <Basics = B>> ::IncrementalTimeSteward for Steward<B, Steward0, Steward1> {
+impl<B: Basics, List: IncrementalStewards <B>> ::IncrementalTimeSteward for Many <B, List> {
   fn step(&mut self) {
-    let updated_0 = self.0.updated_until_before();
-    let updated_1 = self.1.updated_until_before();
-    if let Some (time_0) = updated_0 {
-      if updated_1.as_ref().map_or (true, | time_1 | time_0 < *time_1) {
-// println!("stepping 0");
-        let old_valid_since = self.0.valid_since();
-        let strict = old_valid_since > time_0;
-        self.0.step();
-        let new_valid_since = self.0.valid_since();
-        assert!(new_valid_since <= old_valid_since || new_valid_since <= ValidSince::After (time_0), "Steward0 broke the ValidSince rules");
-        if strict { assert!(new_valid_since <= old_valid_since, "Steward0 broke the ValidSince rules"); }
-        return;
-      }
-    }
-    if let Some (time_1) = updated_1 {
-// println!("stepping 1");
-      let old_valid_since = self.1.valid_since();
-      let strict = old_valid_since > time_1;
-      self.1.step();
-      let new_valid_since = self.1.valid_since();
-      assert!(new_valid_since <= old_valid_since || new_valid_since <= ValidSince::After (time_1), "Steward1 broke the ValidSince rules");
-      if strict { assert!(new_valid_since <= old_valid_since, "Steward1 broke the ValidSince rules"); }
+    // step whichever steward is furthest behind
+    let mut furthest_behind: Option <(usize, B::Time)> = None;
+    for (index, updated) in self.0.updated_until_before().into_iter().enumerate() {
+      if let Some (time) = updated {
+        if furthest_behind.as_ref().map_or (true, | &(_, ref earliest) | time < *earliest) {
+          furthest_behind = Some ((index, time));
+        }
+      }
+    }
+    if let Some ((index, time)) = furthest_behind {
+// println!("stepping {}", index);
+      let old_valid_since = self.0.valid_since() [index].clone();
+      let strict = old_valid_since > time;
+      self.2 = None;
+      self.0.step (index);
+      let new_valid_since = self.0.valid_since() [index].clone();
+      assert!(new_valid_since <= old_valid_since || new_valid_since <= ValidSince::After (time), "Steward{} broke the ValidSince rules", index);
+      if strict { assert!(new_valid_since <= old_valid_since, "Steward{} broke the ValidSince rules", index); }
     }
   }
   fn updated_until_before (&self)->Option <B::Time> {
-    match (self.0.updated_until_before(), self.1.updated_until_before()) {
-      (None, None) => None,
-      (Some (time_0), None) => {
-        Some (time_0)
-      },
-      (None, Some (time_1)) => {
-        Some (time_1)
-      },
-      (Some (time_0), Some (time_1)) => {
-        if time_0 <= time_1 {
-          Some (time_0)
-        } else {
-          Some (time_1)
-        }
-      },
-    }
+    self.0.updated_until_before().into_iter().filter_map (| time | time).min()
   }
 }
-
-impl<B: Basics, Steward0: FullTimeSteward <Basics = B>, Steward1: FullTimeSteward <Basics = B>> FullTimeSteward for Steward<B, Steward0, Steward1> {}
-impl<B: Basics, Steward0: CanonicalTimeSteward <Basics = B>, Steward1: CanonicalTimeSteward <Basics = B>> CanonicalTimeSteward for Steward<B, Steward0, Steward1> {}
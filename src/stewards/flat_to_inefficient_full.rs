@@ -5,7 +5,7 @@
 
 
 use {DeterministicRandomId, Basics, TimeSteward, TimeStewardFromConstants, TimeStewardFromSnapshot, FiatEventOperationError, ValidSince,
-     MomentaryAccessor, FiatSnapshot};
+     MomentaryAccessor, FiatSnapshot, ScheduledEvent};
 use std::collections::HashMap;
 
 pub struct Steward<B: Basics, Steward0: TimeStewardFromConstants + TimeStewardFromSnapshot <Basics = B>> {
@@ -91,6 +91,15 @@ impl<B: Basics, Steward0: TimeStewardFromConstants + TimeStewardFromSnapshot <Ba
       .expect("reloading from an earlier snapshot was supposed to make this work!"));
     self.steward.snapshot_before(time)
   }
+
+  fn scheduled_events(&self, start: &B::Time, end: &B::Time) -> Vec<ScheduledEvent<B>> {
+    // The wrapped steward forgets events once it executes them, even though we can go back
+    // and execute them again. That's consistent with what it would report if it were used directly.
+    self.steward.scheduled_events(start, end)
+  }
+  fn lists_scheduled_events(&self) -> bool {
+    self.steward.lists_scheduled_events()
+  }
 }
 
 impl<B: Basics, Steward0: TimeStewardFromConstants + TimeStewardFromSnapshot <Basics = B>> TimeStewardFromConstants for Steward<B, Steward0> {
@@ -5,8 +5,8 @@
 //!
 
 
-use ::{DeterministicRandomId, SiphashIdGenerator, RowId, FieldId, Column, ExtendedTime, Basics,
-       TimeSteward, FiatEventOperationError, ValidSince, StewardRc, FieldRc, Accessor};
+use ::{DeterministicRandomId, SiphashIdGenerator, RowId, FieldId, PredictorId, Column, ExtendedTime, Basics,
+       TimeSteward, FiatEventOperationError, ValidSince, StewardRc, FieldRc, Accessor, ScheduledEvent, EventSource};
 use implementation_support::common::{self, Filter, DynamicEventFn};
 use std::collections::{HashMap, BTreeMap};
 use rand::Rng;
@@ -182,13 +182,9 @@ impl<B: Basics> StewardState<B> {
   }
 }
 impl<B: Basics> StewardImpl<B> {
-  fn next_event(&self) -> Option<(ExtendedTime<B>, DynamicEvent<B>)> {
-    let first_fiat_event_iter = self.state
-      .fiat_events
-      .iter()
-      .map(|ev| (ev.0.clone(), ev.1.clone()));
+  fn predicted_events(&self) -> Vec<(ExtendedTime<B>, DynamicEvent<B>, PredictorId, RowId)> {
     let empty = Vec::new();
-    let predicted_events_iter = self.state.field_states.keys().flat_map(|field_id| {
+    self.state.field_states.keys().flat_map(|field_id| {
       let column = field_id.column_id;
       self.settings
         .settings
@@ -221,10 +217,17 @@ impl<B: Basics> StewardImpl<B> {
                                                                        no fields yet?"))
                 .expect("this should only fail if the time was in the past, a case that was \
                          already ruled out");
-            (extended, event)
+            (extended, event, predictor.predictor_id, field_id.row_id)
           })
         })
-    });
+    }).collect()
+  }
+  fn next_event(&self) -> Option<(ExtendedTime<B>, DynamicEvent<B>)> {
+    let first_fiat_event_iter = self.state
+      .fiat_events
+      .iter()
+      .map(|ev| (ev.0.clone(), ev.1.clone()));
+    let predicted_events_iter = self.predicted_events().into_iter().map(|ev| (ev.0, ev.1));
     let events_iter = first_fiat_event_iter.chain(predicted_events_iter);
     events_iter.min_by_key(|ev| ev.0.clone())
   }
@@ -296,6 +299,37 @@ impl<B: Basics> TimeSteward for Steward<B> {
       settings: self.settings.clone(),
     })
   }
+
+  // Like executing an event, this reruns every predictor on every field that exists,
+  // so each call costs about as much as executing an event. Nothing is cached, for the same reason
+  // as everywhere else in this steward: being obviously correct matters more than being fast.
+  fn scheduled_events(&self, start: &B::Time, end: &B::Time) -> Vec<ScheduledEvent<B>> {
+    let fiat_events = self.state.fiat_events.iter().map(|(time, event)| {
+      ScheduledEvent {
+        time: time.clone(),
+        event_id: event.event_id(),
+        source: EventSource::Fiat,
+      }
+    });
+    let predicted_events = self.predicted_events().into_iter().map(|(time, event, predictor_id, row_id)| {
+      ScheduledEvent {
+        time: time,
+        event_id: event.event_id(),
+        source: EventSource::Predicted {
+          predictor_id: predictor_id,
+          row_id: row_id,
+        },
+      }
+    });
+    let mut result: Vec<ScheduledEvent<B>> = fiat_events.chain(predicted_events)
+      .filter(|event| event.time.base >= *start && event.time.base < *end)
+      .collect();
+    result.sort_by(|a, b| a.time.cmp(&b.time));
+    result
+  }
+  fn lists_scheduled_events(&self) -> bool {
+    true
+  }
 }
 
 impl<B: Basics> ::TimeStewardFromConstants for Steward<B> {
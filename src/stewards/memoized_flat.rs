@@ -6,8 +6,9 @@
 
 
 use ::{DeterministicRandomId, SiphashIdGenerator, RowId, FieldId, PredictorId, StewardRc, FieldRc,
-       Accessor, Column, ExtendedTime, Basics, TimeSteward, TimeStewardFromConstants, FiatEventOperationError, ValidSince};
-use implementation_support::common::{self, Filter, DynamicEventFn};
+       Accessor, Column, ExtendedTime, Basics, TimeSteward, TimeStewardFromConstants, FiatEventOperationError, ValidSince,
+       StewardStatistics, StewardAction, StewardObserver, ScheduledEvent, EventSource};
+use implementation_support::common::{self, Filter, DynamicEventFn, StatisticsRecorder};
 use std::collections::{HashMap, BTreeMap, HashSet};
 use std::collections::hash_map::Entry;
 use std::rc::Rc;
@@ -36,6 +37,7 @@ type SnapshotsData<B> = BTreeMap<SnapshotIdx,
 struct Fields<B: Basics> {
   field_states: FieldsMap<B>,
   changed_since_snapshots: SnapshotsData<B>,
+  statistics: StatisticsRecorder,
 }
 
 
@@ -203,14 +205,16 @@ impl<'a, B: Basics> IntoIterator for &'a Snapshot<B> {
 }
 
 impl<B: Basics> Field<B> {
-  fn update_snapshots(&self, my_id: FieldId, snapshots: &SnapshotsData<B>) {
+  fn update_snapshots(&self, my_id: FieldId, snapshots: &SnapshotsData<B>, statistics: &mut StatisticsRecorder) {
     // Old snapshot are already "updated" with all nonexistent values
     for (index, snapshot_map) in snapshots.iter().rev() {
       if *index < self.first_snapshot_not_updated {
         break;
       }
-      snapshot_map.get_default(my_id,
-                               || Some((self.data.clone(), self.last_change.clone())));
+      snapshot_map.get_default(my_id, || {
+        statistics.record(StewardAction::SnapshotUpdated);
+        Some((self.data.clone(), self.last_change.clone()))
+      });
     }
   }
 }
@@ -272,7 +276,7 @@ impl<B: Basics> Fields<B> {
     match self.field_states
       .entry(field_id) {
       Entry::Occupied(mut entry) => {
-        entry.get_mut().update_snapshots(field_id, &self.changed_since_snapshots);
+        entry.get_mut().update_snapshots(field_id, &self.changed_since_snapshots, &mut self.statistics);
         entry.insert(field);
         false
       }
@@ -288,7 +292,7 @@ impl<B: Basics> Fields<B> {
     let removed = self.field_states
       .remove(&field_id);
     if let Some(value) = removed {
-      value.update_snapshots(field_id, &self.changed_since_snapshots);
+      value.update_snapshots(field_id, &self.changed_since_snapshots, &mut self.statistics);
       return true;
     }
     false
@@ -341,6 +345,7 @@ impl<B: Basics> Steward<B> {
 
   fn clear_prediction(&mut self, row_id: RowId, predictor_id: PredictorId) {
     if let Some(prediction) = self.owned.predictions_by_id.remove(&(row_id, predictor_id)) {
+      self.shared.fields.borrow_mut().statistics.record(StewardAction::PredictionDiscarded);
       for field_id in prediction.predictor_accessed.iter() {
         if let Entry::Occupied(mut entry) = self.owned
           .prediction_dependencies
@@ -408,6 +413,7 @@ impl<B: Basics> Steward<B> {
           .map(|event_time| (event_time, event))
       }),
     });
+    self.shared.fields.borrow_mut().statistics.record(StewardAction::PredictionMade);
     self.owned.predictions_by_id.insert((row_id, predictor_id), prediction.clone());
     if let Some((ref time, _)) = prediction.what_will_happen {
       self.owned.predictions_by_time.insert(time.clone(), prediction.clone());
@@ -428,6 +434,7 @@ impl<B: Basics> Steward<B> {
       };
       event(&mut mutator);
       predictions_needed = mutator.predictions_needed;
+      mutator.fields.statistics.record(StewardAction::EventExecuted);
     }
     // if it was a fiat event, clean it up:
     self.owned.fiat_events.remove(&event_time);
@@ -444,6 +451,23 @@ impl<B: Basics> Steward<B> {
       self.execute_event(event_time, event);
     }
   }
+
+  pub fn statistics(&self) -> StewardStatistics {
+    use std::mem::size_of;
+    let fields = self.shared.fields.borrow();
+    let mut result = fields.statistics.statistics.clone();
+    result.history_bytes = fields.field_states.len() * size_of::<Field<B>>() +
+                           self.owned.fiat_events.len() * size_of::<DynamicEvent<B>>();
+    for prediction in self.owned.predictions_by_id.values() {
+      result.history_bytes += size_of::<Prediction<B>>() + prediction.predictor_accessed.len() * size_of::<FieldId>();
+    }
+    result
+  }
+
+  /// Sets an observer to be notified of every action counted by statistics().
+  pub fn set_observer(&mut self, observer: Option<Box<StewardObserver>>) {
+    self.shared.fields.borrow_mut().statistics.observer = observer;
+  }
 }
 
 
@@ -510,6 +534,44 @@ impl<B: Basics> TimeSteward for Steward<B> {
     self.owned.next_snapshot += 1;
     result
   }
+
+  fn scheduled_events(&self, start: &B::Time, end: &B::Time) -> Vec<ScheduledEvent<B>> {
+    let mut result: Vec<ScheduledEvent<B>> = self.owned
+      .fiat_events
+      .iter()
+      .filter(|&(time, _)| time.base >= *start && time.base < *end)
+      .map(|(time, event)| {
+        ScheduledEvent {
+          time: time.clone(),
+          event_id: event.event_id(),
+          source: EventSource::Fiat,
+        }
+      })
+      .chain(self.owned
+        .predictions_by_time
+        .iter()
+        .filter(|&(time, _)| time.base >= *start && time.base < *end)
+        .map(|(time, prediction)| {
+          ScheduledEvent {
+            time: time.clone(),
+            event_id: prediction.what_will_happen
+              .as_ref()
+              .expect("a prediction that predicted nothing was stored in predictions")
+              .1
+              .event_id(),
+            source: EventSource::Predicted {
+              predictor_id: prediction.predictor_id,
+              row_id: prediction.prediction_is_about_row_id,
+            },
+          }
+        }))
+      .collect();
+    result.sort_by(|a, b| a.time.cmp(&b.time));
+    result
+  }
+  fn lists_scheduled_events(&self) -> bool {
+    true
+  }
 }
 
 impl<B: Basics> TimeStewardFromConstants for Steward<B> {
@@ -531,6 +593,7 @@ impl<B: Basics> TimeStewardFromConstants for Steward<B> {
         fields: RefCell::new(Fields {
           field_states: HashMap::default(),
           changed_since_snapshots: BTreeMap::new(),
+          statistics: StatisticsRecorder::default(),
         }),
       }),
     }
@@ -0,0 +1,233 @@
+// A wrapper that writes every fiat event operation to a log,
+// so that a session can be replayed deterministically later
+// (for instance, to reproduce a bug reported by a player).
+//
+// The log is a stream of bincode values: first a header containing
+// the constants and the checksum chunk parameters, then one entry for
+// each successful insert_fiat_event()/remove_fiat_event() call.
+// Whenever the recording is settled, it also writes the checksums
+// of the chunks that became settled, so that replay() can confirm that the
+// replayed simulation is identical to the recorded one.
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::any::Any;
+use std::ops::{Sub, Mul, Div};
+use {ExtendedTime, Basics, TimeSteward, SimpleSynchronizableTimeSteward, DeterministicRandomId,
+     EventId, Event, FiatEventOperationError, ValidSince, ScheduledEvent};
+use bincode;
+use serde::Deserialize;
+use serde::de::Error;
+use implementation_support::list_of_types::event_ids;
+
+
+// serde(deserialize_with is a hacky workaround for https://github.com/rust-lang/rust/issues/41617 (see https://github.com/serde-rs/serde/issues/943)
+#[derive (Clone, Debug, Serialize, Deserialize)]
+pub struct LogHeader<B: Basics> {
+  #[serde(deserialize_with = "Deserialize::deserialize")]
+  pub constants: B::Constants,
+  #[serde(deserialize_with = "Deserialize::deserialize")]
+  pub start: B::Time,
+  #[serde(deserialize_with = "Deserialize::deserialize")]
+  pub stride: B::Time,
+}
+
+#[derive (Clone, Serialize, Deserialize)]
+enum LogEntry<B: Basics> {
+  InsertFiatEvent(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time, #[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId, #[serde(deserialize_with = "Deserialize::deserialize")] EventId, #[serde(deserialize_with = "Deserialize::deserialize")] Vec<u8>),
+  RemoveFiatEvent(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time, #[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId),
+  Checksum(#[serde(deserialize_with = "Deserialize::deserialize")] i64, #[serde(deserialize_with = "Deserialize::deserialize")] u64),
+  Finished,
+}
+
+pub struct Steward<B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>, W: Write> {
+  steward: Steward0,
+  writer: W,
+  start: B::Time,
+  stride: B::Time,
+  valid_since: ValidSince <B::Time>,
+  checksums_recorded: i64,
+}
+
+#[derive (Debug)]
+pub enum ReplayError<B: Basics> {
+  /// The log could not be read.
+  Malformed (bincode::internal::Error),
+  /// A fiat event operation that succeeded during recording was rejected during replay.
+  OperationRejected (B::Time, DeterministicRandomId, FiatEventOperationError),
+  /// The replayed simulation differs from the recorded one.
+  ///
+  /// `events` contains the replayed events in the chunk, with their individual checksums.
+  ChecksumMismatch {chunk: i64, recorded: u64, replayed: u64, events: BTreeMap<ExtendedTime<B>, u64>},
+}
+
+fn write_entry <B: Basics, W: Write> (writer: &mut W, entry: &LogEntry <B>) {
+  bincode::serialize_into (writer, entry, bincode::Infinite).expect ("failed writing to fiat event log");
+  // flush every time, so that the log is still useful if the program crashes
+  writer.flush().expect ("failed writing to fiat event log");
+}
+
+impl <B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>, W: Write> Steward <B, Steward0, W>
+where B::Time: Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>
+{
+  pub fn new (constants: B::Constants, start: B::Time, stride: B::Time, mut writer: W)->Self {
+    bincode::serialize_into (&mut writer, &LogHeader::<B> {constants: constants.clone(), start: start.clone(), stride: stride.clone()}, bincode::Infinite).expect ("failed writing to fiat event log");
+    let mut steward: Steward0 = Steward0::from_constants (constants);
+    steward.begin_checks (start.clone(), stride.clone());
+    Steward {
+      steward: steward,
+      writer: writer,
+      start: start, stride: stride,
+      valid_since: ValidSince::TheBeginning,
+      checksums_recorded: 0,
+    }
+  }
+
+  /// Promises that no more fiat events will be inserted or removed before the given time,
+  /// and records the checksums of all chunks that are now completely settled.
+  pub fn settle_before (&mut self, time: B::Time) {
+    if self.valid_since() > time {
+      return;
+    }
+    self.valid_since = ValidSince::Before (time.clone());
+    let settled_chunk: i64 = (time - self.start.clone())/self.stride.clone() - 1;
+    while self.checksums_recorded <= settled_chunk {
+      let checksum = self.steward.checksum (self.checksums_recorded);
+      write_entry (&mut self.writer, &LogEntry::<B>::Checksum (self.checksums_recorded, checksum));
+      self.checksums_recorded += 1;
+    }
+  }
+
+  /// Marks the end of the log and returns the underlying writer.
+  pub fn finish (mut self)->W {
+    write_entry (&mut self.writer, &LogEntry::<B>::Finished);
+    self.writer
+  }
+
+  pub fn steward (&self)->&Steward0 {
+    &self.steward
+  }
+}
+
+impl<B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>, W: Any + Write> TimeSteward for Steward<B, Steward0, W>
+where B::Time: Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64> {
+  type Basics = B;
+  type Snapshot = Steward0::Snapshot;
+
+  fn valid_since(&self) -> ValidSince<B::Time> {
+    ::std::cmp::max (self.valid_since.clone(), self.steward.valid_since())
+  }
+
+  fn insert_fiat_event<E: ::Event<Basics = B>>(&mut self,
+                                        time: B::Time,
+                                        id: DeterministicRandomId,
+                                        event: E)
+                                        -> Result<(), FiatEventOperationError> {
+    time_steward_common_insert_fiat_event_prefix!(B, self, time, E);
+    let result = self.steward.insert_fiat_event (time.clone(), id, event.clone());
+    if result.is_ok() {
+      write_entry (&mut self.writer, &LogEntry::<B>::InsertFiatEvent (time, id, E::event_id(), bincode::serialize (&event, bincode::Infinite).unwrap()));
+    }
+    result
+  }
+
+  fn remove_fiat_event(&mut self,
+                      time: &B::Time,
+                      id: DeterministicRandomId)
+                      -> Result<(), FiatEventOperationError> {
+    if self.valid_since() > *time {
+      return Err(FiatEventOperationError::InvalidTime);
+    }
+    let result = self.steward.remove_fiat_event (time, id);
+    if result.is_ok() {
+      write_entry (&mut self.writer, &LogEntry::<B>::RemoveFiatEvent (time.clone(), id));
+    }
+    result
+  }
+
+  fn snapshot_before<'b>(&'b mut self, time: &'b B::Time) -> Option<Steward0::Snapshot> {
+    self.steward.snapshot_before (time)
+  }
+
+  fn scheduled_events (&self, start: &B::Time, end: &B::Time)->Vec <ScheduledEvent <B>> {
+    self.steward.scheduled_events (start, end)
+  }
+  fn lists_scheduled_events (&self)->bool {
+    self.steward.lists_scheduled_events()
+  }
+}
+
+impl<B: Basics, Steward0: ::IncrementalTimeSteward + SimpleSynchronizableTimeSteward<Basics = B>, W: Any + Write> ::IncrementalTimeSteward for Steward<B, Steward0, W>
+where B::Time: Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>
+{
+  fn step(&mut self) {
+    self.steward.step();
+  }
+  fn updated_until_before (&self)->Option <B::Time> {
+    self.steward.updated_until_before()
+  }
+}
+
+
+time_steward_dynamic_fn! (fn replay_fiat_event <B: Basics, [Steward0: Any + TimeSteward<Basics = B>]> (event_id: EventId of <E: Event <Basics = B>>, steward: &mut Steward0, time: B::Time, id: DeterministicRandomId, data: Vec <u8>)->Result <(), ReplayError <B>> {
+  let event = try! (bincode::deserialize:: <E> (data.as_slice()).map_err (ReplayError::Malformed));
+  steward.insert_fiat_event (time.clone(), id, event).map_err (| error | ReplayError::OperationRejected (time, id, error))
+});
+
+/// Rebuilds a steward from a log written by a recording Steward,
+/// checking every recorded checksum along the way.
+///
+/// Steward0 does not need to be the same type that was used for recording,
+/// so this can also be used to check one TimeSteward implementation against another.
+/// If the log ends early (for instance, because the recording program crashed),
+/// the steward is returned in the state described by the part of the log that exists.
+pub fn replay <B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>, R: Read> (reader: &mut R)->Result <Steward0, ReplayError <B>>
+where B::Time: Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>
+{
+  let header = try! (read_header (reader).map_err (ReplayError::Malformed));
+  replay_from_header (header, reader)
+}
+
+/// Reads only the header of a log, leaving the reader positioned at the first entry.
+pub fn read_header <B: Basics, R: Read> (reader: &mut R)->bincode::internal::Result <LogHeader <B>> {
+  bincode::deserialize_from (reader, bincode::Infinite)
+}
+
+/// Like replay(), for a reader whose header has already been read by read_header().
+pub fn replay_from_header <B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>, R: Read> (header: LogHeader <B>, reader: &mut R)->Result <Steward0, ReplayError <B>>
+where B::Time: Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>
+{
+  let mut steward: Steward0 = Steward0::from_constants (header.constants);
+  steward.begin_checks (header.start, header.stride);
+  let known_events = event_ids::<B>();
+  loop {
+    let entry: LogEntry <B> = match bincode::deserialize_from (reader, bincode::Infinite) {
+      Ok (entry) => entry,
+      Err (error) => {
+        if let bincode::internal::ErrorKind::Io (ref io_error) = *error {
+          if io_error.kind() == io::ErrorKind::UnexpectedEof {
+            return Ok (steward);
+          }
+        }
+        return Err (ReplayError::Malformed (error));
+      }
+    };
+    match entry {
+      LogEntry::InsertFiatEvent (time, id, event_id, data) => {
+        // the log comes from outside the program, so check the id before the dynamic fn can panic on it
+        if !known_events.contains (&event_id) {
+          return Err (ReplayError::Malformed (bincode::internal::Error::custom (format! ("{:?} in fiat event log isn't in Basics::IncludedTypes", event_id))));
+        }
+        try! (replay_fiat_event (event_id, &mut steward, time, id, data))
+      },
+      LogEntry::RemoveFiatEvent (time, id) => try! (steward.remove_fiat_event (&time, id).map_err (| error | ReplayError::OperationRejected (time, id, error))),
+      LogEntry::Checksum (chunk, recorded) => {
+        let replayed = steward.checksum (chunk);
+        if replayed != recorded {
+          return Err (ReplayError::ChecksumMismatch {chunk: chunk, recorded: recorded, replayed: replayed, events: steward.debug_dump (chunk)});
+        }
+      },
+      LogEntry::Finished => return Ok (steward),
+    }
+  }
+}
@@ -7,12 +7,14 @@
 // }
 //
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
 use std::io::{Read, Write};
 use std::any::Any;
-use std::ops::{Sub, Mul, Div};
-use {ExtendedTime, Basics, TimeSteward, SimpleSynchronizableTimeSteward, DeterministicRandomId,
-     EventId, Event, FiatEventOperationError, ValidSince};
+use std::ops::{Add, Sub, Mul, Div};
+use {ExtendedTime, Basics, TimeSteward, TimeStewardFromSnapshot, SimpleSynchronizableTimeSteward, DeterministicRandomId,
+     EventId, Event, FiatEventOperationError, ValidSince, SnapshotEntry, FiatSnapshot, serialize_snapshot, deserialize_snapshot, ScheduledEvent};
+use implementation_support::list_of_types::event_ids;
 use std::sync::mpsc::{channel, Sender, Receiver};
 use bincode;
 use serde::Deserialize;
@@ -21,28 +23,169 @@ use serde::Deserialize;
 // serde(deserialize_with is a hacky workaround for https://github.com/rust-lang/rust/issues/41617 (see https://github.com/serde-rs/serde/issues/943)
 #[derive (Clone, Serialize, Deserialize)]
 enum Message<B: Basics> {
+  Introduce(#[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId),
   InsertFiatEvent(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time, #[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId, #[serde(deserialize_with = "Deserialize::deserialize")] EventId, #[serde(deserialize_with = "Deserialize::deserialize")] Vec<u8>),
   RemoveFiatEvent(#[serde(deserialize_with = "Deserialize::deserialize")] B::Time, #[serde(deserialize_with = "Deserialize::deserialize")] DeterministicRandomId),
   Settled(#[serde(deserialize_with = "Deserialize::deserialize")] i64),
   Checksum(#[serde(deserialize_with = "Deserialize::deserialize")] i64, #[serde(deserialize_with = "Deserialize::deserialize")] u64),
-  DebugDump(#[serde(deserialize_with = "Deserialize::deserialize")] BTreeMap<ExtendedTime<B>, u64>),
-  EventDetails(#[serde(deserialize_with = "Deserialize::deserialize")] String),
+  DebugDump(#[serde(deserialize_with = "Deserialize::deserialize")] i64, #[serde(deserialize_with = "Deserialize::deserialize")] BTreeMap<ExtendedTime<B>, u64>),
+  EventDetails(#[serde(deserialize_with = "Deserialize::deserialize")] BTreeMap<ExtendedTime<B>, String>),
   Finished(#[serde(deserialize_with = "Deserialize::deserialize")] u32),
+  Welcome(#[serde(deserialize_with = "Deserialize::deserialize")] Welcome<B>),
 }
 
+// Everything a late-joining peer needs to start participating:
+// the checksum parameters, the first chunk it will check, a snapshot from the beginning of that chunk,
+// and the fiat events that the host knows about from that time onwards.
+#[derive (Clone, Serialize, Deserialize)]
+struct Welcome<B: Basics> {
+  #[serde(deserialize_with = "Deserialize::deserialize")]
+  start: B::Time,
+  #[serde(deserialize_with = "Deserialize::deserialize")]
+  stride: B::Time,
+  #[serde(deserialize_with = "Deserialize::deserialize")]
+  first_chunk: i64,
+  #[serde(deserialize_with = "Deserialize::deserialize")]
+  snapshot: Vec<u8>,
+  #[serde(deserialize_with = "Deserialize::deserialize")]
+  fiat_events: Vec<(B::Time, DeterministicRandomId, EventId, Vec<u8>)>,
+}
+
+// What actually goes over the wire. Each Message is numbered, so that after a connection is re-established,
+// both sides can resend whatever the other side hasn't acknowledged, and discard anything they have already received.
+#[derive (Clone, Serialize, Deserialize)]
+enum Packet<B: Basics> {
+  Message(#[serde(deserialize_with = "Deserialize::deserialize")] u64, #[serde(deserialize_with = "Deserialize::deserialize")] Message<B>),
+  Acknowledge(#[serde(deserialize_with = "Deserialize::deserialize")] u64),
+}
+
+// What the connection threads pass back to the main thread.
+// The u32 is the generation of the connection that was lost, so that a late report
+// from an old connection doesn't disconnect a newer one.
+enum Incoming<B: Basics> {
+  Packet(Packet<B>),
+  Disconnected(u32),
+}
+
+struct Peer<B: Basics> {
+  // None until the peer's Introduce message arrives
+  id: Option<DeterministicRandomId>,
+  sender: Sender<Packet<B>>,
+  generation: u32,
+  connected: bool,
+  // set when the peer sends something that breaks the protocol; we stop talking to it for good
+  dropped: bool,
+  // the sequence number of the last message we sent to this peer, and the messages it hasn't acknowledged yet
+  sent: u64,
+  unacknowledged: VecDeque<(u64, Message<B>)>,
+  // the sequence number of the last message we received from this peer
+  received: u64,
+  // the last chunk this peer has promised not to send us any more fiat events for,
+  // and the last chunk we have promised the same to it
+  settled_through: i64,
+  announced_settled_through: i64,
+  checksums: Vec<u64>,
+  checksums_compared: usize,
+  desynchronized: bool,
+  // Event details that arrived while we were waiting for another peer's.
+  // With several desynchronizations in flight at once, they can arrive in any order between peers.
+  event_details: VecDeque<BTreeMap<ExtendedTime<B>, String>>,
+  finishes_received: u32,
+  // Peers that joined late are only connected to the peer that welcomed them,
+  // so fiat events to or from them have to be passed along.
+  relay: bool,
+}
+
+/// A TimeSteward that keeps several clients synchronized with each other.
+///
+/// Each client must be connected directly to every other client (using add_peer(), or the
+/// Reader/Writer pair passed to new()), and all peers must be added before any fiat events are inserted.
+/// Fiat events are sent to every peer, and every pair of peers compares checksums
+/// for each chunk that all of them have settled.
+///
+/// Additional clients can join a session that is already running, by connecting to any one of the
+/// existing clients (see add_late_peer() and join()). That client then passes fiat events along
+/// between the new client and the rest of the session.
+///
+/// If a connection drops, the session continues, and messages to that peer are kept until
+/// both sides re-attach a new connection using reconnect_peer(). Messages sent in the meantime
+/// are then delivered, in order and exactly once.
 pub struct Steward<B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>> {
   steward: Steward0,
   id: DeterministicRandomId,
-  sender: Sender<Message<B>>,
-  receiver: Receiver<Message<B>>,
+  peers: Vec<Peer<B>>,
+  incoming_sender: Sender<(usize, Incoming<B>)>,
+  receiver: Receiver<(usize, Incoming<B>)>,
+  postponed: VecDeque<(usize, Message<B>)>,
   start: B::Time,
   stride: B::Time,
   valid_since: ValidSince <B::Time>,
   settled_through: i64,
-  other_settled_through: i64,
   checksums: Vec<u64>,
-  dump: Option<BTreeMap<ExtendedTime<B>, u64>>,
-  finishes_received: u32,
+  // Every fiat event that might still be needed to welcome a late-joining peer,
+  // indexed by time and qualified id.
+  fiat_events: BTreeMap<(B::Time, DeterministicRandomId), (EventId, Vec<u8>)>,
+  desynchronization_handler: Option<Box<FnMut (Desynchronization <B>)>>,
+  maximum_lateness: Option<(B::Time, LatenessPolicy)>,
+  // the latest time passed to snapshot_before(), which the maximum lateness is measured from
+  latest_snapshot: Option<B::Time>,
+}
+
+/// What to do with a local fiat event that is further in the past than the maximum lateness allows.
+#[derive (Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LatenessPolicy {
+  /// Return Err(FiatEventOperationError::TooLate).
+  Reject,
+  /// Insert the event at the earliest time that is still allowed instead.
+  /// That time is earliest_allowed(), which only changes when snapshot_before() is called,
+  /// so callers that need to remove the event later can find out where it went.
+  Clamp,
+}
+
+/// An event that happened differently (or only happened) on one of two peers.
+///
+/// `local` and `remote` are the event_details() from each peer, or None if the event didn't happen on that peer.
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct EventDifference<B: Basics> {
+  pub time: ExtendedTime<B>,
+  pub local: Option<String>,
+  pub remote: Option<String>,
+}
+
+/// A report that the checksums of this client and a peer differed for a chunk.
+///
+/// `differences` lists every event in the chunk that differed, in order.
+/// It can be empty if the chunk checksums differed even though every individual event matched.
+///
+/// `complete` is false if the connection to the peer was lost before it sent the details of its events.
+/// The differences are still listed, but every `remote` is None.
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct Desynchronization<B: Basics> {
+  pub peer: DeterministicRandomId,
+  pub chunk: i64,
+  pub differences: Vec<EventDifference<B>>,
+  pub complete: bool,
+}
+
+impl<B: Basics> fmt::Display for Desynchronization<B> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    try!(write!(f, "desynchronized from peer {} in chunk {}", self.peer, self.chunk));
+    if !self.complete {
+      try!(write!(f, "\nlost connection to the peer before it sent its event details, so only local details are available"));
+    }
+    if self.differences.is_empty() {
+      try!(write!(f, "\nboth debug dumps are the same, even though the checksums were different?"));
+    }
+    for difference in self.differences.iter() {
+      match (difference.local.as_ref(), difference.remote.as_ref()) {
+        (Some (local), Some (remote)) => try!(write!(f, "\nevent occurred this way locally:\n {}\n\nbut this way remotely: {}", local, remote)),
+        (Some (local), None) => try!(write!(f, "\nevent only occurred locally:\n {}", local)),
+        (None, Some (remote)) => try!(write!(f, "\nevent only occurred remotely:\n {}", remote)),
+        (None, None) => try!(write!(f, "\nevent at {:?} differed, but its details are unavailable", difference.time)),
+      }
+    }
+    Ok(())
+  }
 }
 
 time_steward_dynamic_fn! (fn do_fiat_event_message <B: Basics, [Steward0: Any + SimpleSynchronizableTimeSteward<Basics = B>]> (event_id: EventId of <E: Event <Basics = B>>, steward: &mut Steward <B, Steward0>, time: B::Time, qualified_id: DeterministicRandomId, data: Vec <u8>)->() {
@@ -50,114 +193,362 @@ time_steward_dynamic_fn! (fn do_fiat_event_message <B: Basics, [Steward0: Any +
 });
 
 impl <B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>> Steward <B, Steward0>
-where << Steward0 as TimeSteward>::Basics as Basics>::Time: Sub <Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Mul<i64, Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Div<<<Steward0 as TimeSteward>::Basics as Basics>::Time, Output = i64>
+where << Steward0 as TimeSteward>::Basics as Basics>::Time: Add <Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Sub <Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Mul<i64, Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Div<<<Steward0 as TimeSteward>::Basics as Basics>::Time, Output = i64>
 {
-  pub fn new <Reader: Any + Read + Send, Writer: Any + Write + Send> (id: DeterministicRandomId, start: B::Time, stride: B::Time, constants: B::Constants, mut reader: Reader, mut writer: Writer)->Self {
+  /// Creates a steward synchronized with exactly one peer.
+  pub fn new <Reader: Any + Read + Send, Writer: Any + Write + Send> (id: DeterministicRandomId, start: B::Time, stride: B::Time, constants: B::Constants, reader: Reader, writer: Writer)->Self {
+    let mut result = Self::new_without_peers (id, start, stride, constants);
+    result.add_peer (reader, writer);
+    result
+  }
+
+  pub fn new_without_peers (id: DeterministicRandomId, start: B::Time, stride: B::Time, constants: B::Constants)->Self {
+    let (incoming_sender, receiver) = channel();
+    let mut steward: Steward0 = Steward0::from_constants (constants);
+    steward.begin_checks (start.clone(), stride.clone());
+    Steward {
+      steward: steward,
+      id: id,
+      peers: Vec::new(),
+      incoming_sender: incoming_sender,
+      receiver: receiver,
+      postponed: VecDeque::new(),
+      start: start, stride: stride,
+      valid_since: ValidSince::TheBeginning,
+      settled_through: -1,
+      checksums: Vec::new(),
+      fiat_events: BTreeMap::new(),
+      desynchronization_handler: None,
+      maximum_lateness: None,
+      latest_snapshot: None,
+    }
+  }
+
+  /// Connects to another peer. This must be done before any fiat events are inserted.
+  pub fn add_peer <Reader: Any + Read + Send, Writer: Any + Write + Send> (&mut self, reader: Reader, writer: Writer) {
+    self.connect (reader, writer, None, 0, false);
+  }
+
+  fn spawn_connection <Reader: Any + Read + Send, Writer: Any + Write + Send> (&self, index: usize, generation: u32, mut reader: Reader, mut writer: Writer)->Sender <Packet <B>> {
     let (send_away, receive_away) = channel();
-    let (send_back, receive_back) = channel();
+    let send_back = self.incoming_sender.clone();
+    let report_failure = self.incoming_sender.clone();
     ::std::thread::spawn (move | | {
       loop {
-        let message: Message <B> = match bincode::deserialize_from (&mut reader, bincode::Infinite) {
-          Err (_) => return,
-          Ok (message) => message,
+        let packet: Packet <B> = match bincode::deserialize_from (&mut reader, bincode::Infinite) {
+          Err (_) => {
+            let _ = send_back.send ((index, Incoming::Disconnected (generation)));
+            return;
+          },
+          Ok (packet) => packet,
         };
-        send_back.send (message.clone()).unwrap();
-        if let Message::Finished (9) = message {return;}
+        let finished = if let Packet::Message (_, Message::Finished (9)) = packet {true} else {false};
+        if send_back.send ((index, Incoming::Packet (packet))).is_err() {return;}
+        if finished {return;}
       }
     });
     ::std::thread::spawn (move | | {
       loop {
         match receive_away.recv() {
           Err (_) => return,
-          Ok (message) => {match bincode::serialize_into (&mut writer, &message, bincode::Infinite) {
-            Err (_) => return,
-            Ok (_) => {writer.flush().unwrap()},
-          }
-          if let Message::Finished (9) = message {return;}
+          Ok (packet) => {
+            if bincode::serialize_into (&mut writer, &packet, bincode::Infinite).is_err() || writer.flush().is_err() {
+              let _ = report_failure.send ((index, Incoming::Disconnected (generation)));
+              return;
+            }
+            if let Packet::Message (_, Message::Finished (9)) = packet {return;}
           }
         };
       }
     });
-    let mut steward: Steward0 = Steward0::from_constants (constants);
-    steward.begin_checks (start.clone(), stride.clone());
-    Steward {
-      steward: steward,
-      id: id,
-      sender: send_away,
-      receiver: receive_back,
-      start: start, stride: stride,
-      valid_since: ValidSince::TheBeginning,
-      settled_through: -1, other_settled_through: -1,
-      checksums: Vec::new(),
-      dump: None,
+    send_away
+  }
+
+  fn connect <Reader: Any + Read + Send, Writer: Any + Write + Send> (&mut self, reader: Reader, writer: Writer, welcome: Option <Welcome <B>>, first_chunk: usize, relay: bool) {
+    let index = self.peers.len();
+    let sender = self.spawn_connection (index, 0, reader, writer);
+    self.peers.push (Peer {
+      id: None,
+      sender: sender,
+      generation: 0,
+      connected: true,
+      dropped: false,
+      sent: 0,
+      unacknowledged: VecDeque::new(),
+      received: 0,
+      settled_through: first_chunk as i64 - 1,
+      announced_settled_through: first_chunk as i64 - 1,
+      // neither side has checksums from before the first chunk, so fill in placeholders that are never compared
+      checksums: vec![0; first_chunk],
+      checksums_compared: first_chunk,
+      desynchronized: false,
+      event_details: VecDeque::new(),
       finishes_received: 0,
+      relay: relay,
+    });
+    if let Some (welcome) = welcome {
+      self.send (index, Message::Welcome (welcome));
+    }
+    let id = self.id;
+    self.send (index, Message::Introduce (id));
+  }
+
+  /// Replaces the connection to a peer, after the previous one was lost.
+  ///
+  /// The peer must do the same with the other end of the new connection. Both sides then resend
+  /// every message the other side hasn't acknowledged. Returns false if there is no peer with the given id
+  /// (or if it hasn't introduced itself yet, in which case the session can't be resumed).
+  pub fn reconnect_peer <Reader: Any + Read + Send, Writer: Any + Write + Send> (&mut self, peer: DeterministicRandomId, reader: Reader, writer: Writer)->bool {
+    let index = match self.peers.iter().position (| candidate | candidate.id == Some (peer) && !candidate.dropped) {
+      Some (index) => index,
+      None => return false,
+    };
+    let generation = self.peers [index].generation + 1;
+    let sender = self.spawn_connection (index, generation, reader, writer);
+    let peer = &mut self.peers [index];
+    let _ = sender.send (Packet::Acknowledge (peer.received));
+    for &(sequence, ref message) in peer.unacknowledged.iter() {
+      let _ = sender.send (Packet::Message (sequence, message.clone()));
     }
+    peer.sender = sender;
+    peer.generation = generation;
+    peer.connected = true;
+    true
+  }
+
+  /// Lists the peers whose connections have been lost, and have not been replaced using reconnect_peer().
+  pub fn disconnected_peers (&self)->Vec <DeterministicRandomId> {
+    self.peers.iter().filter (| peer | !peer.connected && !peer.dropped).filter_map (| peer | peer.id).collect()
+  }
+
+  /// Lists the peers that were dropped for sending messages that no correct peer would send
+  /// (such as a checksum out of order, or an event type this simulation doesn't have).
+  /// They can't be reconnected, and no longer hold back checksum comparisons between the other peers.
+  pub fn dropped_peers (&self)->Vec <DeterministicRandomId> {
+    self.peers.iter().filter (| peer | peer.dropped).filter_map (| peer | peer.id).collect()
+  }
+
+  fn drop_peer (&mut self, index: usize) {
+    let peer = &mut self.peers [index];
+    peer.dropped = true;
+    peer.connected = false;
+    peer.unacknowledged.clear();
+    // replacing the sender ends the thread that writes to the connection
+    peer.sender = channel().0;
+  }
+
+  // Messages are kept until the peer acknowledges them, so it doesn't matter
+  // whether the connection is currently working.
+  fn send (&mut self, index: usize, message: Message <B>) {
+    let peer = &mut self.peers [index];
+    if peer.dropped {
+      return;
+    }
+    peer.sent += 1;
+    peer.unacknowledged.push_back ((peer.sent, message.clone()));
+    let _ = peer.sender.send (Packet::Message (peer.sent, message));
+  }
+
+  // Sends a fiat event message that we received from one peer along to the peers that wouldn't otherwise get it.
+  fn relay (&mut self, from: usize, message: &Message <B>) {
+    for index in 0..self.peers.len() {
+      if index != from && (self.peers [index].relay || self.peers [from].relay) {
+        self.send (index, message.clone());
+      }
+    }
+  }
+
+  fn broadcast (&mut self, message: Message <B>) {
+    for index in 0..self.peers.len() {
+      self.send (index, message.clone());
+    }
+  }
+
+  // Handles acknowledgements and lost connections, and returns the next message that hasn't been seen before.
+  // When blocking, this also returns None as soon as a connection is lost, so that callers
+  // waiting for a particular peer can stop waiting for it.
+  fn receive_from_network (&mut self, block: bool)->Option <(usize, Message <B>)> {
+    loop {
+      let (index, incoming) = if block {
+        self.receiver.recv().unwrap()
+      }
+      else {
+        match self.receiver.try_recv() {
+          Ok (incoming) => incoming,
+          Err (_) => return None,
+        }
+      };
+      match incoming {
+        Incoming::Disconnected (generation) => {
+          // the application learns about this from disconnected_peers()
+          if generation == self.peers [index].generation && self.peers [index].connected {
+            self.peers [index].connected = false;
+            if block {
+              return None;
+            }
+          }
+        },
+        Incoming::Packet (Packet::Acknowledge (through)) => {
+          let peer = &mut self.peers [index];
+          while peer.unacknowledged.front().map_or (false, | &(sequence, _) | sequence <= through) {
+            peer.unacknowledged.pop_front();
+          }
+        },
+        Incoming::Packet (Packet::Message (sequence, message)) => {
+          let peer = &mut self.peers [index];
+          // anything else is a resent message that we already received before the connection was replaced,
+          // or a message from a peer we have dropped
+          if sequence == peer.received + 1 && !peer.dropped {
+            peer.received = sequence;
+            let _ = peer.sender.send (Packet::Acknowledge (sequence));
+            return Some ((index, message));
+          }
+        },
+      }
+    }
+  }
+
+  fn next_message (&mut self, block: bool)->Option <(usize, Message <B>)> {
+    if let Some (message) = self.postponed.pop_front() {
+      return Some (message);
+    }
+    self.receive_from_network (block)
   }
 
   fn receive_once (&mut self)->bool {
-    match self.receiver.try_recv() {
-      Err (_) => false,
-      Ok (message) => {self.received (message); true},
+    match self.next_message (false) {
+      None => false,
+      Some ((index, message)) => {self.received (index, message); true},
     }
   }
-  fn receive_event_details (&self)->String {
-    while let Ok (message) = self.receiver.recv() {
-      if let Message::EventDetails (event_details) = message {
-        return event_details;
+  // Returns None if the connection to the peer is lost first, because a lost connection
+  // can't be replaced while we are blocked here.
+  fn receive_event_details (&mut self, from: usize)->Option <BTreeMap<ExtendedTime<B>, String>> {
+    loop {
+      if let Some (event_details) = self.peers [from].event_details.pop_front() {
+        return Some (event_details);
+      }
+      if !self.peers [from].connected {
+        return None;
       }
+      match self.receive_from_network (true) {
+        Some ((index, Message::EventDetails (event_details))) => self.peers [index].event_details.push_back (event_details),
+        Some (message) => self.postponed.push_back (message),
+        None => (),
+      }
+    }
+  }
+
+  // Both peers compute the same list of differences from the two dumps,
+  // then each sends the details of the events that occurred locally
+  // before waiting for the details of the events that occurred remotely.
+  fn desynchronization (&mut self, index: usize, chunk: i64, remote_dump: BTreeMap<ExtendedTime<B>, u64>)->Desynchronization <B> {
+    let local_dump = self.steward.debug_dump (chunk);
+    let times: Vec<ExtendedTime<B>> = local_dump.keys().chain (remote_dump.keys())
+      .filter (| time | local_dump.get (*time) != remote_dump.get (*time))
+      .cloned().collect::<BTreeSet<_>>().into_iter().collect();
+    let mut local_details: BTreeMap<ExtendedTime<B>, String> = times.iter()
+      .filter (| time | local_dump.contains_key (*time))
+      .map (| time | (time.clone(), self.steward.event_details (time)))
+      .collect();
+    self.send (index, Message::EventDetails (local_details.clone()));
+    let remote_details = self.receive_event_details (index);
+    let complete = remote_details.is_some();
+    let mut remote_details = remote_details.unwrap_or_default();
+    Desynchronization {
+      peer: self.peers [index].id.expect ("peer sent messages before introducing itself"),
+      chunk: chunk,
+      complete: complete,
+      differences: times.into_iter().map (| time | EventDifference {
+        local: local_details.remove (&time),
+        remote: remote_details.remove (&time),
+        time: time,
+      }).collect(),
+    }
+  }
+
+  /// Sets a function to be called when this client detects that it has desynchronized from a peer.
+  ///
+  /// After reporting a desynchronization, the steward keeps running, but no longer compares checksums
+  /// with that peer. If no handler is set, desynchronization panics, with the report as the message.
+  pub fn on_desynchronization <F: FnMut (Desynchronization <B>) + 'static> (&mut self, handler: F) {
+    self.desynchronization_handler = Some (Box::new (handler));
+  }
+
+  /// Limits how far in the past fiat events can be inserted or removed, measured back from
+  /// the latest time passed to snapshot_before(). Since every peer's fiat events can make the others
+  /// recompute everything after them, this bounds how much work a late message can cause.
+  ///
+  /// Whenever the window moves forward, everything before it is settled, as if settle_before() had been called.
+  /// Peers' windows can move at different speeds, so checksums are still only compared for chunks that
+  /// every peer has settled. Fiat events from a peer are checked against the chunks that peer had settled
+  /// when it sent them, which every receiver knows exactly, because each peer's messages arrive in order.
+  /// Events from before that are rejected by every receiver alike; honest peers never send them.
+  ///
+  /// The underlying steward has no way to forget its history, so nothing is discarded when the window moves;
+  /// settling only stops fiat events before the window from being accepted.
+  pub fn set_maximum_lateness (&mut self, lateness: B::Time, policy: LatenessPolicy) {
+    self.maximum_lateness = Some ((lateness, policy));
+  }
+
+  /// The earliest time at which local fiat events can currently be inserted or removed,
+  /// or None if there is no maximum lateness or snapshot_before() hasn't been called yet.
+  pub fn earliest_allowed (&self)->Option <B::Time> {
+    match (self.maximum_lateness.as_ref(), self.latest_snapshot.as_ref()) {
+      (Some (&(ref lateness, _)), Some (latest)) => Some (latest.clone() - lateness.clone()),
+      _ => None,
     }
-    panic!("did not receive expected event details");
   }
 
-  fn received (&mut self, message: Message <B>) {
+  fn received (&mut self, index: usize, message: Message <B>) {
         match message {
-          Message::InsertFiatEvent (time, id, event_id, data) => do_fiat_event_message (event_id, self, time, id, data),
-          Message::RemoveFiatEvent (time, id) => self.remove_fiat_event (&time, id).unwrap(),
+          Message::Introduce (id) => {self.peers [index].id = Some (id);},
+          Message::InsertFiatEvent (time, id, event_id, data) => {
+            if !event_ids::<B>().contains (&event_id) {
+              self.drop_peer (index);
+              return;
+            }
+            // The peer's own simulation includes the event, so ours now differs from it,
+            // and the difference is reported through the desynchronization handler once the chunk is compared.
+            if self.settled_by_peer (index, &time) {
+              return;
+            }
+            self.relay (index, &Message::InsertFiatEvent (time.clone(), id, event_id, data.clone()));
+            self.fiat_events.insert ((time.clone(), id), (event_id, data.clone()));
+            do_fiat_event_message (event_id, self, time, id, data);
+          },
+          Message::RemoveFiatEvent (time, id) => {
+            if self.settled_by_peer (index, &time) {
+              return;
+            }
+            self.relay (index, &Message::RemoveFiatEvent (time.clone(), id));
+            self.fiat_events.remove (&(time.clone(), id));
+            self.steward.remove_fiat_event (&time, id).unwrap();
+          },
           Message::Settled (chunk) => {
-            self.other_settled_through = chunk;
+            self.peers [index].settled_through = chunk;
+            self.announce_settled();
             self.do_checksums();
           },
           Message::Checksum (chunk, checksum) => {
-            if self.checksums [chunk as usize] != checksum && self.dump.is_none() {
-              println!("detected desynchronization in chunk {}", chunk);
-              self.dump = Some (self.steward.debug_dump (chunk));
-              self.sender.send (Message::DebugDump (self.dump.clone().unwrap())).unwrap();
+            if chunk < 0 || chunk as usize != self.peers [index].checksums.len() {
+              self.drop_peer (index);
+              return;
             }
+            self.peers [index].checksums.push (checksum);
+            self.compare_checksums (index);
           },
-          Message::DebugDump (events) => {
-            let mut my_iter = self.dump.as_ref().unwrap().iter();
-            let mut other_iter = events.iter();
-            loop {
-              match (my_iter.next(), other_iter.next()) {
-                (None, None) => panic!("both debug dumps are the same, even though the checksums were different?"),
-                (Some ((my_time, my_checksum)), Some ((other_time, other_checksum))) => {
-                  if my_time < other_time {
-                    let event_details = self.steward.event_details (my_time);
-                    self.sender.send (Message::EventDetails (event_details.clone())).unwrap();
-                    panic!("event only occurred locally:\n {}", event_details);
-                  }
-                  if my_time > other_time {
-                    panic!("event only occurred remotely:\n {}", self.receive_event_details());
-                  }
-                  if my_checksum != other_checksum {
-                    let event_details = self.steward.event_details (my_time);
-                    self.sender.send (Message::EventDetails (event_details.clone())).unwrap();
-                    panic!("event occurred this way locally:\n {}\n\nbut this way remotely: {}", event_details, self.receive_event_details());
-                  }
-                },
-                (Some ((my_time, _)), None) => {
-                  let event_details = self.steward.event_details (my_time);
-                  self.sender.send (Message::EventDetails (event_details.clone())).unwrap();
-                  panic!("event only occurred locally:\n {}", event_details);
-                },
-                (None, Some ((_, _))) => {
-                  panic!("event only occurred remotely:\n {}", self.receive_event_details());
-                },
-              }
+          Message::DebugDump (chunk, events) => {
+            let desynchronization = self.desynchronization (index, chunk, events);
+            match self.desynchronization_handler.as_mut() {
+              Some (handler) => handler (desynchronization),
+              None => panic!("{}", desynchronization),
             }
           },
-          Message::EventDetails (_) => panic!("We should not receive an event details except where specifically expecting it"),
-          Message::Finished (_) => {self.finishes_received += 1;},
+          Message::EventDetails (event_details) => self.peers [index].event_details.push_back (event_details),
+          Message::Finished (_) => {self.peers [index].finishes_received += 1;},
+          // only the very first message to a joining peer is a welcome, and join() handles that one
+          Message::Welcome (_) => self.drop_peer (index),
         };
   }
 
@@ -168,32 +559,159 @@ where << Steward0 as TimeSteward>::Basics as Basics>::Time: Sub <Output = << Ste
     self.valid_since = ValidSince::Before (time.clone());
     let settled_chunk: i64 = (time - self. start.clone())/self.stride.clone() - 1;
     self.settled_through =::std::cmp::max (settled_chunk, self.settled_through);
-    self.sender.send (Message::Settled (self.settled_through)).unwrap();
+    self.announce_settled();
     self.do_checksums();
   }
 
+  // The last chunk we can promise a peer that we won't send it any more fiat events for.
+  // That includes the events we pass along from other peers, so it can't be later than what they have promised us.
+  fn settled_for (&self, index: usize)->i64 {
+    (0..self.peers.len())
+      .filter (| &other | other != index && !self.peers [other].dropped && (self.peers [other].relay || self.peers [index].relay))
+      .fold (self.settled_through, | settled, other | ::std::cmp::min (settled, self.peers [other].settled_through))
+  }
+
+  fn announce_settled (&mut self) {
+    for index in 0..self.peers.len() {
+      let settled = self.settled_for (index);
+      if settled > self.peers [index].announced_settled_through {
+        self.peers [index].announced_settled_through = settled;
+        self.send (index, Message::Settled (settled));
+      }
+    }
+  }
+
+  // Whether a fiat event message from a peer is in a chunk that the peer had already promised not to send events for.
+  fn settled_by_peer (&self, index: usize, time: &B::Time)->bool {
+    let settled_through = self.peers [index].settled_through;
+    settled_through >= 0 && *time < self.start.clone() + self.stride.clone()*(settled_through + 1)
+  }
+
   fn do_checksums (&mut self) {
-    while (self.checksums.len() as i64) <= ::std::cmp::min (self.settled_through, self.other_settled_through) {
+    let everyone_settled_through = self.peers.iter().filter (| peer | !peer.dropped).fold (self.settled_through, | settled, peer | ::std::cmp::min (settled, peer.settled_through));
+    while (self.checksums.len() as i64) <= everyone_settled_through {
       let checksum: u64 = self.steward.checksum (self.checksums.len() as i64);
-      self.sender.send (Message::Checksum (self.checksums.len() as i64, checksum)).unwrap();
+      self.broadcast (Message::Checksum (self.checksums.len() as i64, checksum));
       self.checksums.push (checksum);
     }
+    // fiat events before the chunks that everyone has settled will never be needed to welcome anyone
+    let boundary = self.start.clone() + self.stride.clone()*(self.checksums.len() as i64);
+    self.fiat_events = self.fiat_events.split_off (&(boundary, DeterministicRandomId::MIN));
+    for index in 0..self.peers.len() {
+      self.compare_checksums (index);
+    }
+  }
+
+  // A peer may send us its checksum for a chunk before we have computed ours
+  // (if it learned that a third peer had settled that chunk before we did),
+  // so we compare whenever either side becomes available.
+  fn compare_checksums (&mut self, index: usize) {
+    while self.peers [index].checksums_compared < ::std::cmp::min (self.checksums.len(), self.peers [index].checksums.len()) {
+      let chunk = self.peers [index].checksums_compared;
+      self.peers [index].checksums_compared += 1;
+      if self.checksums [chunk] != self.peers [index].checksums [chunk] && !self.peers [index].desynchronized {
+        self.peers [index].desynchronized = true;
+        let dump = self.steward.debug_dump (chunk as i64);
+        self.send (index, Message::DebugDump (chunk as i64, dump));
+      }
+    }
   }
 
+  /// Exchanges Finished messages with every peer, so that the messages sent before it are handled on both sides.
+  ///
+  /// Peers whose connections are lost (before or during this) aren't waited for.
   pub fn finish (&mut self) {
     for round in 0..10 {
-      self.sender.send (Message::Finished (round)).unwrap();
-      loop {
-        let message = self.receiver.recv().unwrap();
-        self.received (message);
-        if self.finishes_received > round {break;}
+      self.broadcast (Message::Finished (round));
+      while self.peers.iter().any (| peer | peer.connected && peer.finishes_received <= round) {
+        if let Some ((index, message)) = self.next_message (true) {
+          self.received (index, message);
+        }
       }
     }
   }
 }
 
+impl <B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>> Steward <B, Steward0>
+where B::Time: Add <Output = B::Time> + Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>,
+      for<'a> &'a Steward0::Snapshot: IntoIterator<Item = SnapshotEntry<'a, B>>
+{
+  /// Welcomes a peer that is joining a session that is already running, by sending it
+  /// a snapshot and the fiat events after it. The new peer must be constructed using join().
+  ///
+  /// The snapshot is taken at the beginning of the first chunk that hasn't been settled by every peer,
+  /// so that no fiat events can be inserted before it. The new peer takes part in checksum comparisons from that chunk onwards.
+  /// Afterwards, this client passes fiat events along between the new peer and all other peers.
+  pub fn add_late_peer <Reader: Any + Read + Send, Writer: Any + Write + Send> (&mut self, reader: Reader, writer: Writer) {
+    let first_chunk = self.checksums.len();
+    let time = self.start.clone() + self.stride.clone()*(first_chunk as i64);
+    let snapshot = self.steward.snapshot_before (&time).expect ("a full TimeSteward should be able to provide a snapshot from after the settled chunks");
+    let mut serialized = Vec::new();
+    serialize_snapshot::<B, Steward0::Snapshot, _, _> (&snapshot, &mut serialized, bincode::Infinite).unwrap();
+    let welcome = Welcome {
+      start: self.start.clone(),
+      stride: self.stride.clone(),
+      first_chunk: first_chunk as i64,
+      snapshot: serialized,
+      fiat_events: self.fiat_events.iter().map (| (&(ref time, id), &(event_id, ref data)) | (time.clone(), id, event_id, data.clone())).collect(),
+    };
+    self.connect (reader, writer, Some (welcome), first_chunk, true);
+  }
+}
+
+impl <B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B> + TimeStewardFromSnapshot> Steward <B, Steward0>
+where B::Time: Add <Output = B::Time> + Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>
+{
+  /// Joins a session that is already running, through a peer that called add_late_peer().
+  ///
+  /// This blocks until the welcome message arrives. Returns None if the connection doesn't provide one.
+  /// If the connection is lost while joining, the whole join has to be retried, because the host
+  /// can't resume a session with a peer that never introduced itself.
+  /// The new steward is valid since the beginning of the first chunk it checks.
+  /// Like every other peer, it must call settle_before() regularly, or checksums will stop being compared.
+  pub fn join <Reader: Any + Read + Send, Writer: Any + Write + Send> (id: DeterministicRandomId, mut reader: Reader, writer: Writer)->Option <Self> {
+    let welcome = match bincode::deserialize_from (&mut reader, bincode::Infinite) {
+      Ok (Packet::Message (1, Message::Welcome (welcome))) => welcome,
+      _ => return None,
+    };
+    let Welcome {start, stride, first_chunk, snapshot, fiat_events}: Welcome <B> = welcome;
+    let snapshot: FiatSnapshot <B> = match deserialize_snapshot (&mut &snapshot [..], bincode::Infinite) {
+      Ok (snapshot) => snapshot,
+      Err (_) => return None,
+    };
+    let (incoming_sender, receiver) = channel();
+    let mut steward: Steward0 = Steward0::from_snapshot::<FiatSnapshot <B>> (&snapshot);
+    steward.begin_checks (start.clone(), stride.clone());
+    let time = start.clone() + stride.clone()*first_chunk;
+    let mut result = Steward {
+      steward: steward,
+      id: id,
+      peers: Vec::new(),
+      incoming_sender: incoming_sender,
+      receiver: receiver,
+      postponed: VecDeque::new(),
+      start: start, stride: stride,
+      valid_since: ValidSince::Before (time),
+      settled_through: first_chunk - 1,
+      // placeholders; see connect()
+      checksums: vec![0; first_chunk as usize],
+      fiat_events: BTreeMap::new(),
+      desynchronization_handler: None,
+      maximum_lateness: None,
+      latest_snapshot: None,
+    };
+    for (time, id, event_id, data) in fiat_events {
+      result.fiat_events.insert ((time.clone(), id), (event_id, data.clone()));
+      do_fiat_event_message (event_id, &mut result, time, id, data);
+    }
+    result.connect (reader, writer, None, first_chunk as usize, false);
+    result.peers [0].received = 1;
+    Some (result)
+  }
+}
+
 impl<B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>> TimeSteward for Steward<B, Steward0>
-where  B::Time: Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64> {
+where  B::Time: Add <Output = B::Time> + Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64> {
   type Basics = B;
   type Snapshot = Steward0::Snapshot;
   
@@ -206,13 +724,23 @@ where  B::Time: Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Tim
                                         id: DeterministicRandomId,
                                         event: E)
                                         -> Result<(), FiatEventOperationError> {
+    let time = match (self.earliest_allowed(), self.maximum_lateness.as_ref().map (| &(_, policy) | policy)) {
+      (Some (earliest), Some (LatenessPolicy::Reject)) if time < earliest => return Err (FiatEventOperationError::TooLate),
+      (Some (earliest), Some (LatenessPolicy::Clamp)) if time < earliest => earliest,
+      _ => time,
+    };
     time_steward_common_insert_fiat_event_prefix!(B, self, time, E);
     let qualified_id = DeterministicRandomId::new (& (id, self .id));
     let result = self.steward.insert_fiat_event (time.clone(), qualified_id, event.clone());
     match result {
-      Ok (_) => self.sender.send (Message::InsertFiatEvent (time, qualified_id, E::event_id(), bincode::serialize (&event, bincode::Infinite).unwrap())).unwrap(),
+      Ok (_) => {
+        let data = bincode::serialize (&event, bincode::Infinite).unwrap();
+        self.fiat_events.insert ((time.clone(), qualified_id), (E::event_id(), data.clone()));
+        self.broadcast (Message::InsertFiatEvent (time, qualified_id, E::event_id(), data));
+      },
       Err (FiatEventOperationError::InvalidInput) => (),
       Err (FiatEventOperationError::InvalidTime) => (),
+      Err (FiatEventOperationError::TooLate) => (),
     }
     result
   }
@@ -221,28 +749,48 @@ where  B::Time: Sub <Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Tim
                       time: &B::Time,
                       id: DeterministicRandomId)
                       -> Result<(), FiatEventOperationError> {
+    if self.earliest_allowed().map_or (false, | earliest | *time < earliest) {
+      return Err(FiatEventOperationError::TooLate);
+    }
     if self.valid_since() > *time {
       return Err(FiatEventOperationError::InvalidTime);
     }
     let qualified_id = DeterministicRandomId::new (& (id, self .id));
     let result = self.steward.remove_fiat_event (time, qualified_id);
     match result {
-      Ok (_) => self.sender.send (Message::RemoveFiatEvent (time.clone(), qualified_id)).unwrap(),
+      Ok (_) => {
+        self.fiat_events.remove (&(time.clone(), qualified_id));
+        self.broadcast (Message::RemoveFiatEvent (time.clone(), qualified_id));
+      },
       Err (FiatEventOperationError::InvalidInput) => (),
       Err (FiatEventOperationError::InvalidTime) => (),
+      Err (FiatEventOperationError::TooLate) => (),
     }
     result
   }
 
   fn snapshot_before<'b>(&'b mut self, time: &'b B::Time) -> Option<Steward0::Snapshot> {
     while self.receive_once() {}
+    if self.latest_snapshot.as_ref().map_or (true, | latest | time > latest) {
+      self.latest_snapshot = Some (time.clone());
+      if let Some (earliest) = self.earliest_allowed() {
+        self.settle_before (earliest);
+      }
+    }
     self.steward.snapshot_before (time)
   }
+
+  fn scheduled_events (&self, start: &B::Time, end: &B::Time)->Vec <ScheduledEvent <B>> {
+    self.steward.scheduled_events (start, end)
+  }
+  fn lists_scheduled_events (&self)->bool {
+    self.steward.lists_scheduled_events()
+  }
 }
 
 
 impl<B: Basics, Steward0: ::IncrementalTimeSteward + SimpleSynchronizableTimeSteward<Basics = B>> ::IncrementalTimeSteward for Steward<B, Steward0>
-where << Steward0 as TimeSteward>::Basics as Basics>::Time: Sub <Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Mul<i64, Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Div<<<Steward0 as TimeSteward>::Basics as Basics>::Time, Output = i64>
+where << Steward0 as TimeSteward>::Basics as Basics>::Time: Add <Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Sub <Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Mul<i64, Output = << Steward0 as TimeSteward>::Basics as Basics>::Time> + Div<<<Steward0 as TimeSteward>::Basics as Basics>::Time, Output = i64>
 {
   fn step(&mut self) {
     if !self.receive_once() { self.steward.step(); }
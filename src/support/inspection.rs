@@ -0,0 +1,252 @@
+// Tools for examining snapshots and recorded simulations from outside of the simulation.
+//
+// The time_steward_inspect binary runs main() for the bouncy circles demo.
+// A game that wants the same tool for its own Basics makes a tiny binary of its own
+// whose main() calls main::<TheirBasics, TheirSteward<TheirBasics>>().
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+use std::ops::{Sub, Mul, Div, Range};
+use {Basics, ExtendedTime, FieldId, RowId, ColumnId, MomentaryAccessor, Snapshot, SnapshotEntry,
+     TimeStewardFromSnapshot, SimpleSynchronizableTimeSteward};
+use stewards::recorded;
+use serde_json;
+use bincode;
+
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct SnapshotStatistics {
+  pub rows: usize,
+  pub fields: usize,
+  pub rows_per_column: BTreeMap<ColumnId, usize>,
+}
+
+pub fn snapshot_statistics<'a, B: Basics, Shot: Snapshot<Basics = B>>(snapshot: &'a Shot) -> SnapshotStatistics
+  where &'a Shot: IntoIterator<Item = SnapshotEntry<'a, B>>
+{
+  let mut rows = BTreeSet::new();
+  let mut rows_per_column = BTreeMap::new();
+  for (id, _) in snapshot {
+    rows.insert(id.row_id);
+    *rows_per_column.entry(id.column_id).or_insert(0) += 1;
+  }
+  SnapshotStatistics {
+    rows: rows.len(),
+    fields: snapshot.num_fields(),
+    rows_per_column: rows_per_column,
+  }
+}
+
+/// Returns the number of events that occurred in each of the given checksum chunks.
+pub fn events_per_chunk<Steward0: SimpleSynchronizableTimeSteward>(steward: &mut Steward0, chunks: Range<i64>) -> Vec<usize> {
+  chunks.map(|chunk| {
+    // checksum() makes sure the steward has actually computed the chunk
+    steward.checksum(chunk);
+    steward.debug_dump(chunk).len()
+  }).collect()
+}
+
+/// Describes a single field as JSON, in the same form used by export_snapshot_json().
+pub fn field_json<B: Basics, A: MomentaryAccessor<Basics = B>>(accessor: &A, id: FieldId) -> Option<serde_json::Result<String>> {
+  accessor.generic_data_and_extended_last_change(id).map(|(data, changed)| {
+    let mut field = serde_json::Map::new();
+    field.insert("column_id".to_string(), try!(serde_json::to_value(id.column_id)));
+    field.insert("data".to_string(), try!(::implementation_support::common::field_to_json::<B>(id.column_id, data)));
+    field.insert("last_change".to_string(), try!(serde_json::to_value(changed)));
+    serde_json::to_string_pretty(&serde_json::Value::Object(field))
+  })
+}
+
+/// An event that happened differently (or only happened) in one of two runs.
+///
+/// `first` and `second` are the event_details() from each run, or None if the event didn't happen in that run.
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct EventDifference<B: Basics> {
+  pub time: ExtendedTime<B>,
+  pub first: Option<String>,
+  pub second: Option<String>,
+}
+
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct Divergence<B: Basics> {
+  pub chunk: i64,
+  pub differences: Vec<EventDifference<B>>,
+}
+
+/// Compares two runs chunk by chunk, and describes the first chunk whose checksums differ.
+///
+/// Both stewards must have had begin_checks() called with the same start and stride.
+pub fn first_divergent_chunk<B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B>, Steward1: SimpleSynchronizableTimeSteward<Basics = B>>
+  (first: &mut Steward0,
+   second: &mut Steward1,
+   chunks: Range<i64>)
+   -> Option<Divergence<B>> {
+  for chunk in chunks {
+    if first.checksum(chunk) == second.checksum(chunk) {
+      continue;
+    }
+    let first_dump = first.debug_dump(chunk);
+    let second_dump = second.debug_dump(chunk);
+    let times: BTreeSet<&ExtendedTime<B>> = first_dump.keys().chain(second_dump.keys()).collect();
+    let differences = times.into_iter()
+      .filter(|time| first_dump.get(*time) != second_dump.get(*time))
+      .map(|time| {
+        EventDifference {
+          time: time.clone(),
+          first: first_dump.get(time).map(|_| first.event_details(time)),
+          second: second_dump.get(time).map(|_| second.event_details(time)),
+        }
+      })
+      .collect();
+    return Some(Divergence {
+      chunk: chunk,
+      differences: differences,
+    });
+  }
+  None
+}
+
+pub const USAGE: &'static str = "
+Inspects TimeSteward snapshots and fiat event logs.
+
+Usage:
+  inspect stats <file> <time>
+  inspect step <file> <time>
+  inspect field <file> <time> <row> <column>
+  inspect compare <log> <log> <time>
+
+Files ending in .json are snapshots written by export_snapshot_json(),
+files ending in .snapshot are snapshots written by serialize_snapshot(),
+and all other files are fiat event logs written by stewards::recorded.
+
+Times, rows, and columns are given in the same JSON form that export_snapshot_json() uses.
+
+Commands:
+  stats    Counts rows in each column at <time>. For logs, also counts events in each checksum chunk before <time>.
+  step     Runs the simulation until <time> and writes a JSON snapshot to standard output.
+  field    Prints a single field at <time>.
+  compare  Replays two logs, and describes the first checksum chunk before <time> where they differ.
+";
+
+struct Loaded<B: Basics, Steward0> {
+  steward: Steward0,
+  header: Option<recorded::LogHeader<B>>,
+}
+
+fn load<B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B> + TimeStewardFromSnapshot>(path: &str) -> Result<Loaded<B, Steward0>, String>
+  where B::Time: Sub<Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>
+{
+  let mut reader = BufReader::new(try!(File::open(path).map_err(|error| format!("couldn't open {}: {}", path, error))));
+  if path.ends_with(".json") {
+    let snapshot = try!(::import_snapshot_json::<B, _>(&mut reader).map_err(|error| format!("couldn't read {}: {}", path, error)));
+    Ok(Loaded { steward: Steward0::from_snapshot::<::FiatSnapshot<B>>(&snapshot), header: None })
+  } else if path.ends_with(".snapshot") {
+    let snapshot = try!(::deserialize_snapshot::<B, _, _>(&mut reader, bincode::Infinite).map_err(|error| format!("couldn't read {}: {}", path, error)));
+    Ok(Loaded { steward: Steward0::from_snapshot::<::FiatSnapshot<B>>(&snapshot), header: None })
+  } else {
+    let header = try!(recorded::read_header::<B, _>(&mut reader).map_err(|error| format!("couldn't read {}: {}", path, error)));
+    let steward = try!(recorded::replay_from_header(header.clone(), &mut reader).map_err(|error| format!("couldn't replay {}: {:?}", path, error)));
+    Ok(Loaded { steward: steward, header: Some(header) })
+  }
+}
+
+fn parse<T: ::serde::de::DeserializeOwned>(argument: &str, what: &str) -> Result<T, String> {
+  serde_json::from_str(argument).map_err(|error| format!("couldn't parse {} {:?}: {}", what, argument, error))
+}
+
+fn chunks_before<B: Basics>(header: &recorded::LogHeader<B>, time: &B::Time) -> Range<i64>
+  where B::Time: Sub<Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>
+{
+  0..(time.clone() - header.start.clone()) / header.stride.clone()
+}
+
+/// Runs the inspection tool with the given command-line arguments (not including the program name).
+pub fn run<B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B> + TimeStewardFromSnapshot>(arguments: &[String]) -> Result<(), String>
+  where B::Time: Sub<Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>,
+        for<'a> &'a Steward0::Snapshot: IntoIterator<Item = SnapshotEntry<'a, B>>
+{
+  let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+  match (arguments.get(0).cloned(), arguments.len()) {
+    (Some("stats"), 3) => {
+      let (path, time) = (arguments[1], arguments[2]);
+      let time: B::Time = try!(parse(time, "time"));
+      let mut loaded = try!(load::<B, Steward0>(path));
+      let snapshot = try!(loaded.steward.snapshot_before(&time).ok_or(format!("no snapshot available at {:?}", time)));
+      let statistics = snapshot_statistics(&snapshot);
+      println!("at {:?}: {} rows, {} fields", time, statistics.rows, statistics.fields);
+      for (column, rows) in statistics.rows_per_column.iter() {
+        println!("  {:?}: {} rows", column, rows);
+      }
+      if let Some(header) = loaded.header.as_ref() {
+        let chunks = chunks_before(header, &time);
+        for (chunk, events) in chunks.clone().zip(events_per_chunk(&mut loaded.steward, chunks)) {
+          println!("  chunk {}: {} events", chunk, events);
+        }
+      }
+      Ok(())
+    }
+    (Some("step"), 3) => {
+      let (path, time) = (arguments[1], arguments[2]);
+      let time: B::Time = try!(parse(time, "time"));
+      let mut loaded = try!(load::<B, Steward0>(path));
+      let snapshot = try!(loaded.steward.snapshot_before(&time).ok_or(format!("no snapshot available at {:?}", time)));
+      let stdout = io::stdout();
+      let mut writer = stdout.lock();
+      try!(::export_snapshot_json::<B, Steward0::Snapshot, _>(&snapshot, &mut writer).map_err(|error| error.to_string()));
+      writeln!(writer, "").map_err(|error| error.to_string())
+    }
+    (Some("field"), 5) => {
+      let (path, time, row, column) = (arguments[1], arguments[2], arguments[3], arguments[4]);
+      let time: B::Time = try!(parse(time, "time"));
+      let id = FieldId::new(try!(parse::<RowId>(row, "row")), try!(parse::<ColumnId>(column, "column")));
+      let mut loaded = try!(load::<B, Steward0>(path));
+      let snapshot = try!(loaded.steward.snapshot_before(&time).ok_or(format!("no snapshot available at {:?}", time)));
+      match field_json(&snapshot, id) {
+        None => println!("{:?} does not exist at {:?}", id, time),
+        Some(json) => println!("{}", try!(json.map_err(|error| error.to_string()))),
+      }
+      Ok(())
+    }
+    (Some("compare"), 4) => {
+      let (first_path, second_path, time) = (arguments[1], arguments[2], arguments[3]);
+      let time: B::Time = try!(parse(time, "time"));
+      let mut first = try!(load::<B, Steward0>(first_path));
+      let mut second = try!(load::<B, Steward0>(second_path));
+      let chunks = match (first.header.as_ref(), second.header.as_ref()) {
+        (Some(first_header), Some(second_header)) => {
+          if first_header.start != second_header.start || first_header.stride != second_header.stride {
+            return Err("the logs were recorded with different checksum chunks".to_string());
+          }
+          chunks_before(first_header, &time)
+        }
+        _ => return Err("compare requires two fiat event logs".to_string()),
+      };
+      match first_divergent_chunk(&mut first.steward, &mut second.steward, chunks) {
+        None => println!("no differences before {:?}", time),
+        Some(divergence) => {
+          println!("first divergence in chunk {}", divergence.chunk);
+          for difference in divergence.differences {
+            println!("\nat {:?}:", difference.time);
+            println!("first run: {}", difference.first.unwrap_or("(event did not occur)".to_string()));
+            println!("second run: {}", difference.second.unwrap_or("(event did not occur)".to_string()));
+          }
+        }
+      }
+      Ok(())
+    }
+    _ => Err(USAGE.to_string()),
+  }
+}
+
+/// The whole inspection tool: runs it with the process's command-line arguments,
+/// and exits with an error message if it fails.
+pub fn main<B: Basics, Steward0: SimpleSynchronizableTimeSteward<Basics = B> + TimeStewardFromSnapshot>()
+  where B::Time: Sub<Output = B::Time> + Mul<i64, Output = B::Time> + Div<B::Time, Output = i64>,
+        for<'a> &'a Steward0::Snapshot: IntoIterator<Item = SnapshotEntry<'a, B>>
+{
+  let arguments: Vec<String> = ::std::env::args().skip(1).collect();
+  if let Err(message) = run::<B, Steward0>(&arguments) {
+    writeln!(&mut io::stderr(), "{}", message).expect("failed printing to stderr");
+    ::std::process::exit(1);
+  }
+}
@@ -0,0 +1,259 @@
+// A dense time type, for simulations where events should happen at exact fractional times
+// instead of being rounded to ticks.
+//
+// Rational64 can be used as Basics::Time directly. It supports the operations that the stewards
+// which work in strides (simply_synchronized, recorded, amortized) require of their times,
+// and it serializes as a [numerator, denominator] pair. tests/rational_time.rs runs those stewards with it.
+//
+// The solvers in time_functions accept it as their base and now times, through TrajectoryTime,
+// but they still work in whole ticks of the trajectory, so their answers are rounded up to a tick.
+// LinearTrajectory::exactly_when_crosses() is the only exact one.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Sub, Mul, Div, Neg};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::Error;
+use rowless::api::StewardData;
+
+/// A fraction of two i64s, always stored in lowest terms with a positive denominator.
+///
+/// Arithmetic is computed exactly using i128s, and panics if the reduced result doesn't fit in i64s,
+/// just like integer overflow in debug builds. The checked_ methods return None instead.
+#[derive (Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rational64 {
+  numerator: i64,
+  denominator: i64,
+}
+
+fn gcd(mut first: i128, mut second: i128) -> i128 {
+  while second != 0 {
+    let remainder = first % second;
+    first = second;
+    second = remainder;
+  }
+  first.abs()
+}
+
+fn floor_divide(numerator: i128, denominator: i128) -> i128 {
+  let quotient = numerator / denominator;
+  if (numerator % denominator != 0) && ((numerator < 0) != (denominator < 0)) {
+    quotient - 1
+  } else {
+    quotient
+  }
+}
+
+impl Rational64 {
+  pub fn new(numerator: i64, denominator: i64) -> Rational64 {
+    assert!(denominator != 0, "Rational64 with a denominator of 0");
+    Rational64::from_i128s(numerator as i128, denominator as i128).expect("Rational64 overflow")
+  }
+  pub fn from_integer(value: i64) -> Rational64 {
+    Rational64 {
+      numerator: value,
+      denominator: 1,
+    }
+  }
+  pub fn zero() -> Rational64 {
+    Rational64::from_integer(0)
+  }
+
+  /// Reduces the fraction, or returns None if the denominator is 0 or the reduced fraction doesn't fit.
+  pub fn from_i128s(numerator: i128, denominator: i128) -> Option<Rational64> {
+    if denominator == 0 {
+      return None;
+    }
+    let divisor = gcd(numerator, denominator) * denominator.signum();
+    let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+    if numerator > i64::max_value() as i128 || numerator < i64::min_value() as i128 ||
+       denominator > i64::max_value() as i128 {
+      return None;
+    }
+    Some(Rational64 {
+      numerator: numerator as i64,
+      denominator: denominator as i64,
+    })
+  }
+
+  pub fn numerator(&self) -> i64 {
+    self.numerator
+  }
+  pub fn denominator(&self) -> i64 {
+    self.denominator
+  }
+  pub fn is_integer(&self) -> bool {
+    self.denominator == 1
+  }
+  pub fn floor(&self) -> i64 {
+    floor_divide(self.numerator as i128, self.denominator as i128) as i64
+  }
+  pub fn ceil(&self) -> i64 {
+    -floor_divide(-(self.numerator as i128), self.denominator as i128) as i64
+  }
+
+  pub fn checked_add(&self, other: Rational64) -> Option<Rational64> {
+    Rational64::from_i128s(self.numerator as i128 * other.denominator as i128 +
+                           other.numerator as i128 * self.denominator as i128,
+                           self.denominator as i128 * other.denominator as i128)
+  }
+  pub fn checked_sub(&self, other: Rational64) -> Option<Rational64> {
+    Rational64::from_i128s(self.numerator as i128 * other.denominator as i128 -
+                           other.numerator as i128 * self.denominator as i128,
+                           self.denominator as i128 * other.denominator as i128)
+  }
+  pub fn checked_mul(&self, other: Rational64) -> Option<Rational64> {
+    Rational64::from_i128s(self.numerator as i128 * other.numerator as i128,
+                           self.denominator as i128 * other.denominator as i128)
+  }
+  /// Returns None when dividing by 0, as well as on overflow.
+  pub fn checked_div(&self, other: Rational64) -> Option<Rational64> {
+    Rational64::from_i128s(self.numerator as i128 * other.denominator as i128,
+                           self.denominator as i128 * other.numerator as i128)
+  }
+}
+
+impl Default for Rational64 {
+  fn default() -> Rational64 {
+    Rational64::zero()
+  }
+}
+
+impl From<i64> for Rational64 {
+  fn from(value: i64) -> Rational64 {
+    Rational64::from_integer(value)
+  }
+}
+
+impl Ord for Rational64 {
+  fn cmp(&self, other: &Rational64) -> Ordering {
+    // the denominators are positive, so cross-multiplying preserves the order
+    (self.numerator as i128 * other.denominator as i128).cmp(&(other.numerator as i128 * self.denominator as i128))
+  }
+}
+impl PartialOrd for Rational64 {
+  fn partial_cmp(&self, other: &Rational64) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Add for Rational64 {
+  type Output = Rational64;
+  fn add(self, other: Rational64) -> Rational64 {
+    self.checked_add(other).expect("Rational64 overflow")
+  }
+}
+impl Sub for Rational64 {
+  type Output = Rational64;
+  fn sub(self, other: Rational64) -> Rational64 {
+    self.checked_sub(other).expect("Rational64 overflow")
+  }
+}
+impl Mul for Rational64 {
+  type Output = Rational64;
+  fn mul(self, other: Rational64) -> Rational64 {
+    self.checked_mul(other).expect("Rational64 overflow")
+  }
+}
+impl Mul<i64> for Rational64 {
+  type Output = Rational64;
+  fn mul(self, other: i64) -> Rational64 {
+    self * Rational64::from_integer(other)
+  }
+}
+/// The number of whole times `other` fits into `self`, rounded down.
+///
+/// This is the operation the stewards use to count strides, so that Rational64 can be their Time;
+/// use checked_div() for the exact quotient.
+impl Div for Rational64 {
+  type Output = i64;
+  fn div(self, other: Rational64) -> i64 {
+    self.checked_div(other).expect("Rational64 division by 0 or overflow").floor()
+  }
+}
+impl Neg for Rational64 {
+  type Output = Rational64;
+  fn neg(self) -> Rational64 {
+    Rational64::new(-self.numerator, self.denominator)
+  }
+}
+
+impl fmt::Display for Rational64 {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    if self.denominator == 1 {
+      write!(f, "{}", self.numerator)
+    } else {
+      write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+  }
+}
+impl fmt::Debug for Rational64 {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "Rational64({}/{})", self.numerator, self.denominator)
+  }
+}
+
+// Serialize as a plain pair, and reduce when deserializing, so that
+// equal values always compare (and hash) equal no matter where they came from.
+impl Serialize for Rational64 {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    (self.numerator, self.denominator).serialize(serializer)
+  }
+}
+impl<'de> Deserialize<'de> for Rational64 {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Rational64, D::Error> {
+    let (numerator, denominator): (i64, i64) = try!(Deserialize::deserialize(deserializer));
+    Rational64::from_i128s(numerator as i128, denominator as i128)
+      .ok_or_else(|| D::Error::custom("Rational64 with a denominator of 0, or too large to reduce"))
+  }
+}
+
+impl StewardData for Rational64 {}
+
+#[cfg (test)]
+mod tests {
+  use super::*;
+  use serde_json;
+  use bincode;
+
+  quickcheck! {
+    fn ordering_matches_cross_multiplication (a: i32, b: u16, c: i32, d: u16)->bool {
+      let (b, d) = (b as i64 + 1, d as i64 + 1);
+      let (first, second) = (Rational64::new (a as i64, b), Rational64::new (c as i64, d));
+      first.cmp (&second) == (a as i64 * d).cmp (&(c as i64 * b))
+    }
+    fn floor_and_ceil (a: i32, b: u16)->bool {
+      let value = Rational64::new (a as i64, b as i64 + 1);
+      let float = a as f64 / (b as f64 + 1.0);
+      value.floor() == float.floor() as i64 && value.ceil() == float.ceil() as i64
+    }
+    fn arithmetic_is_exact (a: i32, b: u16, c: i32, d: u16)->bool {
+      let (b, d) = (b as i64 + 1, d as i64 + 1);
+      let (first, second) = (Rational64::new (a as i64, b), Rational64::new (c as i64, d));
+      (first + second) - second == first && (first * second) == Rational64::new (a as i64 * c as i64, b*d)
+    }
+  }
+
+  #[test]
+  fn reduced() {
+    assert_eq!(Rational64::new(6, -4), Rational64::new(-3, 2));
+    assert_eq!(Rational64::new(6, -4).numerator(), -3);
+    assert_eq!(Rational64::new(6, -4).denominator(), 2);
+    assert_eq!(Rational64::new(0, -4), Rational64::zero());
+    assert_eq!(format!("{}", Rational64::new(10, 5)), "2");
+    assert_eq!(Rational64::new(7, 2) / Rational64::new(1, 3), 10);
+    assert_eq!(Rational64::new(1, 1 << 62).checked_mul(Rational64::new(1, 1 << 62)), None);
+  }
+
+  #[test]
+  fn serialization() {
+    let value = Rational64::new(-7, 3);
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(json, "[-7,3]");
+    assert_eq!(serde_json::from_str::<Rational64>(&json).unwrap(), value);
+    assert_eq!(serde_json::from_str::<Rational64>("[14,-6]").unwrap(), value);
+    assert!(serde_json::from_str::<Rational64>("[1,0]").is_err());
+    let bytes = bincode::serialize(&value, bincode::Infinite).unwrap();
+    assert_eq!(bincode::deserialize::<Rational64>(&bytes).unwrap(), value);
+  }
+}
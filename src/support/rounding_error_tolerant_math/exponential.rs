@@ -0,0 +1,58 @@
+use super::range::*;
+use super::series::*;
+
+// ln(2) << 62, rounded down and up.
+const LN_2_SHIFTED_62: [i64; 2] = [3196577161300663914, 3196577161300663915];
+
+/**
+
+2 to the power of -(time / half_life), scaled up by 1 << output_shift, for time >= 0.
+
+This is the factor by which anything with the given half-life has decayed after the given time. Like all Ranges, the result is guaranteed to include the exact value. It's computed with integer Range arithmetic, so it's the same on every platform.
+
+*/
+pub fn exponential_decay_factor(time: i64, half_life: i64, output_shift: u32) -> Range {
+  assert!(half_life > 0, "half-lives must be positive");
+  assert!(time >= 0, "exponential decay can't be evaluated backwards in time");
+  assert!(output_shift <= PRECISION_SHIFT,
+          "the decay factor can't be more precise than the internal precision");
+  let halvings = time / half_life;
+  if halvings >= output_shift as i64 + 2 {
+    // the exact factor is at most 1/4
+    return Range::new(0, 1);
+  }
+  let fraction = time % half_life;
+
+  // 2^(-fraction/half_life) = e^(-y), where y = ln(2) * fraction/half_life < ln(2).
+  // e^(-y) = 1 - y + y^2/2 - y^3/6 + ..., and since y < 1 the terms shrink,
+  // so the remainder is no bigger than the first omitted term.
+  // With y < ln(2), 18 terms leave a remainder below 2^-60.
+  let y = ((Range::new(LN_2_SHIFTED_62[0], LN_2_SHIFTED_62[1]) * fraction) / Range::exactly(half_life)) >> 2;
+  let sum = alternating_series(Range::exactly(1) << PRECISION_SHIFT, 18, |term, which| {
+    ((term * y) >> PRECISION_SHIFT) / Range::exactly(which as i64)
+  });
+  sum >> (PRECISION_SHIFT - output_shift) >> (halvings as u32)
+}
+
+#[cfg (test)]
+mod tests {
+  use super::*;
+
+  quickcheck! {
+    fn decay_factor_includes_exact (time: u32, half_life: u32)->bool {
+      let half_life = half_life as i64 + 1;
+      let factor = exponential_decay_factor (time as i64, half_life, 40);
+      let exact = (-(time as f64) / half_life as f64).exp2() * (1u64 << 40) as f64;
+      (factor.min() as f64) <= exact + 0.01 && (factor.max() as f64) >= exact - 0.01 &&
+        factor.max() - factor.min() <= 2
+    }
+  }
+
+  #[test]
+  fn exact_halvings() {
+    assert_eq!(exponential_decay_factor(0, 1000, 30), Range::exactly(1 << 30));
+    assert!(exponential_decay_factor(1000, 1000, 30).includes(&Range::exactly(1 << 29)));
+    assert!(exponential_decay_factor(3000, 1000, 30).includes(&Range::exactly(1 << 27)));
+    assert!(exponential_decay_factor(62000, 1000, 60).includes(&Range::exactly(0)));
+  }
+}
@@ -0,0 +1,95 @@
+use std::ops::{Add, Sub, Mul, Div, Neg, Shr, Shl, BitAnd, Not, AddAssign, SubAssign, MulAssign,
+               DivAssign, ShrAssign, ShlAssign};
+use std::hash::Hash;
+use std::fmt::{Debug, Display};
+use rand::Rng;
+
+/**
+
+The signed integer types that GenericRange, the polynomial solvers, and the trajectories can be built on.
+
+Everything in this module is written in terms of bits(), so a wider type simply gives you more headroom before the exponent has to grow. It is implemented for i64 (the default, used by Range) and i128 (used by Range128).
+
+*/
+pub trait RangeInteger: Copy + Ord + Hash + Debug + Display + Send + Sync + 'static + From<i64> +
+  Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> + Neg<Output = Self> +
+  Shr<u32, Output = Self> + Shl<u32, Output = Self> + BitAnd<Output = Self> + Not<Output = Self> +
+  AddAssign + SubAssign + MulAssign + DivAssign + ShrAssign<u32> + ShlAssign<u32> {
+  /// The total number of bits, including the sign bit.
+  fn bits() -> u32;
+  fn max_value() -> Self;
+  fn min_value() -> Self;
+  fn abs(self) -> Self;
+  fn signum(self) -> Self;
+  fn leading_zeros(self) -> u32;
+  fn checked_add(self, other: Self) -> Option<Self>;
+  fn checked_mul(self, other: Self) -> Option<Self>;
+  fn saturating_mul(self, other: Self) -> Self;
+  fn wrapping_sub(self, other: Self) -> Self;
+  fn to_f64(self) -> f64;
+  /// Rounds towards 0, like an `as` cast.
+  fn from_f64(value: f64) -> Self;
+  /// A uniformly random value of the whole type.
+  fn random<R: Rng>(generator: &mut R) -> Self;
+  /// A random value in [low, high). Used for the self-tests in the polynomial solvers.
+  fn random_in<R: Rng>(generator: &mut R, low: Self, high: Self) -> Self;
+}
+
+macro_rules! range_integer_impl {
+  ($integer: ident, $unsigned: ident, $bits: expr) => {
+impl RangeInteger for $integer {
+  fn bits() -> u32 {
+    $bits
+  }
+  fn max_value() -> $integer {
+    $integer::max_value()
+  }
+  fn min_value() -> $integer {
+    $integer::min_value()
+  }
+  fn abs(self) -> $integer {
+    $integer::abs(self)
+  }
+  fn signum(self) -> $integer {
+    $integer::signum(self)
+  }
+  fn leading_zeros(self) -> u32 {
+    $integer::leading_zeros(self)
+  }
+  fn checked_add(self, other: $integer) -> Option<$integer> {
+    $integer::checked_add(self, other)
+  }
+  fn checked_mul(self, other: $integer) -> Option<$integer> {
+    $integer::checked_mul(self, other)
+  }
+  fn saturating_mul(self, other: $integer) -> $integer {
+    $integer::saturating_mul(self, other)
+  }
+  fn wrapping_sub(self, other: $integer) -> $integer {
+    $integer::wrapping_sub(self, other)
+  }
+  fn to_f64(self) -> f64 {
+    self as f64
+  }
+  fn from_f64(value: f64) -> $integer {
+    value as $integer
+  }
+  fn random<R: Rng>(generator: &mut R) -> $integer {
+    let mut result: $integer = 0;
+    for _ in 0..$bits / 64 {
+      result = (result << 32 << 32) | (generator.gen::<u64>() as $integer);
+    }
+    result
+  }
+  fn random_in<R: Rng>(generator: &mut R, low: $integer, high: $integer) -> $integer {
+    assert!(low < high, "random_in needs a nonempty interval");
+    let span = high.wrapping_sub(low) as $unsigned as u128;
+    let value = ((generator.gen::<u64>() as u128) << 64) | (generator.gen::<u64>() as u128);
+    low.wrapping_add((value % span) as $integer)
+  }
+}
+  }
+}
+
+range_integer_impl!(i64, u64, 64);
+range_integer_impl!(i128, u128, 128);
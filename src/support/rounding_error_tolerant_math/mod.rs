@@ -1,4 +1,13 @@
+mod integer;
 mod range;
+mod root_set;
 mod polynomials;
+mod series;
+mod trigonometry;
+mod exponential;
+pub use self::integer::*;
 pub use self::range::*;
+pub use self::root_set::*;
 pub use self::polynomials::*;
+pub use self::trigonometry::*;
+pub use self::exponential::*;
@@ -1,6 +1,8 @@
 use std::cmp::{max, min};
 
+use super::integer::RangeInteger;
 use super::range::*;
+use super::root_set::{RootSet, ROOT_SET_CAPACITY};
 
 macro_rules! printlnerr(
     ($($arg:tt)*) => { {use std::io::Write;
@@ -16,40 +18,44 @@ A polynomial pseudo-solver, using Range.
 
 Returns a collection of ranges that include the exact roots. False-positives are possible.
 
-TODO: instead of Vec<Range>, these should return a stack-allocated type.
+Everything here is generic over the integer type of the Range; the inputs (min_input, max_input, and so on) use the same integer type as the coefficients.
+
+The results are returned in a RootSet, which is stack-allocated, so that predictors can call these in tight loops. Polynomials with up to MAX_POLYNOMIAL_TERMS terms (that is, degree 8) are solved without allocating at all; longer ones (such as the 11-term distance proxy of two degree-5 trajectories) still work, but allocate scratch space for their derivatives.
+
+The degree-general trajectory functions (the polynomial_* ones, which the cubic trajectories use) do allocate: they build their proxies with multiply_polynomials(), which returns a Vec. Only the solvers and the quadratic-specific trajectory functions avoid allocation.
 
 */
 
-pub fn roots_linear(coefficients: [Range; 2], min_input: i64, max_input: i64) -> Vec<Range> {
-  if coefficients[1] == Range::exactly(0) && !coefficients[0].includes_0() {
-    return Vec::new();
+pub fn roots_linear<T: RangeInteger>(coefficients: [GenericRange<T>; 2], min_input: T, max_input: T) -> RootSet<T> {
+  if coefficients[1] == GenericRange::exactly(T::from(0)) && !coefficients[0].includes_0() {
+    return RootSet::new();
   }
   if let Some(result) = ((-coefficients[0]) / coefficients[1]).clamp_to_0_exponent() {
     if result.max() >= min_input && result.min() <= max_input {
-      return vec![result];
+      return RootSet::with_root(result);
     }
   }
-  Vec::new()
+  RootSet::new()
 }
-pub fn roots_quadratic(terms: [Range; 3], min_input: i64, max_input: i64) -> Vec<Range> {
+pub fn roots_quadratic<T: RangeInteger>(terms: [GenericRange<T>; 3], min_input: T, max_input: T) -> RootSet<T> {
   let a = terms[2];
   let b = terms[1];
   let c = terms[0];
-  let discriminant = b.squared() - a * c * Range::exactly(4);
+  let discriminant = b.squared() - a * c * GenericRange::exactly(T::from(4));
   // printlnerr!(" discriminant {:?}", discriminant);
   // printlnerr!("confirm results: {:?}", roots_derivative_based (& terms));
 
-  if discriminant < 0 {
-    return Vec::new();
+  if discriminant < T::from(0) {
+    return RootSet::new();
   }
   let sqrt = discriminant.sqrt()
     .expect("I thought we just ruled out the case where the square root would be nonexistent");
   // printlnerr!(" sqrt {:?}", sqrt);
-  let result_0 = (-b - sqrt) / (a * 2);
-  let result_1 = (-b + sqrt) / (a * 2);
+  let result_0 = (-b - sqrt) / (a * T::from(2));
+  let result_1 = (-b + sqrt) / (a * T::from(2));
   // printlnerr!(" result 0 {:?}", result_0);
   // printlnerr!(" result 1 {:?}", result_1);
-  let mut results = Vec::new();
+  let mut results = RootSet::new();
   if let Some(result) = result_0.clamp_to_0_exponent() {
     if result.max() >= min_input && result.min() <= max_input {
       results.push(result);
@@ -57,7 +63,7 @@ pub fn roots_quadratic(terms: [Range; 3], min_input: i64, max_input: i64) -> Vec
   }
   if let Some(result) = result_1.clamp_to_0_exponent() {
     if result.max() >= min_input && result.min() <= max_input {
-      if results.last().map_or(false, |whatever| result.min() < whatever.min()) {
+      if results.last().map_or(false, |whatever: &GenericRange<T>| result.min() < whatever.min()) {
         results.insert(0, result);
       } else {
         results.push(result);
@@ -79,32 +85,47 @@ pub fn roots_quadratic(terms: [Range; 3], min_input: i64, max_input: i64) -> Vec
 }
 
 
-fn find_root_search <Metadata: Copy, InputStrategy: Fn (i64, i64, Range, Range, Metadata)->i64, MetadataGenerator: Fn (Range)->Metadata, MetadataTransformer: Fn (Metadata, Range, Range)->Metadata> (terms: &[Range],
+/**
+
+Cubic and quartic polynomials don't have a closed form that's practical to evaluate with Range, so these bound the roots using the derivative instead. They are thin wrappers around roots_derivative_based(), which works for any number of terms; they only fix the number of terms in the type.
+
+The roots of the derivative (found by roots(), which uses roots_quadratic for a quadratic derivative and recurses for higher degrees) split the input range into pieces where the polynomial is monotonic for every choice of coefficients within the Ranges. Each piece can contain at most one root, and only if the polynomial's value at its endpoints could have opposite signs, so a regula falsi (Illinois-style secant) search narrows it down, falling back to the midpoint whenever the secant step wouldn't land strictly between the current endpoints. The pieces around the derivative's roots are returned whole unless they are known to be away from 0. So, like the other solvers, these never exclude an exact root, but may include extra inputs.
+
+*/
+pub fn roots_cubic<T: RangeInteger>(terms: [GenericRange<T>; 4], min_input: T, max_input: T) -> RootSet<T> {
+  roots_derivative_based(&terms, min_input, max_input)
+}
+pub fn roots_quartic<T: RangeInteger>(terms: [GenericRange<T>; 5], min_input: T, max_input: T) -> RootSet<T> {
+  roots_derivative_based(&terms, min_input, max_input)
+}
+
+fn find_root_search <T: RangeInteger, Metadata: Copy, InputStrategy: Fn (T, T, GenericRange<T>, GenericRange<T>, Metadata)->T, MetadataGenerator: Fn (GenericRange<T>)->Metadata, MetadataTransformer: Fn (Metadata, GenericRange<T>, GenericRange<T>)->Metadata> (terms: &[GenericRange<T>],
                     min_only: bool,
                     max_only: bool,
-                    input_1: i64,
-                    input_2: i64,
-                    value_1: Range,
-                    value_2: Range,
+                    input_1: T,
+                    input_2: T,
+                    value_1: GenericRange<T>,
+                    value_2: GenericRange<T>,
                     value_1_metadata: Metadata,
                     input_strategy: &InputStrategy,
                     metadata_generator: &MetadataGenerator,
                     metadata_transformer: &MetadataTransformer)
-                    -> (i64, i64) {
+                    -> (T, T) {
+  let zero = T::from(0);
   assert!(!(value_1.includes_0() && value_2.includes_0()));
   if !min_only {
-    assert!((value_1 < 0) != (value_2 < 0));
+    assert!((value_1 < zero) != (value_2 < zero));
   }
   if !max_only {
-    assert!((value_1 > 0) != (value_2 > 0));
+    assert!((value_1 > zero) != (value_2 > zero));
   }
 
-  let mut input_1: i64 = input_1;
-  let mut input_2: i64 = input_2;
-  let mut value_1: Range = value_1;
+  let mut input_1: T = input_1;
+  let mut input_2: T = input_2;
+  let mut value_1: GenericRange<T> = value_1;
   let mut value_1_metadata = value_1_metadata;
-  let mut value_2: Range = value_2;
-  let mut result_for_other: i64 = 0;
+  let mut value_2: GenericRange<T> = value_2;
+  let mut result_for_other: T = zero;
   let mut min_only = min_only;
   loop {
     let input = input_strategy (input_1, input_2, value_1, value_2, value_1_metadata);
@@ -116,12 +137,12 @@ fn find_root_search <Metadata: Copy, InputStrategy: Fn (i64, i64, Range, Range,
 
     let closer_to_1;
     if min_only {
-      closer_to_1 = (value > 0) != (value_2 > 0);
+      closer_to_1 = (value > zero) != (value_2 > zero);
     } else if max_only {
-      closer_to_1 = (value < 0) != (value_2 < 0);
+      closer_to_1 = (value < zero) != (value_2 < zero);
     } else {
-      closer_to_1 = (value > 0) != (value_2 > 0);
-      let other_closer_to_1 = (value < 0) != (value_2 < 0);
+      closer_to_1 = (value > zero) != (value_2 > zero);
+      let other_closer_to_1 = (value < zero) != (value_2 < zero);
 
       if closer_to_1 != other_closer_to_1 {
         min_only = true;
@@ -154,30 +175,30 @@ fn find_root_search <Metadata: Copy, InputStrategy: Fn (i64, i64, Range, Range,
     value_2 = value;
   }
   if max_only {
-    assert!((value_1 < 0) != (value_2 < 0));
-    (if value_1 < 0 { input_1 } else { input_2 }, result_for_other)
+    assert!((value_1 < zero) != (value_2 < zero));
+    (if value_1 < zero { input_1 } else { input_2 }, result_for_other)
   } else {
-    assert!((value_1 > 0) != (value_2 > 0));
-    (if value_1 > 0 { input_1 } else { input_2 }, result_for_other)
+    assert!((value_1 > zero) != (value_2 > zero));
+    (if value_1 > zero { input_1 } else { input_2 }, result_for_other)
   }
 }
 
-fn find_root_search_default (terms: &[Range], min_only: bool,
+fn find_root_search_default <T: RangeInteger> (terms: &[GenericRange<T>], min_only: bool,
                     max_only: bool,
-                    input_1: i64,
-                    input_2: i64,
-                    value_1: Range,
-                    value_2: Range)
-                    -> (i64, i64) {
+                    input_1: T,
+                    input_2: T,
+                    value_1: GenericRange<T>,
+                    value_2: GenericRange<T>)
+                    -> (T, T) {
   
-  let floating = | whatever: Range | (whatever.internal_min() as f64)*(2f64.powi (whatever.exponent() as i32));
+  let floating = | whatever: GenericRange<T> | whatever.internal_min().to_f64()*(2f64.powi (whatever.exponent() as i32));
   let relaxed_result = find_root_search (terms, min_only, max_only, input_1, input_2, value_1, value_2, floating (value_1),
-    & |input_1, input_2, _, value_2, value_1_metadata| {
+    & |input_1: T, input_2: T, _, value_2, value_1_metadata| {
     let mut input;
     let value_2 = floating (value_2);
     let denominator = value_2 - value_1_metadata;
-    input = ((input_2 as f64) -
-             value_2 * ((input_2 as f64) - (input_1 as f64)) / denominator) as i64;
+    input = T::from_f64(input_2.to_f64() -
+             value_2 * (input_2.to_f64() - input_1.to_f64()) / denominator);
     if input.cmp(&input_2) != input.cmp(&input_1).reverse() {
       input = average_round_towards_neginf(input_1, input_2);
     }
@@ -193,14 +214,14 @@ fn find_root_search_default (terms: &[Range], min_only: bool,
     });
   if cfg! (debug_assertions) {
   let strict_result = find_root_search (terms, min_only, max_only, input_1, input_2, value_1, value_2, value_1,
-    & |input_1, input_2, _, value_2, value_1_metadata| {
+    & |input_1: T, input_2: T, _, value_2: GenericRange<T>, value_1_metadata: GenericRange<T>| {
     let mut input;
     let denominator = (value_2 - value_1_metadata).rounded_to_middle_towards_neginf();
     if denominator.includes_0() {
       input = average_round_towards_neginf(input_1, input_2);
     } else {
-      input = (Range::exactly(input_2) -
-               value_2 * (Range::exactly(input_2) - Range::exactly(input_1)) / denominator)
+      input = (GenericRange::exactly(input_2) -
+               value_2 * (GenericRange::exactly(input_2) - GenericRange::exactly(input_1)) / denominator)
         .clamp_to_0_exponent()
         .unwrap()
         .min();
@@ -209,13 +230,13 @@ fn find_root_search_default (terms: &[Range], min_only: bool,
       }
     }
     input
-    }, & | whatever: Range | whatever, & | value,_,_ | value >> 1);
+    }, & | whatever: GenericRange<T> | whatever, & | value: GenericRange<T>,_,_ | value >> 1);
   assert! (relaxed_result == strict_result);
   }
   relaxed_result
 }
 
-fn find_root(terms: &[Range], min: i64, max: i64) -> Option<Range> {
+fn find_root<T: RangeInteger>(terms: &[GenericRange<T>], min: T, max: T) -> Option<GenericRange<T>> {
   if min >= max {
     return None;
   }
@@ -225,10 +246,10 @@ fn find_root(terms: &[Range], min: i64, max: i64) -> Option<Range> {
 
   if min_value.includes_0() {
     if max_value.includes_0() {
-      Some(Range::new(min, max))
+      Some(GenericRange::new(min, max))
     } else {
-      let search_by_min = max_value > 0;
-      Some(Range::new(min,
+      let search_by_min = max_value > T::from(0);
+      Some(GenericRange::new(min,
                       find_root_search_default(terms,
                                        search_by_min,
                                        !search_by_min,
@@ -239,8 +260,8 @@ fn find_root(terms: &[Range], min: i64, max: i64) -> Option<Range> {
                         .0))
     }
   } else if max_value.includes_0() {
-    let search_by_min = min_value > 0;
-    Some(Range::new(find_root_search_default(terms,
+    let search_by_min = min_value > T::from(0);
+    Some(GenericRange::new(find_root_search_default(terms,
                                      search_by_min,
                                      !search_by_min,
                                      min,
@@ -259,7 +280,7 @@ fn find_root(terms: &[Range], min: i64, max: i64) -> Option<Range> {
                                                             max,
                                                             min_value,
                                                             max_value);
-    Some(Range::new_either_order(result_for_min, result_for_max))
+    Some(GenericRange::new_either_order(result_for_min, result_for_max))
   }
   // return find_root_search (terms, false, min, max,
   // let mut lower_bound = min;
@@ -285,34 +306,47 @@ fn find_root(terms: &[Range], min: i64, max: i64) -> Option<Range> {
   // Some(Range::new(lower_bound, upper_bound))
 
 }
-fn collect_root(terms: &[Range], min: i64, max: i64, bucket: &mut Vec<Option<Range>>) {
-  bucket.push(find_root(terms, min, max));
-}
+/// The most terms the solvers can handle with their scratch space on the stack. Longer polynomials use the heap.
+pub const MAX_POLYNOMIAL_TERMS: usize = 9;
 
+// The bucket can't overflow, because there is one more bucket entry than there are extrema,
+// and the extrema are in a RootSet.
+fn collect_root<T: RangeInteger>(terms: &[GenericRange<T>], min: T, max: T, bucket: &mut [Option<GenericRange<T>>; ROOT_SET_CAPACITY + 1], bucket_len: &mut usize) {
+  bucket[*bucket_len] = find_root(terms, min, max);
+  *bucket_len += 1;
+}
 
-fn roots_derivative_based(terms: &[Range], min_input: i64, max_input: i64) -> Vec<Range> {
 
-  let derivative: Vec<Range> = terms[1..]
-    .iter()
-    .enumerate()
-    .map(|(which, term)| term * (which as i64 + 1))
-    .collect();
+fn roots_derivative_based<T: RangeInteger>(terms: &[GenericRange<T>], min_input: T, max_input: T) -> RootSet<T> {
+  let derivative_term = |(which, term): (usize, &GenericRange<T>)| term * T::from(which as i64 + 1);
+  let mut stack_derivative = [GenericRange::zero(); MAX_POLYNOMIAL_TERMS - 1];
+  let heap_derivative: Vec<GenericRange<T>>;
+  let derivative: &[GenericRange<T>] = if terms.len() <= MAX_POLYNOMIAL_TERMS {
+    for (which, term) in terms[1..].iter().enumerate() {
+      stack_derivative[which] = derivative_term((which, term));
+    }
+    &stack_derivative[..terms.len() - 1]
+  } else {
+    heap_derivative = terms[1..].iter().enumerate().map(derivative_term).collect();
+    &heap_derivative
+  };
   // printlnerr!(" Derivative {:?}", derivative);
-  let extrema = roots(derivative.as_slice(), min_input, max_input);
+  let extrema = roots(derivative, min_input, max_input);
   // printlnerr!("extrema {:?}", extrema);
-  let mut bucket = Vec::new();
-  let mut results = Vec::new();
+  let mut bucket = [None; ROOT_SET_CAPACITY + 1];
+  let mut bucket_len = 0;
+  let mut results = RootSet::new();
   if extrema.is_empty() {
-    collect_root(terms, min_input, max_input, &mut bucket);
+    collect_root(terms, min_input, max_input, &mut bucket, &mut bucket_len);
   } else {
-    collect_root(terms, min_input, extrema[0].min(), &mut bucket);
+    collect_root(terms, min_input, extrema[0].min(), &mut bucket, &mut bucket_len);
     for which in 0..(extrema.len() - 1) {
       collect_root(terms,
                    max(extrema[which].max(), min_input),
                    min(extrema[which + 1].min(), max_input),
-                   &mut bucket);
+                   &mut bucket, &mut bucket_len);
     }
-    collect_root(terms, extrema.last().unwrap().max(), max_input, &mut bucket);
+    collect_root(terms, extrema.last().unwrap().max(), max_input, &mut bucket, &mut bucket_len);
   }
   // if we found a root on both sides of a derivative-root, we know that the derivative-root is bounded away from 0
   for which in 0..extrema.len() {
@@ -334,29 +368,31 @@ fn roots_derivative_based(terms: &[Range], min_input: i64, max_input: i64) -> Ve
   results
 
 }
-pub fn roots(terms: &[Range], min: i64, max: i64) -> Vec<Range> {
+pub fn roots<T: RangeInteger>(terms: &[GenericRange<T>], min: T, max: T) -> RootSet<T> {
   let mut terms = terms;
-  while terms.last().map_or(false, |term| term == &Range::exactly(0)) {
+  while terms.last().map_or(false, |term| term == &GenericRange::exactly(T::from(0))) {
     terms = &terms[..terms.len() - 1]
   }
   match terms.len() {
-    0 => vec![Range::new(min, max)],
+    0 => RootSet::with_root(GenericRange::new(min, max)),
     1 => {
-      if terms[0].internal_min() <= 0 && terms[0].internal_max() >= 0 {
-        vec![Range::new(min, max)]
+      if terms[0].internal_min() <= T::from(0) && terms[0].internal_max() >= T::from(0) {
+        RootSet::with_root(GenericRange::new(min, max))
       } else {
-        Vec::new()
+        RootSet::new()
       }
     }
     2 => roots_linear([terms[0], terms[1]], min, max),
     3 => roots_quadratic([terms[0], terms[1], terms[2]], min, max),
+    4 => roots_cubic([terms[0], terms[1], terms[2], terms[3]], min, max),
+    5 => roots_quartic([terms[0], terms[1], terms[2], terms[3], terms[4]], min, max),
     _ => roots_derivative_based(terms, min, max),
   }
 }
 
-pub fn evaluate(terms: &[Range], input: i64) -> Range {
-  let mut factor = Range::exactly(1);
-  let mut result = Range::exactly(0);
+pub fn evaluate<T: RangeInteger>(terms: &[GenericRange<T>], input: T) -> GenericRange<T> {
+  let mut factor = GenericRange::exactly(T::from(1));
+  let mut result = GenericRange::exactly(T::from(0));
   for term in terms.iter() {
     result = result + (term * factor);
     factor = factor * input;
@@ -364,16 +400,21 @@ pub fn evaluate(terms: &[Range], input: i64) -> Range {
   result
 }
 
-pub fn multiply_polynomials(terms_0: &[Range], terms_1: &[Range]) -> Vec<Range> {
-  (0..terms_0.len() + terms_1.len() - 1)
-    .map(|new_index| {
-      (max(terms_1.len(), new_index + 1) - terms_1.len()..min(terms_0.len(), new_index + 1))
-        .map(|view| terms_0[view] * terms_1[new_index - view])
-        .sum()
-    })
-    .collect()
+pub fn multiply_polynomials<T: RangeInteger>(terms_0: &[GenericRange<T>], terms_1: &[GenericRange<T>]) -> Vec<GenericRange<T>> {
+  let mut result = vec![GenericRange::zero(); terms_0.len() + terms_1.len() - 1];
+  multiply_polynomials_into(terms_0, terms_1, &mut result);
+  result
+}
+
+/// Like multiply_polynomials, but writes into a buffer, which must have exactly the right length.
+pub fn multiply_polynomials_into<T: RangeInteger>(terms_0: &[GenericRange<T>], terms_1: &[GenericRange<T>], result: &mut [GenericRange<T>]) {
+  assert!(result.len() == terms_0.len() + terms_1.len() - 1);
+  for (new_index, term) in result.iter_mut().enumerate() {
+    *term = (max(terms_1.len(), new_index + 1) - terms_1.len()..min(terms_0.len(), new_index + 1))
+      .map(|view| terms_0[view] * terms_1[new_index - view])
+      .sum()
+  }
 }
-use rand::Rng;
 use rand;
 // when coercing an update to land on an integer value, we obviously have a possible rounding error of up to 2 units (one from dividing the velocity, one from dividing the acceleration).
 // But that's not all. The multiplications also have rounding error if they have to prevent overflows.
@@ -389,85 +430,199 @@ use rand;
 // more than 8 times the 1+2^{-28} figure for the same actual distance. Less than 16, though.
 // So chopping off another 4 bits will be enough: 1+2^{-24}.
 // So any constant error term is associated with a maximum distance traveled that will have no more than that much error.
-pub fn max_error_for_distance_traveled(distance: i64) -> i64 {
+// (Wider integer types keep more than 31 bits of each factor, so this is a conservative bound for them.)
+pub fn max_error_for_distance_traveled<T: RangeInteger>(distance: T) -> T {
   right_shift_round_up(distance, 24)
 }
 
 const DO_TESTS: bool = cfg!(debug_assertions);
 
+/// Why a trajectory's origin couldn't be moved.
+#[derive (Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum TrajectoryError {
+  /// The rounding error of the move would exceed the max error. This happens when the trajectory would
+  /// travel further than the distance the max error was computed for (see max_error_for_distance_traveled).
+  /// Moving the origin in smaller steps avoids it.
+  TraveledTooFar,
+  /// A coordinate would no longer fit in the integer type.
+  Overflow,
+  /// The origin would have to move by a fraction of a tick. This happens when comparing trajectories
+  /// whose base times aren't a whole number of ticks apart.
+  FractionalTicks,
+  /// The origin would have to move backwards in time.
+  BackwardsInTime,
+}
+
+impl ::std::fmt::Display for TrajectoryError {
+  fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+    match *self {
+      TrajectoryError::TraveledTooFar => write!(f, "trajectory traveled further than its max distance at once"),
+      TrajectoryError::Overflow => write!(f, "trajectory coordinate overflowed"),
+      TrajectoryError::FractionalTicks => write!(f, "trajectory base times are a fraction of a tick apart"),
+      TrajectoryError::BackwardsInTime => write!(f, "trajectory origin can't move backwards in time"),
+    }
+  }
+}
+impl ::std::error::Error for TrajectoryError {
+  fn description(&self) -> &str {
+    match *self {
+      TrajectoryError::TraveledTooFar => "trajectory traveled too far",
+      TrajectoryError::Overflow => "trajectory overflowed",
+      TrajectoryError::FractionalTicks => "trajectory base times are a fraction of a tick apart",
+      TrajectoryError::BackwardsInTime => "trajectory moved backwards in time",
+    }
+  }
+}
+
+fn change_for_move<T: RangeInteger>(change: GenericRange<T>, max_error: T) -> Result<T, TrajectoryError> {
+  let change = try!(change.clamp_to_0_exponent().ok_or(TrajectoryError::Overflow));
+  if change.max() - change.min() > max_error * T::from(2) {
+    return Err(TrajectoryError::TraveledTooFar);
+  }
+  Ok(change.rounded_towards_0())
+}
+
 // We require the user to pass in a max error value – specifically, the one that they use with
 // quadratic_trajectories_possible_distance_crossing_intervals –
 // so that we can check to make sure they didn't go beyond the bounds of what they tested for.
-pub fn quadratic_move_origin_rounding_change_towards_0(terms: &mut [i64],
-                                                       origin: i64,
-                                                       input_scale_shift: u32,
-                                                       max_error: i64)
-                                                       -> bool {
-  let distance_traveled = ((Range::exactly(terms[1]) * origin) >> input_scale_shift) +
-                          ((Range::exactly(terms[2]) * origin * origin) >> (input_scale_shift * 2));
+//
+// On failure, the terms are left unchanged.
+pub fn try_quadratic_move_origin_rounding_change_towards_0<T: RangeInteger>(terms: &mut [T],
+                                                           origin: T,
+                                                           input_scale_shift: u32,
+                                                           max_error: T)
+                                                           -> Result<(), TrajectoryError> {
+  let distance_traveled = try!(change_for_move(((GenericRange::exactly(terms[1]) * origin) >> input_scale_shift) +
+                          ((GenericRange::exactly(terms[2]) * origin * origin) >> (input_scale_shift * 2)), max_error));
+  let velocity_change = try!(((GenericRange::exactly(terms[2]) * origin) >> (input_scale_shift - 1))
+    .clamp_to_0_exponent()
+    .ok_or(TrajectoryError::Overflow))
+    .rounded_towards_0();
+  let position = try!(terms[0].checked_add(distance_traveled).ok_or(TrajectoryError::Overflow));
+  let velocity = try!(terms[1].checked_add(velocity_change).ok_or(TrajectoryError::Overflow));
 
-  if distance_traveled.max() - distance_traveled.min() > max_error * 2 {
-    printlnerr!("overflow-ish in quadratic_move_origin_rounding_change_towards_0; error size \
-                 exceeded the given max error");
-    return false;
-  }
-  let mut between_time = 0;
-  let mut confirm = [Range::exactly(0);3];
+  let mut between_time = T::from(0);
+  let mut confirm = [GenericRange::exactly(T::from(0));3];
   if DO_TESTS {
-    between_time = rand::thread_rng().gen_range(0, origin + 1);
+    between_time = T::random_in(&mut rand::thread_rng(), T::from(0), origin + T::from(1));
     confirm = quadratic_future_proxy_minimizing_error(terms, between_time, input_scale_shift, max_error);
   }
-  terms[0] += distance_traveled.rounded_towards_0();
-  terms[1] += ((Range::exactly(terms[2]) * origin) >> (input_scale_shift - 1)).rounded_towards_0();
+  terms[0] = position;
+  terms[1] = velocity;
   if DO_TESTS {
     let experimented = evaluate(&confirm, origin - between_time) >> (input_scale_shift * 2);
     // printlnerr!("experimented {}, actually {}", experimented, terms [0]);
-    assert!(experimented.includes(&Range::exactly(terms[0])));
+    assert!(experimented.includes(&GenericRange::exactly(terms[0])));
   }
-  true
+  Ok(())
 }
 
-pub fn quadratic_future_proxy_minimizing_error(terms: &[i64],
-                                               origin: i64,
+pub fn quadratic_move_origin_rounding_change_towards_0<T: RangeInteger>(terms: &mut [T],
+                                                       origin: T,
+                                                       input_scale_shift: u32,
+                                                       max_error: T)
+                                                       -> bool {
+  match try_quadratic_move_origin_rounding_change_towards_0(terms, origin, input_scale_shift, max_error) {
+    Ok(()) => true,
+    Err(error) => {
+      printlnerr!("overflow-ish in quadratic_move_origin_rounding_change_towards_0: {}", error);
+      false
+    }
+  }
+}
+
+/// The longest time (up to `limit`) that a quadratic trajectory can be moved forward in one step
+/// without traveling further than `max_distance`, or 0 if even one time unit is too far.
+/// Moving the origin in steps of at most this long keeps the rounding error within
+/// max_error_for_distance_traveled(max_distance).
+pub fn quadratic_max_safe_move<T: RangeInteger>(terms: &[T], input_scale_shift: u32, max_distance: T, limit: T) -> T {
+  let within = |time: T| {
+    let distance = ((GenericRange::exactly(terms[1].abs()) * time) >> input_scale_shift) +
+                   ((GenericRange::exactly(terms[2].abs()) * time * time) >> (input_scale_shift * 2));
+    distance.clamp_to_0_exponent().map_or(false, |distance| distance.max() <= max_distance)
+  };
+  if within(limit) {
+    return limit;
+  }
+  let (mut safe, mut too_far) = (T::from(0), limit);
+  while too_far - safe > T::from(1) {
+    let middle = safe + (too_far - safe) / T::from(2);
+    if within(middle) {
+      safe = middle;
+    } else {
+      too_far = middle;
+    }
+  }
+  safe
+}
+
+/// Like quadratic_max_safe_move, but for polynomials of any degree.
+pub fn polynomial_max_safe_move<T: RangeInteger>(terms: &[T], input_scale_shift: u32, max_distance: T, limit: T) -> T {
+  let within = |time: T| {
+    let mut power = GenericRange::exactly(T::from(1));
+    let mut distance = GenericRange::exactly(T::from(0));
+    for (exponent, term) in terms.iter().enumerate().skip(1) {
+      power = power * time;
+      distance = distance + ((GenericRange::exactly(term.abs()) * power) >> (input_scale_shift * exponent as u32));
+    }
+    distance.clamp_to_0_exponent().map_or(false, |distance| distance.max() <= max_distance)
+  };
+  if within(limit) {
+    return limit;
+  }
+  let (mut safe, mut too_far) = (T::from(0), limit);
+  while too_far - safe > T::from(1) {
+    let middle = safe + (too_far - safe) / T::from(2);
+    if within(middle) {
+      safe = middle;
+    } else {
+      too_far = middle;
+    }
+  }
+  safe
+}
+
+pub fn quadratic_future_proxy_minimizing_error<T: RangeInteger>(terms: &[T],
+                                               origin: T,
                                                input_scale_shift: u32,
-                                               max_error: i64)
-                                               -> [Range; 3] {
+                                               max_error: T)
+                                               -> [GenericRange<T>; 3] {
   // in the constant term, preserve the error of 2 units noted above.
   // Multiplication error term is about (term 1*time since original origin) >> 30+shift + (term 2*time since original origin squared) >> 29+shift*2
   // but time since original origin is actually "origin" + the input of the quadratic we're creating,
   // this error is actually quadratic.
-  [(Range::new(terms[0] - 2 - max_error, terms[0] + 2 + max_error) << (input_scale_shift * 2)) +
-   ((Range::exactly(terms[1]) * origin) << input_scale_shift) +
-   (Range::exactly(terms[2]) * origin * origin),
+  [(GenericRange::new(terms[0] - T::from(2) - max_error, terms[0] + T::from(2) + max_error) << (input_scale_shift * 2)) +
+   ((GenericRange::exactly(terms[1]) * origin) << input_scale_shift) +
+   (GenericRange::exactly(terms[2]) * origin * origin),
 
-   (Range::exactly(terms[1]) << input_scale_shift) + ((Range::exactly(terms[2]) * origin) << 1),
+   (GenericRange::exactly(terms[1]) << input_scale_shift) + ((GenericRange::exactly(terms[2]) * origin) << 1),
 
-   Range::exactly(terms[2])]
+   GenericRange::exactly(terms[2])]
 }
 
 
-pub fn time_until_which_quadratic_trajectory_may_remain_in_bounds(start_time: i64,
-                                                                  trajectory: &[[i64; 3]],
-                                                                  bounds: &[[i64; 2]],
+pub fn time_until_which_quadratic_trajectory_may_remain_in_bounds<T: RangeInteger>(start_time: T,
+                                                                  trajectory: &[[T; 3]],
+                                                                  bounds: &[[T; 2]],
                                                                   input_scale_shift: u32,
-                                                                  max_error: i64)
-                                                                  -> Option<i64> {
+                                                                  max_error: T)
+                                                                  -> Option<T> {
   assert!(trajectory.len() == bounds.len());
   assert!(trajectory.len() > 0);
   let mut min_input = start_time;
-  let mut max_input = i64::max_value() - max(0, start_time);
+  let mut max_input = T::max_value() - max(T::from(0), start_time);
   // printlnerr!("begin {:?} {:?} {:?}", start_time, trajectory, bounds);
   for (third, more) in trajectory.iter().zip(bounds.iter()) {
     let mut rubble =
-      quadratic_future_proxy_minimizing_error(third, 0, input_scale_shift, max_error);
-    rubble[0] = rubble[0] - (Range::new(more[0], more[1]) << (input_scale_shift * 2));
+      quadratic_future_proxy_minimizing_error(third, T::from(0), input_scale_shift, max_error);
+    rubble[0] = rubble[0] - (GenericRange::new(more[0], more[1]) << (input_scale_shift * 2));
     let possible_overlap_times = roots(&rubble, min_input, max_input);
     // printlnerr!("roots {:?} {:?}", rubble, possible_overlap_times);
     if let Some((this_min, this_max)) = if possible_overlap_times.is_empty() {
       None
     } else if possible_overlap_times.len() == 2 &&
                                                   possible_overlap_times[0].max() >=
-                                                  possible_overlap_times[1].min() - 1 {
+                                                  possible_overlap_times[1].min() - T::from(1) {
       if possible_overlap_times[0].min() <= start_time &&
          possible_overlap_times[1].max() >= start_time {
         Some((possible_overlap_times[0].min(), possible_overlap_times[1].max()))
@@ -492,20 +647,20 @@ pub fn time_until_which_quadratic_trajectory_may_remain_in_bounds(start_time: i6
   Some(max_input)
 }
 
-
-pub fn quadratic_trajectories_possible_distance_crossing_intervals(distance: i64,
-                                                                   first: (i64, &[[i64; 3]]),
-                                                                   second: (i64, &[[i64; 3]]),
+// The trajectories may have any number of dimensions, as long as they both have the same number;
+// the squared distance is the sum of the squared displacements in each dimension.
+pub fn quadratic_trajectories_possible_distance_crossing_intervals<T: RangeInteger>(distance: T,
+                                                                   first: (T, &[[T; 3]]),
+                                                                   second: (T, &[[T; 3]]),
                                                                    input_scale_shift: u32,
-                                                                   max_error: i64)
-                                                                   -> Vec<Range> {
+                                                                   max_error: T)
+                                                                   -> RootSet<T> {
   assert!(first.1.len() == second.1.len());
   assert!(first.1.len() > 0);
   let base = max(first.0, second.0);
-  let mut proxy =
-    [Range::exactly(0), Range::exactly(0), Range::exactly(0), Range::exactly(0), Range::exactly(0)];
-  let mut min_input = 0;
-  let mut max_input = i64::max_value() - max(0, base);
+  let mut proxy = [GenericRange::exactly(T::from(0)); 5];
+  let mut min_input = T::from(0);
+  let mut max_input = T::max_value() - max(T::from(0), base);
   for (third, more) in first.1.iter().zip(second.1.iter()) {
     let mut rubble = quadratic_future_proxy_minimizing_error(third.as_ref(),
                                                              base - first.0,
@@ -519,27 +674,29 @@ pub fn quadratic_trajectories_possible_distance_crossing_intervals(distance: i64
       rubble[index] = rubble[index] - bravo[index];
     }
     let this_dimension_tester =
-      [rubble[0] + (Range::error_sized(distance) << (input_scale_shift * 2)), rubble[1], rubble[2]];
+      [rubble[0] + (GenericRange::error_sized(distance) << (input_scale_shift * 2)), rubble[1], rubble[2]];
     let possible_overlap_times = roots(&this_dimension_tester, min_input, max_input);
     // printlnerr!("one-dimensional proxy: {:?} {:?} {:?} {:?}", min_input, max_input, this_dimension_tester, possible_overlap_times );
 
     if possible_overlap_times.is_empty() {
 
-      return Vec::new();
+      return RootSet::new();
     } else {
       min_input = max(min_input, possible_overlap_times[0].min());
       max_input = min(max_input, possible_overlap_times.last().unwrap().max());
       if min_input > max_input {
-        return Vec::new();
+        return RootSet::new();
       }
     }
-    for (which, value) in multiply_polynomials(&rubble, &rubble).into_iter().enumerate() {
-      proxy[which] = proxy[which] + value
+    let mut squared = [GenericRange::zero(); 5];
+    multiply_polynomials_into(&rubble, &rubble, &mut squared);
+    for (which, value) in squared.iter().enumerate() {
+      proxy[which] = proxy[which] + *value
     }
   }
-  proxy[0] = proxy[0] - (Range::exactly(distance).squared() << (input_scale_shift * 4));
-  let real_distance_squared = |input| {
-    let mut result = 0i64;
+  proxy[0] = proxy[0] - (GenericRange::exactly(distance).squared() << (input_scale_shift * 4));
+  let real_distance_squared = |input: T| {
+    let mut result = T::from(0);
     for (third, more) in first.1.iter().zip(second.1.iter()) {
       let mut rubble = third.clone();
       if !quadratic_move_origin_rounding_change_towards_0(&mut rubble,
@@ -571,37 +728,37 @@ pub fn quadratic_trajectories_possible_distance_crossing_intervals(distance: i64
     Some(result)
 
   };
-  let test = |input| {
+  let test = |input: T| {
     let evaluated = evaluate(&proxy, input);
     // printlnerr!("input: {}, base: {}, evaluated: {}", input, base, evaluated);
-    if input < 0 || input > 1i64 << 32 {
+    if input < T::from(0) || input > T::from(1) << 32 {
       return evaluated;
     }
     if let Some(distance_squared) = real_distance_squared(input + base) {
       let real = distance_squared - distance * distance;
       // printlnerr!("real: {}", real);
-      assert!((evaluated >> (input_scale_shift * 4)).includes(&Range::exactly(real)));
+      assert!((evaluated >> (input_scale_shift * 4)).includes(&GenericRange::exactly(real)));
     }
     evaluated
   };
-  let test_empty_interval = |start, stop| {
+  let test_empty_interval = |start: T, stop: T| {
     // Currently, evaluate() is more permissive than it theoretically needs to be.
     // It could include 0 even if the polynomial couldn't actually emit 0 from that input.
     // roots_derivative_based() uses evaluate() directly, so it's fine to assume that evaluate() is correct.
     // However, roots_quadratic() might return a slightly tighter result.
     // So we can't test quadratics in quite the same way.
-    if proxy[3] == Range::exactly(0) && proxy[4] == Range::exactly(0) {
+    if proxy[3] == GenericRange::exactly(T::from(0)) && proxy[4] == GenericRange::exactly(T::from(0)) {
       return;
     }
     if start >= stop {
       return;
     }
-    let sample_points: Vec<i64> = vec![start,
-                                       stop,
-                                       rand::thread_rng().gen_range(start, stop),
-                                       rand::thread_rng().gen_range(start, stop),
-                                       rand::thread_rng().gen_range(start, stop)];
-    let sample_values: Vec<Range> = sample_points.iter().map(|input| test(input.clone())).collect();
+    let sample_points: Vec<T> = vec![start,
+                                     stop,
+                                     T::random_in(&mut rand::thread_rng(), start, stop),
+                                     T::random_in(&mut rand::thread_rng(), start, stop),
+                                     T::random_in(&mut rand::thread_rng(), start, stop)];
+    let sample_values: Vec<GenericRange<T>> = sample_points.iter().map(|input| test(input.clone())).collect();
     let signum = sample_values[0].internal_min().signum();
     for value in sample_values.iter() {
       if value.includes_0_strictly() || value.internal_min().signum() == -signum {
@@ -620,24 +777,253 @@ pub fn quadratic_trajectories_possible_distance_crossing_intervals(distance: i64
   let mut result = roots(proxy.as_ref(), min_input, max_input);
   // printlnerr!(" Proxy: {:?}\n Roots: {:?}", proxy, result);
   if DO_TESTS {
-    test(0);
-    test(1000);
+    test(T::from(0));
+    test(T::from(1000));
     test(base);
     for (which, root) in result.iter().enumerate() {
-      test((root.max() - root.min()) / 2);
+      test((root.max() - root.min()) / T::from(2));
       if which == 0 {
-        test_empty_interval(min_input, root.min() - 1);
+        test_empty_interval(min_input, root.min() - T::from(1));
       }
       if which < result.len() - 1 {
-        test_empty_interval(root.max() + 1, result[which + 1].min() - 1);
+        test_empty_interval(root.max() + T::from(1), result[which + 1].min() - T::from(1));
       } else {
-        test_empty_interval(root.max() + 1, max_input);
+        test_empty_interval(root.max() + T::from(1), max_input);
       }
       // printlnerr!("root check: {}: {} and then {} and then {}", root, evaluate (& proxy, root.max - 1),  evaluate (& proxy, root.max()), evaluate (& proxy, root.max() + 1));
     }
   }
   for root in result.iter_mut() {
-    *root = *root + Range::exactly(base);
+    *root = *root + GenericRange::exactly(base);
+  }
+  result
+}
+
+// The functions below generalize the quadratic ones above to polynomials of any degree,
+// for trajectories with jerk or higher terms. As with the quadratic ones, terms[k] is
+// the coefficient of (input >> input_scale_shift)^k, and the proxies they return are scaled up
+// by input_scale_shift * degree bits so that they can be exact.
+// Unlike the quadratic ones, they still allocate their proxies on the heap.
+
+fn binomial_coefficient<T: RangeInteger>(n: usize, k: usize) -> T {
+  let mut result = T::from(1);
+  for index in 0..k {
+    result = result * T::from((n - index) as i64) / T::from(index as i64 + 1);
+  }
+  result
+}
+
+// Like try_quadratic_move_origin_rounding_change_towards_0, the max error is checked against
+// the change in the constant term, which is the only one whose rounding is visible right away.
+// On failure, the terms are left unchanged.
+pub fn try_polynomial_move_origin_rounding_change_towards_0<T: RangeInteger>(terms: &mut [T],
+                                                            origin: T,
+                                                            input_scale_shift: u32,
+                                                            max_error: T)
+                                                            -> Result<(), TrajectoryError> {
+  let changes: Vec<GenericRange<T>> = (0..terms.len())
+    .map(|which| {
+      let mut factor = GenericRange::exactly(T::from(1));
+      let mut change = GenericRange::exactly(T::from(0));
+      for (power, term) in terms.iter().enumerate().skip(which + 1) {
+        factor = factor * origin;
+        change = change +
+                 ((GenericRange::exactly(*term) * binomial_coefficient::<T>(power, which) * factor) >>
+                  (input_scale_shift * (power - which) as u32));
+      }
+      change
+    })
+    .collect();
+
+  let mut moved = Vec::with_capacity(terms.len());
+  for (which, (term, change)) in terms.iter().zip(changes.iter()).enumerate() {
+    let change = if which == 0 {
+      try!(change_for_move(*change, max_error))
+    } else {
+      try!(change.clamp_to_0_exponent().ok_or(TrajectoryError::Overflow)).rounded_towards_0()
+    };
+    moved.push(try!(term.checked_add(change).ok_or(TrajectoryError::Overflow)));
+  }
+
+  let mut between_time = T::from(0);
+  let mut confirm = Vec::new();
+  if DO_TESTS {
+    between_time = T::random_in(&mut rand::thread_rng(), T::from(0), origin + T::from(1));
+    confirm = polynomial_future_proxy_minimizing_error(terms, between_time, input_scale_shift, max_error);
+  }
+  terms.copy_from_slice(&moved);
+  if DO_TESTS {
+    let degree = (terms.len() - 1) as u32;
+    let experimented = evaluate(&confirm, origin - between_time) >> (input_scale_shift * degree);
+    assert!(experimented.includes(&GenericRange::exactly(terms[0])));
+  }
+  Ok(())
+}
+
+pub fn polynomial_move_origin_rounding_change_towards_0<T: RangeInteger>(terms: &mut [T],
+                                                        origin: T,
+                                                        input_scale_shift: u32,
+                                                        max_error: T)
+                                                        -> bool {
+  match try_polynomial_move_origin_rounding_change_towards_0(terms, origin, input_scale_shift, max_error) {
+    Ok(()) => true,
+    Err(error) => {
+      printlnerr!("overflow-ish in polynomial_move_origin_rounding_change_towards_0: {}", error);
+      false
+    }
+  }
+}
+
+pub fn polynomial_future_proxy_minimizing_error<T: RangeInteger>(terms: &[T],
+                                                origin: T,
+                                                input_scale_shift: u32,
+                                                max_error: T)
+                                                -> Vec<GenericRange<T>> {
+  // Each rounded term contributes up to 1 unit of error to the constant term,
+  // the same way the quadratic version preserves an error of 2 units.
+  let degree = terms.len() - 1;
+  (0..terms.len())
+    .map(|which| {
+      let mut factor = GenericRange::exactly(T::from(1));
+      let mut result = if which == 0 {
+        let error = T::from(degree as i64) + max_error;
+        GenericRange::new(terms[0] - error, terms[0] + error) << (input_scale_shift * degree as u32)
+      } else {
+        GenericRange::exactly(terms[which]) << (input_scale_shift * (degree - which) as u32)
+      };
+      for (power, term) in terms.iter().enumerate().skip(which + 1) {
+        factor = factor * origin;
+        result = result +
+                 ((GenericRange::exactly(*term) * binomial_coefficient::<T>(power, which) * factor) <<
+                  (input_scale_shift * (degree - power) as u32));
+      }
+      result
+    })
+    .collect()
+}
+
+// Groups roots that touch or overlap, since the polynomial may stay near 0 across all of them.
+fn merged_root_intervals<T: RangeInteger>(roots: &[GenericRange<T>]) -> Vec<(T, T)> {
+  let mut result: Vec<(T, T)> = Vec::new();
+  for root in roots.iter() {
+    if let Some(last) = result.last_mut() {
+      if last.1 >= root.min() - T::from(1) {
+        last.1 = max(last.1, root.max());
+        continue;
+      }
+    }
+    result.push((root.min(), root.max()));
+  }
+  result
+}
+
+pub fn time_until_which_polynomial_trajectory_may_remain_in_bounds<T: RangeInteger, Terms: AsRef<[T]>>
+  (start_time: T,
+   trajectory: &[Terms],
+   bounds: &[[T; 2]],
+   input_scale_shift: u32,
+   max_error: T)
+   -> Option<T> {
+  assert!(trajectory.len() == bounds.len());
+  assert!(trajectory.len() > 0);
+  let mut min_input = start_time;
+  let mut max_input = T::max_value() - max(T::from(0), start_time);
+  for (third, more) in trajectory.iter().zip(bounds.iter()) {
+    let mut rubble =
+      polynomial_future_proxy_minimizing_error(third.as_ref(), T::from(0), input_scale_shift, max_error);
+    let degree = (rubble.len() - 1) as u32;
+    rubble[0] = rubble[0] - (GenericRange::new(more[0], more[1]) << (input_scale_shift * degree));
+    let possible_overlap_times = roots(&rubble, min_input, max_input);
+    if let Some((this_min, this_max)) = merged_root_intervals(&possible_overlap_times)
+      .into_iter()
+      .find(|&(this_min, this_max)| this_min <= start_time && this_max >= start_time) {
+      min_input = max(min_input, this_min);
+      max_input = min(max_input, this_max);
+      assert!(min_input <= max_input,
+              "an interval containing start_time should never exclude it");
+    } else {
+      return None;
+    }
+  }
+  Some(max_input)
+}
+
+pub fn polynomial_trajectories_possible_distance_crossing_intervals<T: RangeInteger, Terms: AsRef<[T]>>
+  (distance: T,
+   first: (T, &[Terms]),
+   second: (T, &[Terms]),
+   input_scale_shift: u32,
+   max_error: T)
+   -> RootSet<T> {
+  assert!(first.1.len() == second.1.len());
+  assert!(first.1.len() > 0);
+  let base = max(first.0, second.0);
+  let mut proxy: Vec<GenericRange<T>> = Vec::new();
+  let mut degree = 0;
+  let mut min_input = T::from(0);
+  let mut max_input = T::max_value() - max(T::from(0), base);
+  for (third, more) in first.1.iter().zip(second.1.iter()) {
+    assert!(third.as_ref().len() == more.as_ref().len());
+    let mut rubble = polynomial_future_proxy_minimizing_error(third.as_ref(),
+                                                              base - first.0,
+                                                              input_scale_shift,
+                                                              max_error);
+    let bravo = polynomial_future_proxy_minimizing_error(more.as_ref(),
+                                                         base - second.0,
+                                                         input_scale_shift,
+                                                         max_error);
+    for (term, other) in rubble.iter_mut().zip(bravo.iter()) {
+      *term = *term - *other;
+    }
+    degree = (rubble.len() - 1) as u32;
+    let mut this_dimension_tester = rubble.clone();
+    this_dimension_tester[0] = this_dimension_tester[0] +
+                               (GenericRange::error_sized(distance) << (input_scale_shift * degree));
+    let possible_overlap_times = roots(&this_dimension_tester, min_input, max_input);
+
+    if possible_overlap_times.is_empty() {
+      return RootSet::new();
+    } else {
+      min_input = max(min_input, possible_overlap_times[0].min());
+      max_input = min(max_input, possible_overlap_times.last().unwrap().max());
+      if min_input > max_input {
+        return RootSet::new();
+      }
+    }
+    let squared = multiply_polynomials(&rubble, &rubble);
+    if proxy.is_empty() {
+      proxy = squared;
+    } else {
+      for (term, value) in proxy.iter_mut().zip(squared.into_iter()) {
+        *term = *term + value;
+      }
+    }
+  }
+  proxy[0] = proxy[0] - (GenericRange::exactly(distance).squared() << (input_scale_shift * degree * 2));
+
+  if DO_TESTS {
+    for &input in [T::from(0), T::from(1000)].iter() {
+      let mut real = Some(-(distance * distance));
+      for (third, more) in first.1.iter().zip(second.1.iter()) {
+        let mut rubble = third.as_ref().to_vec();
+        let mut bravo = more.as_ref().to_vec();
+        if !polynomial_move_origin_rounding_change_towards_0(&mut rubble, input + base - first.0, input_scale_shift, max_error) ||
+           !polynomial_move_origin_rounding_change_towards_0(&mut bravo, input + base - second.0, input_scale_shift, max_error) {
+          real = None;
+          break;
+        }
+        let displacement = rubble[0] - bravo[0];
+        real = real.and_then(|total| displacement.checked_mul(displacement).and_then(|square| total.checked_add(square)));
+      }
+      if let Some(real) = real {
+        assert!((evaluate(&proxy, input) >> (input_scale_shift * degree * 2)).includes(&GenericRange::exactly(real)));
+      }
+    }
+  }
+
+  let mut result = roots(&proxy, min_input, max_input);
+  for root in result.iter_mut() {
+    *root = *root + GenericRange::exactly(base);
   }
   result
 }
@@ -662,9 +1048,46 @@ mod tests {
                         .collect::<Vec<Range>>());
   }
 
-  // quickcheck! {
-  // fn automatic_roots(given_roots: Vec<Range>)
-  // }
+  use quickcheck::TestResult;
+
+  // Builds leading*(x - roots[0])*(x - roots[1])*..., then widens each coefficient by `slop`,
+  // and checks that every exact root is still inside one of the computed roots.
+  fn roots_include(given_roots: &[i64], leading: i64, slop: i64) -> TestResult {
+    if leading == 0 {
+      return TestResult::discard();
+    }
+    let mut polynomial = vec![Range::exactly(leading)];
+    for root in given_roots.iter() {
+      polynomial = multiply_polynomials(polynomial.as_slice(), &[Range::exactly(-root), Range::exactly(1)])
+    }
+    for term in polynomial.iter_mut() {
+      *term = *term + Range::error_sized(slop);
+    }
+    let computed = roots(polynomial.as_slice(), -i64::max_value(), i64::max_value());
+    TestResult::from_bool(given_roots.iter().all(|root| {
+      computed.iter().any(|range| range.min() <= *root && range.max() >= *root)
+    }))
+  }
+
+  quickcheck! {
+    fn cubic_roots_inclusive (roots: (i16, i16, i16), leading: i8, slop: u8)->TestResult {
+      roots_include (&[roots.0 as i64, roots.1 as i64, roots.2 as i64], leading as i64, slop as i64)
+    }
+    fn quartic_roots_inclusive (roots: (i16, i16, i16, i16), leading: i8, slop: u8)->TestResult {
+      roots_include (&[roots.0 as i64, roots.1 as i64, roots.2 as i64, roots.3 as i64], leading as i64, slop as i64)
+    }
+    fn cubic_roots_sorted (roots: (i16, i16, i16), leading: i8)->TestResult {
+      if leading == 0 {
+        return TestResult::discard();
+      }
+      let mut polynomial = vec![Range::exactly(leading as i64)];
+      for root in [roots.0, roots.1, roots.2].iter() {
+        polynomial = multiply_polynomials(polynomial.as_slice(), &[Range::exactly(-(*root as i64)), Range::exactly(1)])
+      }
+      let computed = roots_cubic([polynomial[0], polynomial[1], polynomial[2], polynomial[3]], -i64::max_value(), i64::max_value());
+      TestResult::from_bool (computed.windows(2).all(| pair | pair[0].min() <= pair[1].min()))
+    }
+  }
 
   #[test]
   fn explicit_roots() {
@@ -696,6 +1119,9 @@ mod tests {
                     Range::new(-90, -80),
                     Range::new(-1967, -1940)]);
 
+    // More terms than MAX_POLYNOMIAL_TERMS, as in the distance proxy of two degree-5 trajectories.
+    test_roots([-40, -20, -9, -3, -1, 2, 5, 11, 30, 70].iter().map(|&root| Range::exactly(root)).collect());
+
 
 
 
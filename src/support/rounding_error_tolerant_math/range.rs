@@ -3,6 +3,7 @@ use std::cmp::{max, min, Ordering};
 use std::iter::Sum;
 use std::fmt;
 use quickcheck::{Arbitrary, Gen};
+use super::integer::RangeInteger;
 
 /**
 
@@ -14,15 +15,20 @@ A Range represents an inclusive range of real-number values.
 
 Range is also a partially floating-point type: it handles overflow by increasing an exponent value and rounding off. This rounding never removes elements from the Range, but sometimes adds new ones.
 
+The endpoints can be any RangeInteger; Range itself uses i64, and Range128 uses i128, for when i64 doesn't leave enough headroom. The same guarantees hold for every integer type.
+
 */
 #[derive (Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Range {
-  min: i64,
-  max: i64,
+pub struct GenericRange<T: RangeInteger> {
+  min: T,
+  max: T,
   exponent: u32,
 }
 
-impl fmt::Display for Range {
+pub type Range = GenericRange<i64>;
+pub type Range128 = GenericRange<i128>;
+
+impl<T: RangeInteger> fmt::Display for GenericRange<T> {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     if self.exponent > 0 {
       write!(f, "Range:({},{})<<{}", self.min, self.max, self.exponent)
@@ -32,7 +38,7 @@ impl fmt::Display for Range {
   }
 }
 
-impl fmt::Debug for Range {
+impl<T: RangeInteger> fmt::Debug for GenericRange<T> {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     if self.exponent > 0 {
       write!(f, "Range:({},{})<<{}", self.min, self.max, self.exponent)
@@ -44,26 +50,26 @@ impl fmt::Debug for Range {
 
 
 
-pub fn right_shift_round_up(value: i64, shift: u32) -> i64 {
+pub fn right_shift_round_up<T: RangeInteger>(value: T, shift: u32) -> T {
   (value >> shift) +
-  if value & ((1i64 << shift) - 1) != 0 {
-    1
+  if value & ((T::from(1) << shift) - T::from(1)) != T::from(0) {
+    T::from(1)
   } else {
-    0
+    T::from(0)
   }
 }
-pub fn average_round_towards_neginf(input_1: i64, input_2: i64) -> i64 {
-  (input_1 >> 1) + (input_2 >> 1) + (input_1 & input_2 & 1)
+pub fn average_round_towards_neginf<T: RangeInteger>(input_1: T, input_2: T) -> T {
+  (input_1 >> 1) + (input_2 >> 1) + (input_1 & input_2 & T::from(1))
 }
 
-pub fn overflow_checked_shift_left(value: i64, shift: u32) -> Option<i64> {
-  if value == 0 {
-    return Some(0);
+pub fn overflow_checked_shift_left<T: RangeInteger>(value: T, shift: u32) -> Option<T> {
+  if value == T::from(0) {
+    return Some(T::from(0));
   }
-  if shift > 63 {
+  if shift > T::bits() - 1 {
     return None;
   }
-  if (value.abs() & !((1i64 << (63 - shift)).wrapping_sub(1))) != 0 {
+  if (value.abs() & !((T::from(1) << (T::bits() - 1 - shift)).wrapping_sub(T::from(1)))) != T::from(0) {
     return None;
   }
   Some(value << shift)
@@ -72,48 +78,48 @@ pub fn overflow_checked_shift_left(value: i64, shift: u32) -> Option<i64> {
 
 
 
-impl Range {
-  pub fn new(min: i64, max: i64) -> Range {
+impl<T: RangeInteger> GenericRange<T> {
+  pub fn new(min: T, max: T) -> GenericRange<T> {
     assert!(max >= min, "invalid Range");
-    let mut result = Range {
+    let mut result = GenericRange {
       min: min,
       max: max,
       exponent: 0,
     };
-    if min == i64::min_value() {
+    if min == T::min_value() {
       result.increase_exponent_by(1);
     }
     result
   }
-  pub fn new_either_order(min: i64, max: i64) -> Range {
+  pub fn new_either_order(min: T, max: T) -> GenericRange<T> {
     if min > max {
-      Range::new(max, min)
+      GenericRange::new(max, min)
     } else {
-      Range::new(min, max)
+      GenericRange::new(min, max)
     }
   }
-  pub fn exactly(value: i64) -> Range {
-    Range::new(value, value)
+  pub fn exactly(value: T) -> GenericRange<T> {
+    GenericRange::new(value, value)
   }
-  pub fn zero() -> Range {
-    Range {
-      min: 0,
-      max: 0,
+  pub fn zero() -> GenericRange<T> {
+    GenericRange {
+      min: T::from(0),
+      max: T::from(0),
       exponent: 0,
     }
   }
-  pub fn error_sized(value: i64) -> Range {
-    if value < 0 {
-      Range::new(value, -value)
+  pub fn error_sized(value: T) -> GenericRange<T> {
+    if value < T::from(0) {
+      GenericRange::new(value, -value)
     } else {
-      Range::new(-value, value)
+      GenericRange::new(-value, value)
     }
   }
 
-  pub fn everywhere() -> Range {
-    Range {
-      min: -i64::max_value(),
-      max: i64::max_value(),
+  pub fn everywhere() -> GenericRange<T> {
+    GenericRange {
+      min: -T::max_value(),
+      max: T::max_value(),
       exponent: u32::max_value(),
     }
   }
@@ -125,7 +131,7 @@ impl Range {
   }
   fn increase_exponent_by(&mut self, increase: u32) {
     // let confirm = self.clone();
-    if increase >= 63 {
+    if increase >= T::bits() - 1 {
       self.min = self.min.signum();
       self.max = self.max.signum();
     } else {
@@ -140,10 +146,10 @@ impl Range {
     self.exponent += increase;
   }
   fn minimize_exponent(&mut self) {
-    let mut confirm = Range::zero();
+    let mut confirm = GenericRange::zero();
     if cfg! (debug_assertions) {confirm = self.clone();}
     if self.exponent == 0 {return;}
-    if self.min == 0 && self.max == 0 {
+    if self.min == T::from(0) && self.max == T::from(0) {
       self.exponent = 0;
       return;
     }
@@ -157,7 +163,7 @@ impl Range {
       if self.min <= self.max {
         assert! (self.exponent_is_minimized());
       }
-      assert! (self.exponent == 0 || self.min.checked_mul(2).map_or(true, | result | result == i64::min_value()) || self.max.checked_mul(2).map_or(true, | result | result == i64::min_value()));
+      assert! (self.exponent == 0 || self.min.checked_mul(T::from(2)).map_or(true, | result | result == T::min_value()) || self.max.checked_mul(T::from(2)).map_or(true, | result | result == T::min_value()));
       let mut confirm_2 = self.clone();
       confirm_2.increase_exponent_to(confirm.exponent);
       assert!(confirm == confirm_2);
@@ -165,15 +171,15 @@ impl Range {
   }
   fn exponent_is_minimized(&self)->bool {
     debug_assert!(self.min <= self.max);
-    self.exponent == 0 || self.min <= (-1i64 << 62) || self.max >= (1i64 << 62)
+    self.exponent == 0 || self.min <= (T::from(-1) << (T::bits() - 2)) || self.max >= (T::from(1) << (T::bits() - 2))
   }
   pub fn includes_0(&self) -> bool {
-    self.min <= 0 && self.max >= 0
+    self.min <= T::from(0) && self.max >= T::from(0)
   }
   pub fn includes_0_strictly(&self) -> bool {
-    self.min < 0 && self.max > 0
+    self.min < T::from(0) && self.max > T::from(0)
   }
-  pub fn includes(&self, other: &Range) -> bool {
+  pub fn includes(&self, other: &GenericRange<T>) -> bool {
     if self.exponent < other.exponent {
       return false;
     }
@@ -189,61 +195,61 @@ impl Range {
     }
     true
   }
-  pub fn rounded_towards_0(&self) -> i64 {
+  pub fn rounded_towards_0(&self) -> T {
     assert!(self.exponent == 0);
     if self.includes_0() {
-      0
-    } else if self.max < 0 {
+      T::from(0)
+    } else if self.max < T::from(0) {
       self.max
     } else {
       self.min
     }
   }
-  pub fn min(&self) -> i64 {
+  pub fn min(&self) -> T {
     assert!(self.exponent == 0);
     self.min
   }
-  pub fn max(&self) -> i64 {
+  pub fn max(&self) -> T {
     assert!(self.exponent == 0);
     self.max
   }
-  pub fn min_signum(&self) -> i64 {
+  pub fn min_signum(&self) -> T {
     self.min.signum()
   }
-  pub fn max_signum(&self) -> i64 {
+  pub fn max_signum(&self) -> T {
     self.max.signum()
   }
-  pub fn internal_min(&self) -> i64 {
+  pub fn internal_min(&self) -> T {
     self.min
   }
-  pub fn internal_max(&self) -> i64 {
+  pub fn internal_max(&self) -> T {
     self.max
   }
   pub fn exponent(&self) -> u32 {
     self.exponent
   }
 
-  pub fn clamp_to_0_exponent(&self) -> Option<Range> {
+  pub fn clamp_to_0_exponent(&self) -> Option<GenericRange<T>> {
     let mut result = self.clone();
-    if self.exponent >= 63 {
-      result.min = self.min.signum() * i64::max_value();
-      result.max = self.max.signum() * i64::max_value();
+    if self.exponent >= T::bits() - 1 {
+      result.min = self.min.signum() * T::max_value();
+      result.max = self.max.signum() * T::max_value();
     } else {
-      result.min = self.min.saturating_mul(1i64 << self.exponent);
-      result.max = self.max.saturating_mul(1i64 << self.exponent);
+      result.min = self.min.saturating_mul(T::from(1) << self.exponent);
+      result.max = self.max.saturating_mul(T::from(1) << self.exponent);
     }
     result.exponent = 0;
-    if result.min == i64::max_value() || result.max <= -i64::max_value() {
+    if result.min == T::max_value() || result.max <= -T::max_value() {
       None
     } else {
       Some(result)
     }
   }
-  pub fn rounded_to_middle_towards_neginf(&self) -> Range {
+  pub fn rounded_to_middle_towards_neginf(&self) -> GenericRange<T> {
     // this could sometimes be slightly more accurate when the exponent is not 0,
     // but it probably isn't worth the complication
     let middle = average_round_towards_neginf(self.min, self.max);
-    let mut result = Range {
+    let mut result = GenericRange {
       min: middle,
       max: middle,
       exponent: self.exponent,
@@ -254,17 +260,17 @@ impl Range {
 }
 
 macro_rules! binary_operation_fill {
-($implementor: ident, $joiner: ident, $operation: ident, $method:ident) => {
+($implementor: ty, $joiner: ty, $operation: ident, $method:ident) => {
 
 
-impl<'a> $operation <& 'a $joiner> for $implementor{
+impl<'a, T: RangeInteger> $operation <& 'a $joiner> for $implementor{
 type Output = $implementor;
 fn $method (self, other: & 'a $joiner)->$implementor{
 (& self).$method (other)
 }
 }
 
-impl<'a> $operation <$joiner> for & 'a $implementor{
+impl<'a, T: RangeInteger> $operation <$joiner> for & 'a $implementor{
 type Output = $implementor;
 fn $method (self, other: $joiner)->$implementor{
 self.$method (& other)
@@ -272,7 +278,7 @@ self.$method (& other)
 }
 
 
-impl $operation <$joiner> for $implementor{
+impl<T: RangeInteger> $operation <$joiner> for $implementor{
 type Output = $implementor;
 fn $method (self, other: $joiner)->$implementor{
 (& self).$method (& other)
@@ -284,20 +290,20 @@ fn $method (self, other: $joiner)->$implementor{
 
 }
 
-impl<'a> Neg for &'a Range {
-  type Output = Range;
-  fn neg(self) -> Range {
-    Range {
+impl<'a, T: RangeInteger> Neg for &'a GenericRange<T> {
+  type Output = GenericRange<T>;
+  fn neg(self) -> GenericRange<T> {
+    GenericRange {
       min: -self.max,
       max: -self.min,
       exponent: self.exponent,
     }
   }
 }
-impl Neg for Range {
-  type Output = Range;
-  fn neg(self) -> Range {
-    Range {
+impl<T: RangeInteger> Neg for GenericRange<T> {
+  type Output = GenericRange<T>;
+  fn neg(self) -> GenericRange<T> {
+    GenericRange {
       min: -self.max,
       max: -self.min,
       exponent: self.exponent,
@@ -307,15 +313,15 @@ impl Neg for Range {
 
 
 
-impl<'a> Add for &'a Range {
-  type Output = Range;
-  fn add(self, other: Self) -> Range {
+impl<'a, T: RangeInteger> Add for &'a GenericRange<T> {
+  type Output = GenericRange<T>;
+  fn add(self, other: Self) -> GenericRange<T> {
     let possibly_needed = max(self.exponent, other.exponent);
     let mut result = self.clone();
     let mut other = other.clone();
     result.increase_exponent_to(possibly_needed);
     other.increase_exponent_to(possibly_needed);
-    if result.min.checked_add(other.min).map_or(true, |result| result == i64::min_value()) ||
+    if result.min.checked_add(other.min).map_or(true, |result| result == T::min_value()) ||
        result.max.checked_add(other.max).is_none() {
       result.increase_exponent_by(1);
       other.increase_exponent_by(1);
@@ -327,31 +333,32 @@ impl<'a> Add for &'a Range {
   }
 }
 
-binary_operation_fill! (Range, Range, Add, add);
+binary_operation_fill! (GenericRange<T>, GenericRange<T>, Add, add);
 
-impl<'a> Sub for &'a Range {
-  type Output = Range;
-  fn sub(self, other: Self) -> Range {
+impl<'a, T: RangeInteger> Sub for &'a GenericRange<T> {
+  type Output = GenericRange<T>;
+  fn sub(self, other: Self) -> GenericRange<T> {
     self + (-other)
   }
 }
 
-binary_operation_fill! (Range, Range, Sub, sub);
+binary_operation_fill! (GenericRange<T>, GenericRange<T>, Sub, sub);
 
 
-impl<'a> Mul for &'a Range {
-  type Output = Range;
-  fn mul(self, other: Self) -> Range {
+impl<'a, T: RangeInteger> Mul for &'a GenericRange<T> {
+  type Output = GenericRange<T>;
+  fn mul(self, other: Self) -> GenericRange<T> {
     let mut result = self.clone();
     let mut other = other.clone();
-    let result_high_bit = 63 -
+    let top_bit = T::bits() as i32 - 1;
+    let result_high_bit = top_bit -
                           min(result.min.abs().leading_zeros(),
                               result.max.abs().leading_zeros()) as i32;
-    let other_high_bit = 63 -
+    let other_high_bit = top_bit -
                          min(other.min.abs().leading_zeros(),
                              other.max.abs().leading_zeros()) as i32;
-    // for each value, it could be anything strictly less than (1 << high_bit+1). So when you multiply them together, it can be just below (1 << high_bit + high_bit + 2). Because increase_exponent_by can round towards a higher magnitude, we have to increase the leeway by one, eliminating "just below". The result must not exceed the 62nd bit.
-    let overflow = result_high_bit + other_high_bit + 2 - 62;
+    // for each value, it could be anything strictly less than (1 << high_bit+1). So when you multiply them together, it can be just below (1 << high_bit + high_bit + 2). Because increase_exponent_by can round towards a higher magnitude, we have to increase the leeway by one, eliminating "just below". The result must not exceed the second-highest bit (the 62nd, for i64).
+    let overflow = result_high_bit + other_high_bit + 2 - (top_bit - 1);
     if overflow > 0 {
       let bigger;
       let smaller;
@@ -376,7 +383,7 @@ impl<'a> Mul for &'a Range {
                     result.max * other.max,
                     result.min * other.max,
                     result.max * other.min];
-    result = Range {
+    result = GenericRange {
       min: extremes[0],
       max: extremes[0],
       exponent: result.exponent + other.exponent,
@@ -395,29 +402,30 @@ impl<'a> Mul for &'a Range {
   }
 }
 
-impl<'a> Mul<&'a i64> for &'a Range {
-  type Output = Range;
-  fn mul(self, other: &'a i64) -> Range {
+impl<'a, T: RangeInteger> Mul<&'a T> for &'a GenericRange<T> {
+  type Output = GenericRange<T>;
+  fn mul(self, other: &'a T) -> GenericRange<T> {
     let mut result = self.clone();
-    let result_high_bit = 63 -
+    let top_bit = T::bits() as i32 - 1;
+    let result_high_bit = top_bit -
                           min(result.min.abs().leading_zeros(),
                               result.max.abs().leading_zeros()) as i32;
-    let other_high_bit = 63 - other.abs().leading_zeros() as i32;
-    // for each value, it could be anything strictly less than (1 << high_bit+1). So when you multiply them together, it can be just below (1 << high_bit + high_bit + 2). Because increase_exponent_by can round towards a higher magnitude, we have to increase the leeway by one, eliminating "just below". The result must not exceed the 62nd bit.
-    let overflow = result_high_bit + other_high_bit + 2 - 62;
+    let other_high_bit = top_bit - other.abs().leading_zeros() as i32;
+    // for each value, it could be anything strictly less than (1 << high_bit+1). So when you multiply them together, it can be just below (1 << high_bit + high_bit + 2). Because increase_exponent_by can round towards a higher magnitude, we have to increase the leeway by one, eliminating "just below". The result must not exceed the second-highest bit (the 62nd, for i64).
+    let overflow = result_high_bit + other_high_bit + 2 - (top_bit - 1);
     if overflow > 0 {
-      if result_high_bit - overflow < 31 {
-        return self * Range::exactly(other.clone());
+      if result_high_bit - overflow < T::bits() as i32 / 2 - 1 {
+        return self * GenericRange::exactly(other.clone());
       }
       result.increase_exponent_by(overflow as u32);
     }
-    if *other >= 0 {
+    if *other >= T::from(0) {
       result.min *= *other;
       result.max *= *other;
     } else {
-      result = Range {
-        min: result.max * other,
-        max: result.min * other,
+      result = GenericRange {
+        min: result.max * *other,
+        max: result.min * *other,
         exponent: result.exponent,
       };
     }
@@ -428,26 +436,26 @@ impl<'a> Mul<&'a i64> for &'a Range {
 }
 
 
-binary_operation_fill! (Range, Range, Mul, mul);
-binary_operation_fill! (Range, i64, Mul, mul);
+binary_operation_fill! (GenericRange<T>, GenericRange<T>, Mul, mul);
+binary_operation_fill! (GenericRange<T>, T, Mul, mul);
 
 
 
 
-impl<'a> Div for &'a Range {
-  type Output = Range;
-  fn div(self, other: Self) -> Range {
+impl<'a, T: RangeInteger> Div for &'a GenericRange<T> {
+  type Output = GenericRange<T>;
+  fn div(self, other: Self) -> GenericRange<T> {
     let mut result = self.clone();
     let mut other = other.clone();
 
-    if other.min < 0 {
+    if other.min < T::from(0) {
       other = -other;
       result = -result;
     }
 
     if other.includes_0() {
       // TODO: what if if other.min == 0
-      return Range::everywhere();
+      return GenericRange::everywhere();
     }
 
     // intuitively, to minimize rounding error, denominator should have about half as many bits as numerator does when we do the actual division operation.
@@ -455,8 +463,9 @@ impl<'a> Div for &'a Range {
     // Handle dividing by denominator.min first. Sometimes, denominator.max isn't even used.
     if result.exponent > other.exponent {
       let leeway = other.min.abs().leading_zeros();
-      if leeway < 32 {
-        let shift = min(32 - leeway, result.exponent - other.exponent);
+      let half = T::bits() / 2;
+      if leeway < half {
+        let shift = min(half - leeway, result.exponent - other.exponent);
         other.increase_exponent_by(shift);
       }
     }
@@ -466,20 +475,20 @@ impl<'a> Div for &'a Range {
     }
     result.exponent -= other.exponent;
 
-    if result.min < 0 {
-      result.min = (result.min + 1) / other.min - 1;
+    if result.min < T::from(0) {
+      result.min = (result.min + T::from(1)) / other.min - T::from(1);
     }
-    if result.max > 0 {
-      result.max = (result.max - 1) / other.min + 1;
+    if result.max > T::from(0) {
+      result.max = (result.max - T::from(1)) / other.min + T::from(1);
     }
     result.minimize_exponent();
 
     // TODO: we might still be able to reduce the rounding error further in these cases:
-    if result.min > 0 {
+    if result.min > T::from(0) {
       result.min = result.min / other.max;
       result.minimize_exponent();
     }
-    if result.max < 0 {
+    if result.max < T::from(0) {
       result.max = result.max / other.max;
       result.minimize_exponent();
     }
@@ -487,12 +496,12 @@ impl<'a> Div for &'a Range {
   }
 }
 
-binary_operation_fill! (Range, Range, Div, div);
+binary_operation_fill! (GenericRange<T>, GenericRange<T>, Div, div);
 
 
-impl<'a> Shr<&'a u32> for &'a Range {
-  type Output = Range;
-  fn shr(self, other: &u32) -> Range {
+impl<'a, T: RangeInteger> Shr<&'a u32> for &'a GenericRange<T> {
+  type Output = GenericRange<T>;
+  fn shr(self, other: &u32) -> GenericRange<T> {
     let mut result = self.clone();
     if result.exponent >= other.clone() {
       result.exponent -= other.clone();
@@ -503,27 +512,27 @@ impl<'a> Shr<&'a u32> for &'a Range {
     result
   }
 }
-binary_operation_fill! (Range, u32, Shr, shr);
+binary_operation_fill! (GenericRange<T>, u32, Shr, shr);
 
-impl<'a> Shl<&'a u32> for &'a Range {
-  type Output = Range;
-  fn shl(self, other: &u32) -> Range {
+impl<'a, T: RangeInteger> Shl<&'a u32> for &'a GenericRange<T> {
+  type Output = GenericRange<T>;
+  fn shl(self, other: &u32) -> GenericRange<T> {
     let mut result = self.clone();
     result.exponent += other.clone();
     result.minimize_exponent();
     result
   }
 }
-binary_operation_fill! (Range, u32, Shl, shl);
+binary_operation_fill! (GenericRange<T>, u32, Shl, shl);
 
 
 
 
-impl Sum for Range {
+impl<T: RangeInteger> Sum for GenericRange<T> {
   fn sum<I>(iter: I) -> Self
-    where I: Iterator<Item = Range>
+    where I: Iterator<Item = GenericRange<T>>
   {
-    let mut result = Range::exactly(0);
+    let mut result = GenericRange::exactly(T::from(0));
     for value in iter {
       result = result + value;
     }
@@ -533,12 +542,12 @@ impl Sum for Range {
 
 
 
-impl Range {
-  pub fn abs(&self) -> Range {
+impl<T: RangeInteger> GenericRange<T> {
+  pub fn abs(&self) -> GenericRange<T> {
     // this could be made more efficient
-    Range {
+    GenericRange {
       min: if self.includes_0() {
-        0
+        T::from(0)
       } else {
         min(self.min.abs(), self.max.abs())
       },
@@ -548,17 +557,18 @@ impl Range {
   }
 
   /// Squaring is a slightly narrower operation than self*self, because it never invokes (for instance) self.min*self.max.
-  pub fn squared(&self) -> Range {
+  pub fn squared(&self) -> GenericRange<T> {
     let mut result = self.clone();
     let leeway = min(self.min.abs().leading_zeros(),
                      self.max.abs().leading_zeros());
-    if leeway < 33 {
-      result.increase_exponent_by(33 - leeway);
+    let needed = T::bits() / 2 + 1;
+    if leeway < needed {
+      result.increase_exponent_by(needed - leeway);
     }
     result.exponent <<= 1;
     if result.includes_0() {
       result.max = max(result.min * result.min, result.max * result.max);
-      result.min = 0;
+      result.min = T::from(0);
     } else {
       let extrema = [result.min * result.min, result.max * result.max];
       if extrema[0] < extrema[1] {
@@ -573,28 +583,31 @@ impl Range {
     result
   }
 
-  pub fn sqrt(&self) -> Option<Range> {
+  pub fn sqrt(&self) -> Option<GenericRange<T>> {
     let mut result = self.clone();
     if result.exponent % 2 == 1 {
       result.increase_exponent_by(1)
     }
     result.exponent >>= 1;
-    if result.max < 0 {
+    if result.max < T::from(0) {
       return None;
     }
-    if result.min < 0 {
-      result.min = 0;
+    if result.min < T::from(0) {
+      result.min = T::from(0);
     }
-    let mut lower_bound = 0;
-    let mut upper_bound = 3037000500i64;
-    let mut move_size = 1i64 << 31;
-    while move_size > 0 {
+    // Squares that would overflow are larger than any value we could be comparing them with.
+    let square_at_most = |value: T, limit: T| value.checked_mul(value).map_or(false, |square| square <= limit);
+    let square_at_least = |value: T, limit: T| value.checked_mul(value).map_or(true, |square| square >= limit);
+    let mut lower_bound = T::from(0);
+    let mut upper_bound = T::from(1) << (T::bits() / 2);
+    let mut move_size = T::from(1) << (T::bits() / 2 - 1);
+    while move_size > T::from(0) {
       if lower_bound + move_size <= upper_bound &&
-         (lower_bound + move_size) * (lower_bound + move_size) <= result.min {
+         square_at_most(lower_bound + move_size, result.min) {
         lower_bound += move_size;
       }
       if upper_bound - move_size >= lower_bound &&
-         (upper_bound - move_size) * (upper_bound - move_size) >= result.max {
+         square_at_least(upper_bound - move_size, result.max) {
         upper_bound -= move_size;
       }
       move_size >>= 1;
@@ -605,8 +618,8 @@ impl Range {
     if cfg! (debug_assertions) {
       let confirm = result.squared();
       let mut confirmation = self.clone();
-      if confirmation.min < 0 {
-        confirmation.min = 0;
+      if confirmation.min < T::from(0) {
+        confirmation.min = T::from(0);
       }
       confirmation.minimize_exponent();
       assert!(confirm.exponent > confirmation.exponent || confirm.includes(&confirmation));
@@ -616,8 +629,8 @@ impl Range {
 }
 
 
-impl PartialOrd for Range {
-  fn partial_cmp(&self, other: &Range) -> Option<Ordering> {
+impl<T: RangeInteger> PartialOrd for GenericRange<T> {
+  fn partial_cmp(&self, other: &GenericRange<T>) -> Option<Ordering> {
     if self.exponent < other.exponent {
       return other.partial_cmp(self).map(|order| order.reverse());
     }
@@ -625,14 +638,14 @@ impl PartialOrd for Range {
       if bound > other.max {
         return Some(Ordering::Greater);
       }
-    } else if self.min > 0 {
+    } else if self.min > T::from(0) {
       return Some(Ordering::Greater);
     }
     if let Some(bound) = overflow_checked_shift_left(self.max, self.exponent - other.exponent) {
       if bound < other.min {
         return Some(Ordering::Less);
       }
-    } else if self.max < 0 {
+    } else if self.max < T::from(0) {
       return Some(Ordering::Less);
     }
     None
@@ -640,20 +653,20 @@ impl PartialOrd for Range {
   // TODO: implement the others for efficiency
 }
 
-impl PartialOrd<i64> for Range {
-  fn partial_cmp(&self, other: &i64) -> Option<Ordering> {
+impl<T: RangeInteger> PartialOrd<T> for GenericRange<T> {
+  fn partial_cmp(&self, other: &T) -> Option<Ordering> {
     if let Some(bound) = overflow_checked_shift_left(self.min, self.exponent) {
       if bound > *other {
         return Some(Ordering::Greater);
       }
-    } else if self.min > 0 {
+    } else if self.min > T::from(0) {
       return Some(Ordering::Greater);
     }
     if let Some(bound) = overflow_checked_shift_left(self.max, self.exponent) {
       if bound < *other {
         return Some(Ordering::Less);
       }
-    } else if self.max < 0 {
+    } else if self.max < T::from(0) {
       return Some(Ordering::Less);
     }
     None
@@ -661,55 +674,80 @@ impl PartialOrd<i64> for Range {
   // TODO: implement the others for efficiency
 }
 
+impl<T: RangeInteger> PartialEq<T> for GenericRange<T> {
+  fn eq(&self, other: &T) -> bool {
+    self.exponent == 0 && self.min == *other && self.max == *other
+  }
+}
 
-impl PartialOrd<Range> for i64 {
-  fn partial_cmp(&self, other: &Range) -> Option<Ordering> {
+// The reversed comparisons can't be implemented generically, because of the orphan rules.
+macro_rules! integer_compared_with_range {
+  ($integer: ident) => {
+impl PartialOrd<GenericRange<$integer>> for $integer {
+  fn partial_cmp(&self, other: &GenericRange<$integer>) -> Option<Ordering> {
     other.partial_cmp(self).map(|order| order.reverse())
   }
   // TODO: implement the others for efficiency
 }
 
-impl PartialEq<i64> for Range {
-  fn eq(&self, other: &i64) -> bool {
-    self.exponent == 0 && self.min == *other && self.max == *other
+impl PartialEq<GenericRange<$integer>> for $integer {
+  fn eq(&self, other: &GenericRange<$integer>) -> bool {
+    other.exponent == 0 && other.min == *self && other.max == *self
   }
 }
-
-impl PartialEq<Range> for i64 {
-  fn eq(&self, other: &Range) -> bool {
-    other.exponent == 0 && other.min == *self && other.max == *self
   }
 }
+integer_compared_with_range!(i64);
+integer_compared_with_range!(i128);
 
-impl Arbitrary for Range {
-  fn arbitrary<G: Gen>(generator: &mut G) -> Range {
-    let mut result = Range::new_either_order(generator.gen(), generator.gen());
+impl<T: RangeInteger> Arbitrary for GenericRange<T> {
+  fn arbitrary<G: Gen>(generator: &mut G) -> GenericRange<T> {
+    let mut result = GenericRange::new_either_order(T::random(generator), T::random(generator));
     result.exponent = generator.gen();
     result.minimize_exponent();
     result
   }
-  // TODO: implement shrink
-  fn shrink(&self) -> Box<Iterator<Item = Range>> {
-    struct Shrinker {
-      value: Range,
-    }
-    impl Iterator for Shrinker {
-      type Item = Range;
-      fn next(&mut self) -> Option<Range> {
-        if self.value.exponent > 0 {
-          self.value.exponent >>= 1;
-        } else {
-          self.value.min /= 2;
-          self.value.max /= 2;
+  // Each candidate is either narrower or closer to 0 (in real value, not just in its representation),
+  // so repeated shrinking always terminates.
+  fn shrink(&self) -> Box<Iterator<Item = GenericRange<T>>> {
+    let zero = T::from(0);
+    let two = T::from(2);
+    let mut candidates = Vec::new();
+    {
+      let mut consider = |min: T, max: T, exponent: u32| {
+        if min > max {
+          return;
         }
-        if self.value == Range::exactly(0) {
-          None
-        } else {
-          Some(self.value)
+        let mut candidate = GenericRange {
+          min: min,
+          max: max,
+          exponent: exponent,
+        };
+        candidate.minimize_exponent();
+        if candidate != *self && !candidates.contains(&candidate) {
+          candidates.push(candidate);
         }
+      };
+      let (min, max, exponent) = (self.min, self.max, self.exponent);
+      consider(zero, zero, 0);
+      if exponent > 0 {
+        consider(min, max, 0);
+        consider(min, max, exponent / 2);
+        consider(min, max, exponent - 1);
       }
+      // Collapse to the endpoint closer to 0.
+      if min.abs() <= max.abs() {
+        consider(min, min, exponent);
+      } else {
+        consider(max, max, exponent);
+      }
+      consider(min / two, max / two, exponent);
+      consider(min / two, max, exponent);
+      consider(min, max / two, exponent);
+      consider(min - min.signum(), max, exponent);
+      consider(min, max - max.signum(), exponent);
     }
-    Box::new(Shrinker { value: self.clone() })
+    Box::new(candidates.into_iter())
   }
 }
 
@@ -720,8 +758,68 @@ impl Arbitrary for Range {
 #[cfg (test)]
 mod tests {
   use super::*;
-  use quickcheck::TestResult;
-  use std::cmp::max;
+  use quickcheck::{Arbitrary, TestResult};
+  use std::cmp::{max, min};
+
+  // An exact real value, mantissa << exponent, for checking that results include what they should.
+  // Any mantissa of a Range<i64> fits, and so does any product of two of them.
+  #[derive (Copy, Clone, Debug)]
+  struct Exact {
+    mantissa: i128,
+    exponent: u64,
+  }
+
+  fn exact(mantissa: i128, exponent: u64) -> Exact {
+    Exact {
+      mantissa: mantissa,
+      exponent: exponent,
+    }
+  }
+
+  // value << shift <= other, which is the same as value <= floor (other >> shift)
+  fn shifted_at_most(value: i128, shift: u64, other: i128) -> bool {
+    value <= other >> min(shift, 127) as u32
+  }
+  // value << shift >= other, which is the same as value >= ceil (other >> shift)
+  fn shifted_at_least(value: i128, shift: u64, other: i128) -> bool {
+    value >= -((-other) >> min(shift, 127) as u32)
+  }
+  fn exact_at_most(first: Exact, second: Exact) -> bool {
+    if first.exponent >= second.exponent {
+      shifted_at_most(first.mantissa, first.exponent - second.exponent, second.mantissa)
+    } else {
+      shifted_at_least(second.mantissa, second.exponent - first.exponent, first.mantissa)
+    }
+  }
+  fn lower_bound(range: &Range) -> Exact {
+    exact(range.min as i128, range.exponent as u64)
+  }
+  fn upper_bound(range: &Range) -> Exact {
+    exact(range.max as i128, range.exponent as u64)
+  }
+  fn includes_exact(range: &Range, value: Exact) -> bool {
+    exact_at_most(lower_bound(range), value) && exact_at_most(value, upper_bound(range))
+  }
+  // Both endpoints, and an arbitrary value in between.
+  fn members(range: &Range, pick: u64) -> [Exact; 3] {
+    let span = (range.max as i128) - (range.min as i128) + 1;
+    [lower_bound(range),
+     upper_bound(range),
+     exact(range.min as i128 + (pick as i128) % span, range.exponent as u64)]
+  }
+  // Brings two values to the same exponent, if that doesn't overflow.
+  fn aligned(first: Exact, second: Exact) -> Option<(i128, i128, u64)> {
+    let exponent = min(first.exponent, second.exponent);
+    let first_shift = first.exponent - exponent;
+    let second_shift = second.exponent - exponent;
+    if first_shift > 60 || second_shift > 60 {
+      return None;
+    }
+    Some((first.mantissa << first_shift as u32, second.mantissa << second_shift as u32, exponent))
+  }
+  fn all_pairs<F: Fn(Exact, Exact) -> bool>(first: &Range, first_pick: u64, second: &Range, second_pick: u64, check: F) -> bool {
+    members(first, first_pick).iter().all(|&a| members(second, second_pick).iter().all(|&b| check(a, b)))
+  }
 
   quickcheck! {
     fn shift_left (range: Range, shift: u32)->TestResult {
@@ -789,6 +887,135 @@ mod tests {
       TestResult::from_bool ((Range::exactly (first)/Range::exactly (second)).includes (& Range::exactly (first/second)))
     }
 
+    fn add_includes_exact (first: Range, second: Range, first_pick: u64, second_pick: u64)->TestResult {
+      if max (first.exponent, second.exponent).checked_add (1).is_none() || aligned (lower_bound (&first), lower_bound (&second)).is_none() {
+        return TestResult::discard()
+      }
+      let result = first + second;
+      TestResult::from_bool (all_pairs (&first, first_pick, &second, second_pick, | a, b | {
+        let (a, b, exponent) = aligned (a, b).unwrap();
+        includes_exact (&result, exact (a + b, exponent))
+      }))
+    }
+    fn subtract_includes_exact (first: Range, second: Range, first_pick: u64, second_pick: u64)->TestResult {
+      if max (first.exponent, second.exponent).checked_add (1).is_none() || aligned (lower_bound (&first), lower_bound (&second)).is_none() {
+        return TestResult::discard()
+      }
+      let result = first - second;
+      TestResult::from_bool (all_pairs (&first, first_pick, &second, second_pick, | a, b | {
+        let (a, b, exponent) = aligned (a, b).unwrap();
+        includes_exact (&result, exact (a - b, exponent))
+      }))
+    }
+    fn multiply_includes_exact (first: Range, second: Range, first_pick: u64, second_pick: u64)->TestResult {
+      if first.exponent.checked_add (second.exponent).and_then (| total | total.checked_add (64)).is_none() {
+        return TestResult::discard()
+      }
+      let result = first * second;
+      TestResult::from_bool (all_pairs (&first, first_pick, &second, second_pick, | a, b | {
+        includes_exact (&result, exact (a.mantissa * b.mantissa, a.exponent + b.exponent))
+      }))
+    }
+    fn multiply_integer_includes_exact (range: Range, factor: i64, pick: u64)->TestResult {
+      if range.exponent.checked_add (64).is_none() {
+        return TestResult::discard()
+      }
+      let result = range * factor;
+      TestResult::from_bool (members (&range, pick).iter().all (| a | {
+        includes_exact (&result, exact (a.mantissa * factor as i128, a.exponent))
+      }))
+    }
+    fn divide_includes_exact (first: Range, second: Range, first_pick: u64, second_pick: u64)->TestResult {
+      if second.includes_0() {
+        return TestResult::discard()
+      }
+      let result = first / second;
+      // result.min <= a/b <= result.max, multiplied through by b (flipping signs to keep b positive)
+      TestResult::from_bool (all_pairs (&first, first_pick, &second, second_pick, | a, b | {
+        let (a, b) = if b.mantissa < 0 {(exact (-a.mantissa, a.exponent), exact (-b.mantissa, b.exponent))} else {(a, b)};
+        exact_at_most (exact (result.min as i128 * b.mantissa, result.exponent as u64 + b.exponent), a) &&
+        exact_at_most (a, exact (result.max as i128 * b.mantissa, result.exponent as u64 + b.exponent))
+      }))
+    }
+    fn shift_right_includes_exact (range: Range, shift: u32, pick: u64)->TestResult {
+      let result = range >> shift;
+      // a >> shift is in the result if a is in (result << shift)
+      let unshifted = |bound: Exact| exact (bound.mantissa, bound.exponent + shift as u64);
+      TestResult::from_bool (members (&range, pick).iter().all (| &a | {
+        exact_at_most (unshifted (lower_bound (&result)), a) && exact_at_most (a, unshifted (upper_bound (&result)))
+      }))
+    }
+    fn shift_left_includes_exact (range: Range, shift: u32, pick: u64)->TestResult {
+      if range.exponent.checked_add (shift).is_none() {
+        return TestResult::discard()
+      }
+      let result = range << shift;
+      TestResult::from_bool (members (&range, pick).iter().all (| a | {
+        includes_exact (&result, exact (a.mantissa, a.exponent + shift as u64))
+      }))
+    }
+    fn negate_includes_exact (range: Range, pick: u64)->bool {
+      let result = -range;
+      members (&range, pick).iter().all (| a | includes_exact (&result, exact (-a.mantissa, a.exponent)))
+    }
+    fn abs_includes_exact (range: Range, pick: u64)->bool {
+      let result = range.abs();
+      members (&range, pick).iter().all (| a | includes_exact (&result, exact (a.mantissa.abs(), a.exponent)))
+    }
+    fn squared_includes_exact (range: Range, pick: u64)->TestResult {
+      if range.exponent.checked_add (range.exponent).and_then (| total | total.checked_add (64)).is_none() {
+        return TestResult::discard()
+      }
+      let result = range.squared();
+      TestResult::from_bool (members (&range, pick).iter().all (| a | {
+        includes_exact (&result, exact (a.mantissa * a.mantissa, a.exponent * 2))
+      }))
+    }
+    fn sqrt_includes_exact (range: Range, pick: u64)->bool {
+      let range = range.abs();
+      let result = range.sqrt().unwrap();
+      // result.min^2 <= a <= result.max^2, since everything is nonnegative
+      let squared_bound = |bound: i64| exact (bound as i128 * bound as i128, result.exponent as u64 * 2);
+      members (&range, pick).iter().all (| &a | {
+        exact_at_most (squared_bound (result.min), a) && exact_at_most (a, squared_bound (result.max))
+      })
+    }
+    fn shrinking_reduces_size (range: Range)->bool {
+      // Every candidate must have a smaller total magnitude, or the same magnitude and a smaller width.
+      // That's what guarantees that shrinking terminates.
+      let magnitude = | range: &Range | exact ((range.min as i128).abs() + (range.max as i128).abs(), range.exponent as u64);
+      let width = | range: &Range | exact (range.max as i128 - range.min as i128, range.exponent as u64);
+      let less = | a: Exact, b: Exact | exact_at_most (a, b) && !exact_at_most (b, a);
+      let equal = | a: Exact, b: Exact | exact_at_most (a, b) && exact_at_most (b, a);
+      range.shrink().all (| candidate | {
+        less (magnitude (&candidate), magnitude (&range)) ||
+        (equal (magnitude (&candidate), magnitude (&range)) && less (width (&candidate), width (&range)))
+      })
+    }
+    fn shrinking_makes_progress (range: Range)->bool {
+      range.shrink().all (| candidate | candidate != range && candidate.min <= candidate.max)
+    }
+
+    fn wide_multiply (first: Range128, second: Range128)->TestResult {
+      if first.exponent.checked_add (second.exponent).and_then (| total | total.checked_add (128)).is_none() {
+        return TestResult::discard()
+      }
+      TestResult::from_bool (((first*second)/second).includes (&first))
+    }
+    fn wide_add (first: Range128, second: Range128)->TestResult {
+      if max (first.exponent, second.exponent).checked_add (1).is_none() {
+        return TestResult::discard()
+      }
+      TestResult::from_bool (((first + second) - second).includes (&first))
+    }
+    fn wide_sqrt (range: Range128)->TestResult {
+      TestResult::from_bool (((range.abs().sqrt().unwrap()).squared()).includes (&range.abs()))
+    }
+    fn wide_generalizes_i64 (first: i64, second: i64)->bool {
+      let (wide_first, wide_second) = (first as i128, second as i128);
+      Range128::exactly (wide_first*wide_second) == Range128::exactly (wide_first)*Range128::exactly (wide_second)
+    }
+
   }
 
   #[test]
@@ -803,6 +1030,9 @@ mod tests {
     assert_eq!(Range::exactly(100) / Range::exactly(2), Range::exactly(50));
     assert_eq!(Range::exactly(4) / Range::exactly(2), Range::exactly(2));
     assert_eq!(Range::exactly(0) / Range::exactly(5), Range::exactly(0));
+
+    assert_eq!(Range128::exactly(1 << 100) * Range128::exactly(1 << 20), Range128::exactly(1 << 120));
+    assert_eq!(Range128::exactly(99) / Range128::exactly(2), Range128::new(49, 50));
   }
 
 }
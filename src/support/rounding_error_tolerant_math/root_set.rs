@@ -0,0 +1,114 @@
+use std::ops::{Deref, DerefMut};
+use std::cmp::{max, min};
+use std::fmt;
+use super::integer::RangeInteger;
+use super::range::GenericRange;
+
+/// The most roots a RootSet can hold separately.
+pub const ROOT_SET_CAPACITY: usize = 16;
+
+/**
+
+The collection of Ranges returned by the polynomial solvers.
+
+It's stored inline, so the solvers never allocate. It dereferences to a slice, so it can be indexed and iterated like the Vec it replaces.
+
+The solvers push roots in increasing order. If a polynomial somehow produces more than ROOT_SET_CAPACITY of them (which takes a high degree and a lot of rounding error), the extra ones are merged into the last Range. That Range still includes all of them, so the result is still a superset of the exact roots, just a looser one.
+
+*/
+#[derive (Copy, Clone)]
+pub struct RootSet<T: RangeInteger> {
+  roots: [GenericRange<T>; ROOT_SET_CAPACITY],
+  len: usize,
+}
+
+impl<T: RangeInteger> RootSet<T> {
+  pub fn new() -> RootSet<T> {
+    RootSet {
+      roots: [GenericRange::zero(); ROOT_SET_CAPACITY],
+      len: 0,
+    }
+  }
+  pub fn with_root(root: GenericRange<T>) -> RootSet<T> {
+    let mut result = RootSet::new();
+    result.push(root);
+    result
+  }
+
+  fn merge_into_last(&mut self, root: GenericRange<T>) {
+    let last = &mut self.roots[self.len - 1];
+    *last = GenericRange::new(min(last.min(), root.min()), max(last.max(), root.max()));
+  }
+
+  pub fn push(&mut self, root: GenericRange<T>) {
+    if self.len == ROOT_SET_CAPACITY {
+      self.merge_into_last(root);
+    } else {
+      self.roots[self.len] = root;
+      self.len += 1;
+    }
+  }
+
+  pub fn insert(&mut self, index: usize, root: GenericRange<T>) {
+    assert!(index <= self.len, "RootSet::insert index out of bounds");
+    if index == self.len {
+      // push() already merges into the last root when the set is full.
+      self.push(root);
+      return;
+    }
+    if self.len == ROOT_SET_CAPACITY {
+      let last = self.roots[self.len - 1];
+      self.len -= 1;
+      self.insert(index, root);
+      self.merge_into_last(last);
+      return;
+    }
+    let mut which = self.len;
+    while which > index {
+      self.roots[which] = self.roots[which - 1];
+      which -= 1;
+    }
+    self.roots[index] = root;
+    self.len += 1;
+  }
+}
+
+impl<T: RangeInteger> Default for RootSet<T> {
+  fn default() -> RootSet<T> {
+    RootSet::new()
+  }
+}
+
+impl<T: RangeInteger> Deref for RootSet<T> {
+  type Target = [GenericRange<T>];
+  fn deref(&self) -> &[GenericRange<T>] {
+    &self.roots[..self.len]
+  }
+}
+
+impl<T: RangeInteger> DerefMut for RootSet<T> {
+  fn deref_mut(&mut self) -> &mut [GenericRange<T>] {
+    &mut self.roots[..self.len]
+  }
+}
+
+impl<'a, T: RangeInteger> IntoIterator for &'a RootSet<T> {
+  type Item = &'a GenericRange<T>;
+  type IntoIter = ::std::slice::Iter<'a, GenericRange<T>>;
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<T: RangeInteger> PartialEq for RootSet<T> {
+  fn eq(&self, other: &RootSet<T>) -> bool {
+    **self == **other
+  }
+}
+impl<T: RangeInteger> Eq for RootSet<T> {}
+
+impl<T: RangeInteger> fmt::Debug for RootSet<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Debug::fmt(&**self, f)
+  }
+}
@@ -0,0 +1,26 @@
+use super::range::*;
+
+// The internal fixed-point precision shared by the transcendental functions;
+// their output_shift can be at most this.
+pub const PRECISION_SHIFT: u32 = 60;
+
+// Sums an alternating series whose terms shrink, and adds the size of the first omitted term
+// as error, which bounds the remainder of such a series.
+// `first` is the first term; `next_term (previous, which)` returns the size of term
+// number `which` (counting from 1), and it is called for which in 1..terms + 1, the last one being the omitted term.
+pub fn alternating_series<F: FnMut(Range, usize) -> Range>(first: Range, terms: usize, mut next_term: F) -> Range {
+  let mut term = first;
+  let mut sum = first;
+  for which in 1..terms + 1 {
+    term = next_term(term, which);
+    if which == terms {
+      let remainder = term.abs().clamp_to_0_exponent().expect("series terms should be tiny by now").max();
+      sum = sum + Range::error_sized(remainder);
+    } else if which % 2 == 1 {
+      sum = sum - term;
+    } else {
+      sum = sum + term;
+    }
+  }
+  sum
+}
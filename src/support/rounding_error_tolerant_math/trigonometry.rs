@@ -0,0 +1,236 @@
+use std::ops::{Add, Sub, Neg};
+use super::range::*;
+use super::series::*;
+
+/**
+
+Deterministic trigonometry, for simulations that can't use floats.
+
+Angles are measured in turns rather than radians, so that they can be exact integers: an Angle is a u32 where 1 << 32 is a full turn. Adding or subtracting them wraps around, just like rotation does.
+
+sin() and cos() return Ranges scaled up by 1 << output_shift (so, for instance, sin (quarter turn, 30) is a Range that includes 1 << 30), and atan2() returns a Range of turn units. Like all Ranges, they are guaranteed to include the exact result. Everything is computed with Range arithmetic, so the result is the same on every platform.
+
+*/
+#[derive (Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug, Default)]
+pub struct Angle(pub u32);
+
+/// The number of Angle units in a full turn, as a shift.
+pub const TURN_SHIFT: u32 = 32;
+
+// pi << 61, rounded down and up.
+const PI_SHIFTED_61: [i64; 2] = [7244019458077122842, 7244019458077122843];
+
+fn pi_shifted_61() -> Range {
+  Range::new(PI_SHIFTED_61[0], PI_SHIFTED_61[1])
+}
+
+impl Angle {
+  pub fn zero() -> Angle {
+    Angle(0)
+  }
+  pub fn quarter_turn() -> Angle {
+    Angle(1 << (TURN_SHIFT - 2))
+  }
+  pub fn half_turn() -> Angle {
+    Angle(1 << (TURN_SHIFT - 1))
+  }
+  /// Any integer number of turn units, wrapped around to a single turn.
+  pub fn from_turn_units(units: i64) -> Angle {
+    Angle(units as u32)
+  }
+  /// The angle as turn units in [-half turn, half turn), which is the convention atan2() uses.
+  pub fn signed_turn_units(&self) -> i64 {
+    self.0 as i32 as i64
+  }
+  /// The angle in radians, scaled up by 1 << output_shift.
+  pub fn radians(&self, output_shift: u32) -> Range {
+    assert!(output_shift <= PRECISION_SHIFT);
+    // radians = units * 2pi >> 32 = units * (pi << 61) >> 92
+    (pi_shifted_61() * self.0 as i64) >> (TURN_SHIFT + 61 - 1 - output_shift)
+  }
+}
+
+impl Add for Angle {
+  type Output = Angle;
+  fn add(self, other: Angle) -> Angle {
+    Angle(self.0.wrapping_add(other.0))
+  }
+}
+impl Sub for Angle {
+  type Output = Angle;
+  fn sub(self, other: Angle) -> Angle {
+    Angle(self.0.wrapping_sub(other.0))
+  }
+}
+impl Neg for Angle {
+  type Output = Angle;
+  fn neg(self) -> Angle {
+    Angle(self.0.wrapping_neg())
+  }
+}
+
+// The Taylor series of sin or cos: each term is the previous one
+// times x_squared / (denominator * (denominator - 1)), starting at `first_denominator`.
+fn sin_cos_series(first: Range, x_squared: Range, first_denominator: i64, terms: usize) -> Range {
+  alternating_series(first, terms, |term, which| {
+    let denominator = first_denominator + 2 * (which as i64 - 1);
+    ((term * x_squared) >> PRECISION_SHIFT) / Range::exactly(denominator * (denominator - 1))
+  })
+}
+
+// sin and cos of an angle of at most an eighth of a turn (pi/4 radians),
+// scaled up by 1 << PRECISION_SHIFT. With x <= pi/4, these numbers of terms
+// (up to x^19/19! and x^20/20!) leave a remainder below 2^-60.
+fn sin_cos_first_octant(units: u32) -> (Range, Range) {
+  debug_assert!(units <= 1 << (TURN_SHIFT - 3));
+  let x = Angle(units).radians(PRECISION_SHIFT);
+  let x_squared = (x * x) >> PRECISION_SHIFT;
+  let one = Range::exactly(1) << PRECISION_SHIFT;
+  (sin_cos_series(x, x_squared, 3, 9), sin_cos_series(one, x_squared, 2, 10))
+}
+
+/// sin and cos together, scaled up by 1 << output_shift.
+pub fn sin_cos(angle: Angle, output_shift: u32) -> (Range, Range) {
+  assert!(output_shift <= PRECISION_SHIFT,
+          "trigonometry output can't be more precise than the internal precision");
+  let quarter = 1u32 << (TURN_SHIFT - 2);
+  let eighth = 1u32 << (TURN_SHIFT - 3);
+  let quadrant = angle.0 >> (TURN_SHIFT - 2);
+  let within = angle.0 & (quarter - 1);
+  let (sin, cos) = if within <= eighth {
+    sin_cos_first_octant(within)
+  } else {
+    let (sin, cos) = sin_cos_first_octant(quarter - within);
+    (cos, sin)
+  };
+  let (sin, cos) = match quadrant {
+    0 => (sin, cos),
+    1 => (cos, -sin),
+    2 => (-sin, -cos),
+    _ => (-cos, sin),
+  };
+  (sin >> (PRECISION_SHIFT - output_shift), cos >> (PRECISION_SHIFT - output_shift))
+}
+
+pub fn sin(angle: Angle, output_shift: u32) -> Range {
+  sin_cos(angle, output_shift).0
+}
+pub fn cos(angle: Angle, output_shift: u32) -> Range {
+  sin_cos(angle, output_shift).1
+}
+
+/// Rotates the vector (x, y) counterclockwise by `angle`.
+pub fn rotate(x: i64, y: i64, angle: Angle) -> (Range, Range) {
+  let (sin, cos) = sin_cos(angle, PRECISION_SHIFT);
+  (((cos * x) - (sin * y)) >> PRECISION_SHIFT, ((sin * x) + (cos * y)) >> PRECISION_SHIFT)
+}
+
+/**
+
+The angle of the vector (x, y), in turn units, within [-half turn, half turn], or None for (0, 0).
+
+The inputs can't be i64::min_value(), because their absolute values have to fit in an i64.
+
+*/
+pub fn atan2(y: i64, x: i64) -> Option<Range> {
+  assert!(x != i64::min_value() && y != i64::min_value(),
+          "atan2 inputs must have absolute values that fit in an i64");
+  if x == 0 && y == 0 {
+    return None;
+  }
+  let (ax, ay) = (x.abs(), y.abs());
+  let swapped = ay > ax;
+  let (small, big) = if swapped { (ax, ay) } else { (ay, ax) };
+
+  let one = Range::exactly(1) << PRECISION_SHIFT;
+  // t = tan(theta) in [0, 1]; halve the angle twice using tan(theta/2) = t/(1 + sqrt(1 + t^2)),
+  // so that t <= tan(pi/16) < 0.2 and the series converges quickly.
+  let mut t = (Range::exactly(small) << PRECISION_SHIFT) / Range::exactly(big);
+  for _ in 0..2 {
+    let hypotenuse = ((one << PRECISION_SHIFT) + t.squared()).sqrt().expect("1 + t^2 is positive");
+    t = (t << PRECISION_SHIFT) / (one + hypotenuse);
+  }
+  // atan(t) = t - t^3/3 + t^5/5 - ..., with a remainder no bigger than the first omitted term.
+  // With t < 0.2, 13 terms leave a remainder below 2^-60.
+  let t_squared = (t * t) >> PRECISION_SHIFT;
+  let mut power = t;
+  let radians = alternating_series(t, 13, |_, which| {
+    power = (power * t_squared) >> PRECISION_SHIFT;
+    power / Range::exactly(2 * which as i64 + 1)
+  });
+  let radians = radians << 2;
+
+  // units = (radians >> 60) * 2^32 / 2pi = (radians << 32) / (pi << 61)
+  let mut units = (radians << TURN_SHIFT) / pi_shifted_61();
+  let quarter = Range::exactly(1 << (TURN_SHIFT - 2));
+  let half = Range::exactly(1 << (TURN_SHIFT - 1));
+  if swapped {
+    units = quarter - units;
+  }
+  if x < 0 {
+    units = half - units;
+  }
+  if y < 0 {
+    units = -units;
+  }
+  Some(units)
+}
+
+#[cfg (test)]
+mod tests {
+  use super::*;
+
+  const SHIFT: u32 = 30;
+
+  fn includes_float(range: Range, value: f64, shift: u32) -> bool {
+    // Allow for the float's own rounding error, which is far smaller than a unit.
+    let scaled = value * (1u64 << shift) as f64;
+    (range.min() as f64) <= scaled + 0.01 && (range.max() as f64) >= scaled - 0.01
+  }
+
+  quickcheck! {
+    fn sin_cos_include_exact (units: u32)->bool {
+      let (sin, cos) = sin_cos (Angle (units), SHIFT);
+      let radians = units as f64 * ::std::f64::consts::PI * 2.0 / (1u64 << TURN_SHIFT) as f64;
+      includes_float (sin, radians.sin(), SHIFT) && includes_float (cos, radians.cos(), SHIFT) &&
+        sin.max() - sin.min() <= 2 && cos.max() - cos.min() <= 2
+    }
+    fn sin_squared_plus_cos_squared (units: u32)->bool {
+      let (sin, cos) = sin_cos (Angle (units), SHIFT);
+      (sin.squared() + cos.squared()).includes (& (Range::exactly (1) << (SHIFT*2)))
+    }
+    fn atan2_includes_exact (y: i32, x: i32)->bool {
+      match atan2 (y as i64, x as i64) {
+        None => x == 0 && y == 0,
+        Some (units) => {
+          let exact = (y as f64).atan2 (x as f64) / (::std::f64::consts::PI * 2.0);
+          includes_float (units, exact, TURN_SHIFT) && units.max() - units.min() <= 2
+        }
+      }
+    }
+    fn atan2_inverts_sin_cos (units: u32)->bool {
+      let angle = Angle (units);
+      let (sin, cos) = sin_cos (angle, SHIFT);
+      // The vector's direction is only known to within the error of sin and cos, so allow a few units more.
+      let result = atan2 (sin.min(), cos.min()).unwrap();
+      let target = angle.signed_turn_units();
+      let distance = |value: i64| {
+        let difference = (value - target).abs();
+        ::std::cmp::min (difference, (1i64 << TURN_SHIFT) - difference)
+      };
+      distance (result.min()) <= 16 && distance (result.max()) <= 16
+    }
+  }
+
+  #[test]
+  fn exact_angles() {
+    assert!(sin(Angle::zero(), SHIFT).includes(&Range::exactly(0)));
+    assert!(cos(Angle::zero(), SHIFT).includes(&Range::exactly(1 << SHIFT)));
+    assert!(sin(Angle::quarter_turn(), SHIFT).includes(&Range::exactly(1 << SHIFT)));
+    assert!(cos(Angle::half_turn(), SHIFT).includes(&Range::exactly(-1 << SHIFT)));
+    assert!(atan2(0, 5).unwrap().includes(&Range::exactly(0)));
+    assert!(atan2(5, 0).unwrap().includes(&Range::exactly(1 << (TURN_SHIFT - 2))));
+    assert!(atan2(-5, -5).unwrap().includes(&Range::exactly(-3 << (TURN_SHIFT - 3))));
+    assert_eq!(Angle::half_turn() + Angle::half_turn(), Angle::zero());
+  }
+}
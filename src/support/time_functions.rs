@@ -1,126 +1,964 @@
-use nalgebra::Vector2;
-use std::cmp::max;
+use nalgebra::{Vector2, Vector3};
+use std::cmp::{min, max};
 use super::rounding_error_tolerant_math::*;
+use super::rational::Rational64;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::DeserializeOwned;
 
-// TODO: polymorphic in number of dimensions, and numeric type
 // TODO: optimize away the pointless inefficiencies I introduced
 // note: the third time is not acceleration, but the coefficient of X squared, which is 2 times acceleration
 pub type Coordinate = i64;
-#[derive (Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
-pub struct QuadraticTrajectory {
-  data: [[Coordinate; 3]; 2],
+/// For worlds too large for i64 coordinates to leave enough headroom.
+pub type WideCoordinate = i128;
+
+/**
+
+A simulation time that the solvers here accept as their `base` and `now` times.
+
+The trajectories themselves count whole ticks since their base time. The solvers round `now` up to the next tick, and report times a whole number of ticks after `base`, so a dense time like Rational64 can be used directly, but the answers are only as fine as the ticks: a crossing between two ticks is reported at the later one. Only LinearTrajectory::exactly_when_crosses() gives exact fractional times; the other solvers have no exact variants.
+
+*/
+pub trait TrajectoryTime<Ticks>: Copy + Ord {
+  /// The number of ticks from `base` to this time, rounded up, or None if it doesn't fit.
+  fn ticks_since(self, base: Self) -> Option<Ticks>;
+  /// The time `ticks` ticks after this one, or None if it doesn't fit.
+  fn plus_ticks(self, ticks: Ticks) -> Option<Self>;
+}
+impl TrajectoryTime<Coordinate> for Coordinate {
+  fn ticks_since(self, base: Coordinate) -> Option<Coordinate> {
+    self.checked_sub(base)
+  }
+  fn plus_ticks(self, ticks: Coordinate) -> Option<Coordinate> {
+    self.checked_add(ticks)
+  }
+}
+impl TrajectoryTime<WideCoordinate> for WideCoordinate {
+  fn ticks_since(self, base: WideCoordinate) -> Option<WideCoordinate> {
+    self.checked_sub(base)
+  }
+  fn plus_ticks(self, ticks: WideCoordinate) -> Option<WideCoordinate> {
+    self.checked_add(ticks)
+  }
+}
+impl TrajectoryTime<Coordinate> for Rational64 {
+  fn ticks_since(self, base: Rational64) -> Option<Coordinate> {
+    self.checked_sub(base).map(|difference| difference.ceil())
+  }
+  fn plus_ticks(self, ticks: Coordinate) -> Option<Rational64> {
+    self.checked_add(Rational64::from_integer(ticks))
+  }
+}
+impl TrajectoryTime<WideCoordinate> for Rational64 {
+  fn ticks_since(self, base: Rational64) -> Option<WideCoordinate> {
+    self.checked_sub(base).map(|difference| difference.ceil() as WideCoordinate)
+  }
+  fn plus_ticks(self, ticks: WideCoordinate) -> Option<Rational64> {
+    if ticks > Coordinate::max_value() as WideCoordinate || ticks < Coordinate::min_value() as WideCoordinate {
+      return None;
+    }
+    self.checked_add(Rational64::from_integer(ticks as Coordinate))
+  }
+}
+
+// serde (before 1.0.60) and bincode 0.8 can't serialize i128s, so the trajectories are serialized
+// through copies of themselves whose coordinates have been converted by this trait.
+// Coordinates are serialized as themselves, so the i64 trajectories have the same format that deriving would give,
+// and WideCoordinates are serialized as their (high, low) 64-bit halves.
+trait WireCoordinate: Copy {
+  type Wire: Copy + Default + Serialize + DeserializeOwned;
+  fn to_wire(self) -> Self::Wire;
+  fn from_wire(wire: Self::Wire) -> Self;
+}
+impl WireCoordinate for Coordinate {
+  type Wire = Coordinate;
+  fn to_wire(self) -> Coordinate {
+    self
+  }
+  fn from_wire(wire: Coordinate) -> Coordinate {
+    wire
+  }
+}
+impl WireCoordinate for WideCoordinate {
+  type Wire = (i64, u64);
+  fn to_wire(self) -> (i64, u64) {
+    ((self >> 64) as i64, self as u64)
+  }
+  fn from_wire((high, low): (i64, u64)) -> WideCoordinate {
+    ((high as i128) << 64) | (low as i128)
+  }
+}
+type Wire<C> = <C as WireCoordinate>::Wire;
+
+#[derive (Serialize, Deserialize)]
+struct SerializedPolynomialTrajectory<Data, Scalar> {
+  data: Data,
+  time_scale_shift: u32,
+  max_distance_traveled_at_once: Scalar,
+}
+
+#[derive (Serialize, Deserialize)]
+struct SerializedLinearTrajectory<Scalar> {
+  value: Scalar,
+  rate: Scalar,
   time_scale_shift: u32,
-  max_distance_traveled_at_once: i64,
 }
 
-fn distance_squared_would_be(first: (Coordinate, &QuadraticTrajectory),
-                             second: (Coordinate, &QuadraticTrajectory),
-                             when: Coordinate)
-                             -> Option<Coordinate> {
-  if let Some(third) = first.1.updated_by(when - first.0) {
-    if let Some(more) = second.1.updated_by(when - second.0) {
-      let displacement = third.evaluate() - more.evaluate();
-      return Some(displacement.dot(&displacement));
+macro_rules! serialize_polynomial_trajectory {
+  ($Trajectory: ident, $dimensions: expr, $terms: expr, $Coordinate: ident) => {
+
+impl Serialize for $Trajectory {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut data: [[Wire<$Coordinate>; $terms]; $dimensions] = [[Default::default(); $terms]; $dimensions];
+    for (wires, terms) in data.iter_mut().zip(self.data.iter()) {
+      for (wire, term) in wires.iter_mut().zip(terms.iter()) {
+        *wire = term.to_wire();
+      }
     }
+    SerializedPolynomialTrajectory {
+      data: data,
+      time_scale_shift: self.time_scale_shift,
+      max_distance_traveled_at_once: self.max_distance_traveled_at_once.to_wire(),
+    }.serialize(serializer)
   }
-  None
 }
 
-impl QuadraticTrajectory {
+impl<'de> Deserialize<'de> for $Trajectory {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$Trajectory, D::Error> {
+    let serialized: SerializedPolynomialTrajectory<[[Wire<$Coordinate>; $terms]; $dimensions], Wire<$Coordinate>> =
+      try!(Deserialize::deserialize(deserializer));
+    let mut data = [[0; $terms]; $dimensions];
+    for (terms, wires) in data.iter_mut().zip(serialized.data.iter()) {
+      for (term, wire) in terms.iter_mut().zip(wires.iter()) {
+        *term = $Coordinate::from_wire(*wire);
+      }
+    }
+    Ok($Trajectory {
+      data: data,
+      time_scale_shift: serialized.time_scale_shift,
+      max_distance_traveled_at_once: $Coordinate::from_wire(serialized.max_distance_traveled_at_once),
+    })
+  }
+}
+
+  }
+}
+
+// The trajectory types for each degree, number of dimensions and coordinate type are identical
+// apart from their array sizes, vector types, integer types, and which functions in rounding_error_tolerant_math
+// they use to move the origin and bound the solutions, so they are all generated by this macro.
+// The math underneath is generic over the integer type.
+//
+// The coordinates passed to new() are all the positions, then all the velocities,
+// then all the coefficients of X squared, and so on; for instance, [x, y, vx, vy, 2ax, 2ay] for a 2D quadratic.
+//
+// Limits:
+// - The solvers only allow for the rounding error of moving the origin at most
+//   max_distance_traveled_at_once in each dimension at a time. Moving it further at once fails
+//   with TrajectoryError::TraveledTooFar, so simulations should update their trajectories
+//   at least that often; rebase_by() does it in steps for a trajectory that has gone a long time
+//   without one.
+// - Positions, velocities, and squared distances between trajectories must fit in the coordinate type,
+//   or the result is TrajectoryError::Overflow. The Wide types have much more room.
+// updated_by(), update_by(), and approximately_when_distance_passes() treat these failures as
+// None or a panic; the try_ versions report them, so that the caller can handle them
+// (for instance, by clamping a fast-moving object's velocity).
+macro_rules! polynomial_trajectory {
+  ($Trajectory: ident, $Vector: ident, $dimensions: expr, $Coordinate: ident, $terms: expr,
+   $move_origin: ident, $max_safe_move: ident, $remain_in_bounds: ident, $crossing_intervals: ident) => {
+
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct $Trajectory {
+  data: [[$Coordinate; $terms]; $dimensions],
+  time_scale_shift: u32,
+  max_distance_traveled_at_once: $Coordinate,
+}
+
+serialize_polynomial_trajectory!($Trajectory, $dimensions, $terms, $Coordinate);
+
+impl $Trajectory {
   pub fn new(time_scale_shift: u32,
-             max_distance_traveled_at_once: i64,
-             coordinates: [Coordinate; 6])
-             -> QuadraticTrajectory {
-    QuadraticTrajectory {
+             max_distance_traveled_at_once: $Coordinate,
+             coordinates: [$Coordinate; $dimensions * $terms])
+             -> $Trajectory {
+    let mut data = [[0; $terms]; $dimensions];
+    for (dimension, polynomial) in data.iter_mut().enumerate() {
+      for (power, term) in polynomial.iter_mut().enumerate() {
+        *term = coordinates[power * $dimensions + dimension];
+      }
+    }
+    $Trajectory {
       time_scale_shift: time_scale_shift,
       max_distance_traveled_at_once: max_distance_traveled_at_once,
-      data: [[coordinates[0], coordinates[2], coordinates[4]],
-             [coordinates[1], coordinates[3], coordinates[5]]],
+      data: data,
     }
   }
 
-  pub fn updated_by(&self, time: Coordinate) -> Option<QuadraticTrajectory> {
+  pub fn try_updated_by(&self, time: $Coordinate) -> Result<$Trajectory, TrajectoryError> {
     let mut result = self.clone();
-    for quadratic in result.data.iter_mut() {
-      if !quadratic_move_origin_rounding_change_towards_0(quadratic.as_mut(),
-                                                              time,
-                                                              self.time_scale_shift,max_error_for_distance_traveled (self.max_distance_traveled_at_once)) {return None;}
+    for polynomial in result.data.iter_mut() {
+      try!($move_origin(polynomial.as_mut(),
+                    time,
+                    self.time_scale_shift,
+                    max_error_for_distance_traveled(self.max_distance_traveled_at_once)));
     }
-    Some(result)
+    Ok(result)
+  }
+  pub fn updated_by(&self, time: $Coordinate) -> Option<$Trajectory> {
+    self.try_updated_by(time).ok()
   }
 
-  pub fn update_by(&mut self, time: Coordinate) {
-    for quadratic in self.data.iter_mut() {
-      assert!(quadratic_move_origin_rounding_change_towards_0(quadratic.as_mut(),
-                                                              time,
-                                                              self.time_scale_shift,max_error_for_distance_traveled (self.max_distance_traveled_at_once)));
+  /// On failure, the trajectory is left unchanged.
+  pub fn try_update_by(&mut self, time: $Coordinate) -> Result<(), TrajectoryError> {
+    *self = try!(self.try_updated_by(time));
+    Ok(())
+  }
+  pub fn update_by(&mut self, time: $Coordinate) {
+    if let Err(error) = self.try_update_by(time) {
+      panic!("{} (use try_update_by() to handle this)", error);
     }
   }
 
-  pub fn add_acceleration(&mut self, acceleration: Vector2<Coordinate>) {
-    self.data[0][2] += acceleration[0] * 2;
-    self.data[1][2] += acceleration[1] * 2;
+  /// Moves the origin forward by `time`, in as many steps as it takes for each one to stay within
+  /// max_distance_traveled_at_once, so that the rounding error stays within what the solvers allow for.
+  /// The rounding error adds up over the steps, just as it would if the simulation had updated
+  /// the trajectory that often.
+  ///
+  /// This only fails if `time` is negative (BackwardsInTime), the trajectory travels more than
+  /// max_distance_traveled_at_once in a single time unit (TraveledTooFar), or its coordinates
+  /// no longer fit (Overflow). On failure, the trajectory is left unchanged.
+  pub fn rebase_by(&mut self, time: $Coordinate) -> Result<(), TrajectoryError> {
+    if time < 0 {
+      return Err(TrajectoryError::BackwardsInTime);
+    }
+    let mut result = self.clone();
+    let mut remaining = time;
+    while remaining > 0 {
+      let step = result.data
+        .iter()
+        .map(|polynomial| {
+          $max_safe_move(&polynomial[..],
+                         result.time_scale_shift,
+                         result.max_distance_traveled_at_once,
+                         remaining)
+        })
+        .min()
+        .unwrap_or(remaining);
+      if step == 0 {
+        return Err(TrajectoryError::TraveledTooFar);
+      }
+      try!(result.try_update_by(step));
+      remaining -= step;
+    }
+    *self = result;
+    Ok(())
   }
-  pub fn add_velocity(&mut self, velocity: Vector2<Coordinate>) {
-    self.data[0][1] += velocity[0];
-    self.data[1][1] += velocity[1];
+
+  pub fn add_acceleration(&mut self, acceleration: $Vector<$Coordinate>) {
+    for (dimension, polynomial) in self.data.iter_mut().enumerate() {
+      polynomial[2] += acceleration[dimension] * 2;
+    }
+  }
+  pub fn add_velocity(&mut self, velocity: $Vector<$Coordinate>) {
+    for (dimension, polynomial) in self.data.iter_mut().enumerate() {
+      polynomial[1] += velocity[dimension];
+    }
+  }
+  pub fn evaluate(&self) -> $Vector<$Coordinate> {
+    $Vector::from_fn(|dimension, _| self.data[dimension][0])
   }
-  pub fn evaluate(&self) -> Vector2<Coordinate> {
-    Vector2::new(self.data[0][0], self.data[1][0])
 
+  fn distance_squared_would_be(first: ($Coordinate, &$Trajectory),
+                               second: ($Coordinate, &$Trajectory),
+                               when: $Coordinate)
+                               -> Result<$Coordinate, TrajectoryError> {
+    let third = try!(first.1.try_updated_by(when - first.0));
+    let more = try!(second.1.try_updated_by(when - second.0));
+    let mut total: $Coordinate = 0;
+    for (third, more) in third.data.iter().zip(more.data.iter()) {
+      total = try!(third[0].checked_sub(more[0])
+        .and_then(|displacement| displacement.checked_mul(displacement))
+        .and_then(|square| total.checked_add(square))
+        .ok_or(TrajectoryError::Overflow));
+    }
+    Ok(total)
   }
 
-  // Bounds are min and max for the first dimension, then min and max for the second dimension
-  pub fn approximately_when_escapes(&self,
-                                    base: Coordinate,
-                                    now: Coordinate,
-                                    bounds: [[Coordinate; 2]; 2])
-                                    -> Option<Coordinate> {
-    time_until_which_quadratic_trajectory_may_remain_in_bounds (now - base, & self.data, & bounds, self.time_scale_shift, max_error_for_distance_traveled (self.max_distance_traveled_at_once)).map (| time | time + base)
+  // Bounds are min and max for the first dimension, then min and max for the second dimension, and so on
+  pub fn approximately_when_escapes<Time: TrajectoryTime<$Coordinate>>(&self,
+                                                                       base: Time,
+                                                                       now: Time,
+                                                                       bounds: [[$Coordinate; 2]; $dimensions])
+                                                                       -> Option<Time> {
+    now.ticks_since(base)
+      .and_then(|now| $remain_in_bounds (now, & self.data, & bounds, self.time_scale_shift, max_error_for_distance_traveled (self.max_distance_traveled_at_once)))
+      .and_then(|time| base.plus_ticks(time))
   }
 
   // direction == -1->"when the distance between the trajectories drops below the distance argument"
   // direction == 1->"when the distance between the trajectories exceeds the distance argument"
-  pub fn approximately_when_distance_passes(distance: Coordinate,
-                                            direction: Coordinate,
-                                            first: (Coordinate, &QuadraticTrajectory),
-                                            second: (Coordinate, &QuadraticTrajectory))
-                                            -> Option<Coordinate> {
+  //
+  // Fails if the distance can't be computed at the later of the two base times, or at a time
+  // when it may pass, which means that one of the trajectories would travel too far from its base time
+  // without being updated, or that a coordinate or the squared distance doesn't fit in the coordinate type.
+  // It also fails with FractionalTicks if the base times aren't a whole number of ticks apart,
+  // since neither trajectory can then be evaluated at the other's ticks.
+  pub fn try_approximately_when_distance_passes<Time: TrajectoryTime<$Coordinate>>(distance: $Coordinate,
+                                                                                  direction: $Coordinate,
+                                                                                  first: (Time, &$Trajectory),
+                                                                                  second: (Time, &$Trajectory))
+                                                                                  -> Result<Option<Time>, TrajectoryError> {
+    // Count ticks from the later base time, so that both origins are whole numbers of ticks.
+    let base = max(first.0, second.0);
+    let origin = |time: Time| -> Result<$Coordinate, TrajectoryError> {
+      let ticks = try!(base.ticks_since(time).ok_or(TrajectoryError::Overflow));
+      if time.plus_ticks(ticks) != Some(base) {
+        return Err(TrajectoryError::FractionalTicks);
+      }
+      Ok(-ticks)
+    };
+    let first_in_ticks = (try!(origin(first.0)), first.1);
+    let second_in_ticks = (try!(origin(second.0)), second.1);
+    match try!($Trajectory::try_approximately_when_distance_passes_in_ticks(distance, direction, first_in_ticks, second_in_ticks)) {
+      None => Ok(None),
+      Some(ticks) => base.plus_ticks(ticks).map(Some).ok_or(TrajectoryError::Overflow),
+    }
+  }
+
+  fn try_approximately_when_distance_passes_in_ticks(distance: $Coordinate,
+                                                     direction: $Coordinate,
+                                                     first: ($Coordinate, &$Trajectory),
+                                                     second: ($Coordinate, &$Trajectory))
+                                                     -> Result<Option<$Coordinate>, TrajectoryError> {
     assert!(first.1.time_scale_shift == second.1.time_scale_shift,
             "we don't actually support interactions between trajectories with different scales");
     assert!(first.1.max_distance_traveled_at_once == second.1.max_distance_traveled_at_once,
             "we don't actually support interactions between trajectories with different error \
              limits");
 
+    let distance_squared = try!(distance.checked_mul(distance).ok_or(TrajectoryError::Overflow));
+    let passed = |distance_squared_then: $Coordinate| {
+      (direction > 0 && distance_squared_then > distance_squared) ||
+      (direction < 0 && distance_squared_then < distance_squared)
+    };
 
     let base = max(first.0, second.0);
-    if (distance_squared_would_be(first, second, base)
-      .expect("we shouldn't have already maxed out our distance traveled at the \
-               BEGINNING of the test!") - distance * distance) * direction > 0 {
-      //printlnerr!("rejefoo");
-      return Some(base);
+    if passed(try!($Trajectory::distance_squared_would_be(first, second, base))) {
+      return Ok(Some(base));
     }
 
     let intervals =
-      quadratic_trajectories_possible_distance_crossing_intervals(distance,
-                                                                  (first.0,
-                                                                   &[first.1.data[0],
-                                                                     first.1.data[1]]),
-                                                                  (second.0,
-                                                                   &[second.1.data[0],
-                                                                     second.1.data[1]]),
-                                                                  first.1.time_scale_shift,max_error_for_distance_traveled (first.1.max_distance_traveled_at_once));
+      $crossing_intervals(distance,
+                (first.0, &first.1.data),
+                (second.0, &second.1.data),
+                first.1.time_scale_shift,max_error_for_distance_traveled (first.1.max_distance_traveled_at_once));
     for interval in intervals.iter() {
-      if interval.max() != i64::max_value() && interval.max() + 1 > base {
-        if let Some(future_distance_squared) = distance_squared_would_be(first,
-                                                                         second,
-                                                                         interval.max() + 1) {
-          if (future_distance_squared - distance * distance) * direction > 0 {
-            return Some(interval.max() + 1);
-          } //else {printlnerr!("rejected interval for not ending in the correct position")}
+      if interval.max() != $Coordinate::max_value() && interval.max() + 1 > base {
+        let future_distance_squared = try!($Trajectory::distance_squared_would_be(first, second, interval.max() + 1));
+        if passed(future_distance_squared) {
+          return Ok(Some(interval.max() + 1));
         }
-      } //else {printlnerr!("rejected interval for being in the past")}
+      }
+    }
+    Ok(None)
+  }
+
+  pub fn approximately_when_distance_passes<Time: TrajectoryTime<$Coordinate>>(distance: $Coordinate,
+                                                                              direction: $Coordinate,
+                                                                              first: (Time, &$Trajectory),
+                                                                              second: (Time, &$Trajectory))
+                                                                              -> Option<Time> {
+    match $Trajectory::try_approximately_when_distance_passes(distance, direction, first, second) {
+      Ok(result) => result,
+      Err(error) => panic!("{} while looking for when the distance passes {} (use \
+                            try_approximately_when_distance_passes() to handle this)", error, distance),
+    }
+  }
+}
+
+  }
+}
+
+// The quadratic trajectories use the specialized (faster) functions for quadratics in rounding_error_tolerant_math.
+macro_rules! quadratic_trajectory {
+  ($Trajectory: ident, $Vector: ident, $dimensions: expr, $Coordinate: ident) => {
+
+polynomial_trajectory!($Trajectory, $Vector, $dimensions, $Coordinate, 3,
+                       try_quadratic_move_origin_rounding_change_towards_0,
+                       quadratic_max_safe_move,
+                       time_until_which_quadratic_trajectory_may_remain_in_bounds,
+                       quadratic_trajectories_possible_distance_crossing_intervals);
+
+  }
+}
+
+quadratic_trajectory!(QuadraticTrajectory, Vector2, 2, Coordinate);
+quadratic_trajectory!(QuadraticTrajectory3, Vector3, 3, Coordinate);
+quadratic_trajectory!(WideQuadraticTrajectory, Vector2, 2, WideCoordinate);
+quadratic_trajectory!(WideQuadraticTrajectory3, Vector3, 3, WideCoordinate);
+
+// Trajectories with a cubic term (jerk), for motion that changes its acceleration smoothly.
+// They use the degree-general functions in rounding_error_tolerant_math, which are somewhat slower.
+macro_rules! cubic_trajectory {
+  ($Trajectory: ident, $Vector: ident, $dimensions: expr, $Coordinate: ident) => {
+
+polynomial_trajectory!($Trajectory, $Vector, $dimensions, $Coordinate, 4,
+                       try_polynomial_move_origin_rounding_change_towards_0,
+                       polynomial_max_safe_move,
+                       time_until_which_polynomial_trajectory_may_remain_in_bounds,
+                       polynomial_trajectories_possible_distance_crossing_intervals);
+
+impl $Trajectory {
+  pub fn add_jerk(&mut self, jerk: $Vector<$Coordinate>) {
+    for (dimension, cubic) in self.data.iter_mut().enumerate() {
+      cubic[3] += jerk[dimension];
     }
-    None
+  }
+}
+
+  }
+}
+
+cubic_trajectory!(CubicTrajectory, Vector2, 2, Coordinate);
+cubic_trajectory!(CubicTrajectory3, Vector3, 3, Coordinate);
+
+// Trajectories for a single value that changes at a constant rate, like an amount of ink
+// flowing from one cell to another. The rate is per (1 << time_scale_shift) time units.
+macro_rules! linear_trajectory {
+  ($Trajectory: ident, $Coordinate: ident) => {
+
+#[derive (Clone, PartialEq, Eq, Debug)]
+pub struct $Trajectory {
+  value: $Coordinate,
+  rate: $Coordinate,
+  time_scale_shift: u32,
+}
+
+impl Serialize for $Trajectory {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    SerializedLinearTrajectory {
+      value: self.value.to_wire(),
+      rate: self.rate.to_wire(),
+      time_scale_shift: self.time_scale_shift,
+    }.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for $Trajectory {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$Trajectory, D::Error> {
+    let serialized: SerializedLinearTrajectory<Wire<$Coordinate>> = try!(Deserialize::deserialize(deserializer));
+    Ok($Trajectory {
+      value: $Coordinate::from_wire(serialized.value),
+      rate: $Coordinate::from_wire(serialized.rate),
+      time_scale_shift: serialized.time_scale_shift,
+    })
+  }
+}
+
+impl $Trajectory {
+  pub fn new(time_scale_shift: u32, value: $Coordinate, rate: $Coordinate) -> $Trajectory {
+    $Trajectory {
+      value: value,
+      rate: rate,
+      time_scale_shift: time_scale_shift,
+    }
+  }
+
+  // Like the other trajectories, this rounds the change towards 0, and returns None if the result would overflow.
+  pub fn updated_by(&self, time: $Coordinate) -> Option<$Trajectory> {
+    let change = (GenericRange::exactly(self.rate) * time) >> self.time_scale_shift;
+    change.clamp_to_0_exponent()
+      .and_then(|change| self.value.checked_add(change.rounded_towards_0()))
+      .map(|value| $Trajectory { value: value, ..self.clone() })
+  }
+
+  pub fn try_update_by(&mut self, time: $Coordinate) -> Result<(), TrajectoryError> {
+    *self = try!(self.updated_by(time).ok_or(TrajectoryError::Overflow));
+    Ok(())
+  }
+  pub fn update_by(&mut self, time: $Coordinate) {
+    if let Err(error) = self.try_update_by(time) {
+      panic!("{} (use try_update_by() to handle this)", error);
+    }
+  }
+
+  // The adders leave the trajectory unchanged if the result would overflow.
+  pub fn add_rate(&mut self, rate: $Coordinate) -> Result<(), TrajectoryError> {
+    self.rate = try!(self.rate.checked_add(rate).ok_or(TrajectoryError::Overflow));
+    Ok(())
+  }
+  pub fn add_value(&mut self, value: $Coordinate) -> Result<(), TrajectoryError> {
+    self.value = try!(self.value.checked_add(value).ok_or(TrajectoryError::Overflow));
+    Ok(())
+  }
+  pub fn evaluate(&self) -> $Coordinate {
+    self.value
+  }
+  pub fn rate(&self) -> $Coordinate {
+    self.rate
+  }
+
+  // Returns the first time, no earlier than now, by which the trajectory has definitely crossed `value`,
+  // or None if it isn't moving towards `value`, crossed it before now, or would only cross it after
+  // the time overflows.
+  pub fn approximately_when_crosses<Time: TrajectoryTime<$Coordinate>>(&self,
+                                                                       base: Time,
+                                                                       now: Time,
+                                                                       value: $Coordinate)
+                                                                       -> Option<Time> {
+    if self.rate == 0 {
+      return None;
+    }
+    let distance = match value.checked_sub(self.value) {
+      Some(distance) => distance,
+      None => return None,
+    };
+    let crossing = (GenericRange::exactly(distance) << self.time_scale_shift) /
+                   GenericRange::exactly(self.rate);
+    let latest = match crossing.clamp_to_0_exponent() {
+      Some(crossing) => crossing.max(),
+      None => return None,
+    };
+    match now.ticks_since(base) {
+      Some(earliest) if latest >= earliest => {}
+      _ => return None,
+    }
+    base.plus_ticks(latest).map(|time| max(time, now))
+  }
+}
+
+  }
+}
+
+linear_trajectory!(LinearTrajectory, Coordinate);
+linear_trajectory!(WideLinearTrajectory, WideCoordinate);
+
+impl LinearTrajectory {
+  // The exact time at which the trajectory reaches `value`, for simulations with a Rational64 Time.
+  // `base` is the time of the trajectory's origin, and is measured in the same ticks as the rate.
+  // Returns None if it isn't moving towards `value`, reached it before now, or the time doesn't fit in a Rational64.
+  pub fn exactly_when_crosses(&self, base: Rational64, now: Rational64, value: Coordinate) -> Option<Rational64> {
+    if self.rate == 0 {
+      return None;
+    }
+    let distance = (value as i128 - self.value as i128) << self.time_scale_shift;
+    Rational64::from_i128s(distance, self.rate as i128)
+      .and_then(|crossing| base.checked_add(crossing))
+      .and_then(|time| if time < now { None } else { Some(time) })
+  }
+}
+
+/**
+
+A value that approaches a target exponentially, halving its distance from the target every half_life time units.
+
+This is the exact solution for diffusion between two cells, cooling towards the ambient temperature, charging a capacitor, and so on, so simulations that use it only need to re-predict when the target actually changes, rather than whenever a linear approximation drifts too far. Decay is just approaching 0.
+
+The distance between the value and the target is stored as a Coordinate, so it must stay within the range of a Coordinate; new(), set_target() and add_value() panic if it wouldn't. That rules out, for instance, approaching i64::MAX from a negative value.
+
+Like the trajectories, it is stored relative to an origin time that the caller keeps track of. value_after() gives a Range that includes the exact value. update_by() has to store an integer, so it rounds the distance from the target towards 0; each update can move the value up to 1 unit closer to the target than the exact function would, but never past it.
+
+*/
+#[derive (Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub struct ExponentialApproach {
+  target: Coordinate,
+  difference: Coordinate,
+  half_life: Coordinate,
+}
+
+impl ExponentialApproach {
+  pub fn new(value: Coordinate, target: Coordinate, half_life: Coordinate) -> ExponentialApproach {
+    assert!(half_life > 0, "half-lives must be positive");
+    ExponentialApproach {
+      target: target,
+      difference: value.checked_sub(target).expect("the distance from the target must fit in a Coordinate"),
+      half_life: half_life,
+    }
+  }
+  pub fn decay(value: Coordinate, half_life: Coordinate) -> ExponentialApproach {
+    ExponentialApproach::new(value, 0, half_life)
+  }
+
+  pub fn evaluate(&self) -> Coordinate {
+    self.target + self.difference
+  }
+  pub fn target(&self) -> Coordinate {
+    self.target
+  }
+  pub fn half_life(&self) -> Coordinate {
+    self.half_life
+  }
+
+  // The number of half-lives after which the exact difference is less than 1, whatever it started at.
+  fn half_lives_to_vanish(&self) -> Coordinate {
+    self.difference.checked_abs().map_or(64, |magnitude| 64 - magnitude.leading_zeros()) as Coordinate
+  }
+
+  // The exact difference from the target, time units after the origin.
+  fn difference_after(&self, time: Coordinate) -> Range {
+    if time / self.half_life >= self.half_lives_to_vanish() {
+      // The decay factor alone is too coarse this far out to bring a large difference below 1.
+      return Range::new(min(self.difference.signum(), 0), max(self.difference.signum(), 0));
+    }
+    ((exponential_decay_factor(time, self.half_life, 60) * self.difference) >> 60)
+      .clamp_to_0_exponent()
+      .expect("the difference can only shrink, so it should still fit")
+  }
+
+  /// The value `time` units after the origin. Exponential approaches can't be evaluated backwards, so this panics if `time` is negative.
+  pub fn value_after(&self, time: Coordinate) -> Range {
+    self.difference_after(time) + Range::exactly(self.target)
+  }
+
+  pub fn updated_by(&self, time: Coordinate) -> ExponentialApproach {
+    let mut result = self.clone();
+    result.update_by(time);
+    result
+  }
+  /// Moves the origin forward by `time`, which must not be negative (this panics if it is).
+  pub fn update_by(&mut self, time: Coordinate) {
+    self.difference = self.difference_after(time).rounded_towards_0();
+  }
+
+  /// Changes the target without changing the current value.
+  pub fn set_target(&mut self, target: Coordinate) {
+    self.difference = self.evaluate().checked_sub(target).expect("the distance from the target must fit in a Coordinate");
+    self.target = target;
+  }
+  pub fn add_value(&mut self, value: Coordinate) {
+    self.difference = self.difference.checked_add(value).expect("the distance from the target must fit in a Coordinate");
+  }
+
+  // Returns the first time at which the value is definitely at or past `value`, or None if it never gets there.
+  // Since the exact function only approaches the target, it never reaches the target itself.
+  // Exponential approaches can't be evaluated backwards, so this also returns None if now is before base.
+  // The result is at most a few time units after the exact crossing, due to rounding error.
+  pub fn approximately_when_reaches<Time: TrajectoryTime<Coordinate>>(&self,
+                                                                      base: Time,
+                                                                      now: Time,
+                                                                      value: Coordinate)
+                                                                      -> Option<Time> {
+    if now < base {
+      return None;
+    }
+    let goal = value - self.target;
+    if goal == 0 || goal.signum() != self.difference.signum() {
+      return None;
+    }
+    let reached = |time: Coordinate| {
+      let difference = self.difference_after(time);
+      if goal > 0 { difference.max() <= goal } else { difference.min() >= goal }
+    };
+    let mut earliest = match now.ticks_since(base) {
+      Some(earliest) => earliest,
+      None => return None,
+    };
+    if reached(earliest) {
+      return base.plus_ticks(earliest).map(|time| max(time, now));
+    }
+    // After half_lives_to_vanish() half-lives, the difference is less than 1, and goal is at least 1.
+    // The extra half-life is a margin for the rounding error of the decay factor.
+    let mut latest = match (self.half_lives_to_vanish() + 1).checked_mul(self.half_life) {
+      Some(latest) => latest,
+      None => Coordinate::max_value(),
+    };
+    if latest <= earliest || !reached(latest) {
+      return None;
+    }
+    while latest - earliest > 1 {
+      let middle = earliest + (latest - earliest) / 2;
+      if reached(middle) {
+        latest = middle;
+      } else {
+        earliest = middle;
+      }
+    }
+    base.plus_ticks(latest)
+  }
+}
+
+#[cfg (test)]
+mod tests {
+  use super::*;
+  use nalgebra::{Vector2, Vector3};
+
+  #[test]
+  fn three_dimensional_escape() {
+    // Moving only along z, with zero acceleration, from the middle of a 2000-wide box.
+    let trajectory = QuadraticTrajectory3::new(10, 1 << 20, [1000, 1000, 1000, 0, 0, 1, 0, 0, 0]);
+    let escape = trajectory.approximately_when_escapes(0, 0, [[0, 2000], [0, 2000], [0, 2000]])
+      .expect("a moving trajectory should eventually leave a bounded box");
+    // It travels one unit every 1 << 10 time units, so it reaches the edge at 1000 << 10;
+    // the bound should only be off by rounding error.
+    assert!(escape >= 999 << 10);
+    assert!(escape <= 1001 << 10);
+  }
+
+  #[test]
+  fn three_dimensional_distance() {
+    let mut first = QuadraticTrajectory3::new(10, 1 << 20, [0; 9]);
+    let second = QuadraticTrajectory3::new(10, 1 << 20, [300, 0, 1200, 0, 0, 0, 0, 0, 0]);
+    first.add_velocity(Vector3::new(0, 0, 1));
+    // The distance starts at 1236 and drops below 500 when first passes z = 800.
+    let time = QuadraticTrajectory3::approximately_when_distance_passes(500, -1, (0, &first), (0, &second))
+      .expect("the trajectories should approach each other");
+    let at = first.updated_by(time).unwrap().evaluate();
+    let displacement = at - second.evaluate();
+    assert!(displacement.dot(&displacement) < 500 * 500);
+    assert!(time >= 799 << 10);
+    assert!(time <= 801 << 10);
+  }
+
+  #[test]
+  fn wide_distance() {
+    // The same as three_dimensional_distance, but scaled up far beyond what i64 coordinates could square.
+    let scale: WideCoordinate = 1 << 50;
+    let mut first = WideQuadraticTrajectory3::new(10, 1 << 62, [0; 9]);
+    let second = WideQuadraticTrajectory3::new(10, 1 << 62, [300 * scale, 0, 1200 * scale, 0, 0, 0, 0, 0, 0]);
+    first.add_velocity(Vector3::new(0, 0, scale));
+    let time = WideQuadraticTrajectory3::approximately_when_distance_passes(500 * scale, -1, (0, &first), (0, &second))
+      .expect("the trajectories should approach each other");
+    assert!(time >= 799 << 10);
+    assert!(time <= 801 << 10);
+  }
+
+  #[test]
+  fn serialization() {
+    use bincode;
+    use serde_json;
+    let wide = WideQuadraticTrajectory3::new(10, 1 << 62, [-3 << 100, 1, -1, 5 << 70, 0, 0, 0, 7, -(1 << 90)]);
+    let bytes = bincode::serialize(&wide, bincode::Infinite).unwrap();
+    assert_eq!(bincode::deserialize::<WideQuadraticTrajectory3>(&bytes).unwrap(), wide);
+    let wide_linear = WideLinearTrajectory::new(4, -(1 << 100) - 1, 1 << 64);
+    let bytes = bincode::serialize(&wide_linear, bincode::Infinite).unwrap();
+    assert_eq!(bincode::deserialize::<WideLinearTrajectory>(&bytes).unwrap(), wide_linear);
+
+    // the i64 trajectories are serialized just like a derived implementation would do it
+    let narrow = QuadraticTrajectory::new(10, 1 << 20, [1, 2, 3, 4, 5, 6]);
+    assert_eq!(serde_json::to_string(&narrow).unwrap(),
+               r#"{"data":[[1,3,5],[2,4,6]],"time_scale_shift":10,"max_distance_traveled_at_once":1048576}"#);
+    let bytes = bincode::serialize(&narrow, bincode::Infinite).unwrap();
+    assert_eq!(bincode::deserialize::<QuadraticTrajectory>(&bytes).unwrap(), narrow);
+  }
+
+  #[test]
+  fn cubic_distance() {
+    // Starting at rest with constant jerk along z, the position is z = t^3 (in units of 1 << 10 time),
+    // so the distance to (300, 0, 1000) drops below 500 when z passes 600, at t = 8.43.
+    let mut first = CubicTrajectory3::new(10, 1 << 20, [0; 12]);
+    let second = CubicTrajectory3::new(10, 1 << 20, [300, 0, 1000, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    first.add_jerk(Vector3::new(0, 0, 1));
+    let time = CubicTrajectory3::approximately_when_distance_passes(500, -1, (0, &first), (0, &second))
+      .expect("the trajectories should approach each other");
+    let at = first.updated_by(time).unwrap().evaluate();
+    let displacement = at - second.evaluate();
+    assert!(displacement.dot(&displacement) < 500 * 500);
+    assert!(time >= 8 << 10);
+    assert!(time <= 9 << 10);
+  }
+
+  #[test]
+  fn cubic_escape() {
+    let mut trajectory = CubicTrajectory::new(10, 1 << 20, [0; 8]);
+    trajectory.add_jerk(Vector2::new(1, 0));
+    // x = t^3 leaves [-1000, 1000] at t = 10.
+    let escape = trajectory.approximately_when_escapes(0, 0, [[-1000, 1000], [-1000, 1000]])
+      .expect("an accelerating trajectory should eventually leave a bounded box");
+    assert!(escape >= 9 << 10);
+    assert!(escape <= 11 << 10);
+  }
+
+  #[test]
+  fn fallible_updates() {
+    // Moving 2^42 units per time unit for 2^22 time units goes far beyond what an i64 can hold.
+    let mut fast = QuadraticTrajectory::new(10, 1 << 20, [0, 0, (1 << 52) + 1, 0, 0, 0]);
+    let original = fast.clone();
+    assert_eq!(fast.try_update_by(1 << 22), Err(TrajectoryError::Overflow));
+    assert_eq!(fast, original);
+    assert_eq!(fast.updated_by(1 << 22), None);
+
+    let resting = QuadraticTrajectory::new(10, 1 << 20, [0; 6]);
+    assert_eq!(QuadraticTrajectory::try_approximately_when_distance_passes(100, 1, (0, &fast), (1 << 22, &resting)),
+               Err(TrajectoryError::Overflow));
+    assert_eq!(QuadraticTrajectory::try_approximately_when_distance_passes(100, 1, (0, &resting), (0, &resting)),
+               Ok(None));
+  }
+
+  #[test]
+  fn rebasing() {
+    // One unit per time unit, so each step can be at most 1 << 20 time units.
+    let mut trajectory = QuadraticTrajectory::new(10, 1 << 20, [0, 0, 1 << 10, 0, 0, 0]);
+    assert_eq!(quadratic_max_safe_move(&trajectory.data[0][..], 10, 1 << 20, 1 << 30), 1 << 20);
+    trajectory.rebase_by(5 << 20).unwrap();
+    assert_eq!(trajectory.evaluate(), Vector2::new(5 << 20, 0));
+
+    // This one travels further than its max distance in a single time unit, so there is no safe step.
+    let mut too_fast = QuadraticTrajectory::new(10, 1 << 20, [0, 0, (1 << 30) + (1 << 10), 0, 0, 0]);
+    assert_eq!(too_fast.rebase_by(1), Err(TrajectoryError::TraveledTooFar));
+    assert_eq!(too_fast.evaluate(), Vector2::new(0, 0));
+    assert_eq!(trajectory.rebase_by(-1), Err(TrajectoryError::BackwardsInTime));
+
+    // The cubic term alone: x = t^3 >> 30, so after 1 << 10 time units it has traveled 1 unit.
+    let mut jerky = CubicTrajectory::new(10, 1 << 20, [0, 0, 0, 0, 0, 0, 1, 0]);
+    assert_eq!(polynomial_max_safe_move(&jerky.data[0][..], 10, 1, 1 << 30), 1 << 10);
+    jerky.rebase_by(1 << 20).unwrap();
+    // Each step rounds the lower terms, so allow some error.
+    assert!((jerky.evaluate()[0] - (1 << 30)).abs() <= 1 << 20);
+  }
+
+  #[test]
+  fn linear_crossing() {
+    // 3 units per 1 << 10 time units, starting at 100 at time 50.
+    let mut trajectory = LinearTrajectory::new(10, 100, 3);
+    assert_eq!(trajectory.approximately_when_crosses(50, 50, 400), Some(50 + (100 << 10)));
+    assert_eq!(trajectory.approximately_when_crosses(50, 50, 0), None);
+    assert_eq!(trajectory.approximately_when_crosses(50, 50, 100), Some(50));
+    let crossing = trajectory.approximately_when_crosses(50, 50, 401).unwrap();
+    assert_eq!(trajectory.updated_by(crossing - 50).unwrap().evaluate(), 401);
+    assert_eq!(trajectory.updated_by(crossing - 51).unwrap().evaluate(), 400);
+
+    trajectory.update_by(10 << 10);
+    assert_eq!(trajectory.evaluate(), 130);
+    trajectory.add_rate(-4).unwrap();
+    assert_eq!(trajectory.approximately_when_crosses(0, 0, 0), Some(130 << 10));
+    assert_eq!(trajectory.approximately_when_crosses(0, 1 << 20, 0), None);
+    assert_eq!(LinearTrajectory::new(0, i64::max_value() - 5, 10).updated_by(1), None);
+  }
+
+  #[test]
+  fn linear_overflow() {
+    let mut trajectory = LinearTrajectory::new(0, i64::max_value() - 5, 10);
+    assert_eq!(trajectory.try_update_by(1), Err(TrajectoryError::Overflow));
+    assert_eq!(trajectory.add_value(6), Err(TrajectoryError::Overflow));
+    assert_eq!(trajectory.add_rate(i64::max_value()), Err(TrajectoryError::Overflow));
+    // failed operations leave the trajectory as it was
+    assert_eq!(trajectory, LinearTrajectory::new(0, i64::max_value() - 5, 10));
+    assert_eq!(trajectory.add_value(5), Ok(()));
+    assert_eq!(trajectory.evaluate(), i64::max_value());
+    trajectory.add_rate(-10).unwrap();
+    assert_eq!(trajectory.try_update_by(1), Ok(()));
+    assert_eq!(trajectory.evaluate(), i64::max_value());
+  }
+
+  #[test]
+  fn linear_exact_crossing() {
+    let trajectory = LinearTrajectory::new(10, 100, 3);
+    let base = Rational64::new(1, 2);
+    assert_eq!(trajectory.exactly_when_crosses(base, base, 401),
+               Some(base + Rational64::new(301 << 10, 3)));
+    assert_eq!(trajectory.exactly_when_crosses(base, base, 0), None);
+    let crossing = trajectory.exactly_when_crosses(base, base, 401).unwrap();
+    assert_eq!(trajectory.approximately_when_crosses(0, 0, 401), Some((crossing - base).ceil()));
+  }
+
+  #[test]
+  fn distance_passes_after_overflow() {
+    // Both move along y so fast that they overflow long before the gap along x closes to 500.
+    let first = QuadraticTrajectory::new(1, 1 << 20, [0, 0, 0, 1 << 55, 0, 0]);
+    let second = QuadraticTrajectory::new(1, 1 << 20, [1000, 0, -1, 1 << 55, 0, 0]);
+    assert_eq!(QuadraticTrajectory::try_approximately_when_distance_passes(500, -1, (0i64, &first), (0, &second)),
+               Err(TrajectoryError::Overflow));
+    // when they aren't moving along y, the gap closes normally, after x has moved 500 units at 2 ticks per unit
+    let first = QuadraticTrajectory::new(1, 1 << 20, [0, 0, 0, 0, 0, 0]);
+    let second = QuadraticTrajectory::new(1, 1 << 20, [1000, 0, -1, 0, 0, 0]);
+    let time = QuadraticTrajectory::try_approximately_when_distance_passes(500, -1, (0i64, &first), (0, &second))
+      .unwrap()
+      .expect("the gap should close");
+    assert!(time >= 1000);
+    assert!(time <= 1010);
+  }
+
+  #[test]
+  fn rational_times() {
+    // With a Rational64 base, the solvers give the same number of ticks after the base as they do for integers.
+    let half = Rational64::new(1, 2);
+    let after = |ticks: Coordinate| Some(half + Rational64::from_integer(ticks));
+
+    let linear = LinearTrajectory::new(10, 100, 3);
+    assert_eq!(linear.approximately_when_crosses(half, half, 401),
+               after(linear.approximately_when_crosses(0, 0, 401).unwrap()));
+
+    let escaping = QuadraticTrajectory3::new(10, 1 << 20, [1000, 1000, 1000, 0, 0, 1, 0, 0, 0]);
+    let bounds = [[0, 2000], [0, 2000], [0, 2000]];
+    assert_eq!(escaping.approximately_when_escapes(half, half, bounds),
+               after(escaping.approximately_when_escapes(0, 0, bounds).unwrap()));
+
+    let approaching = QuadraticTrajectory::new(10, 1 << 20, [0, 0, 1, 0, 0, 0]);
+    let waiting = QuadraticTrajectory::new(10, 1 << 20, [1000, 0, 0, 0, 0, 0]);
+    let ticks = QuadraticTrajectory::approximately_when_distance_passes(100, -1, (0, &approaching), (1, &waiting))
+      .expect("they should come within 100 of each other");
+    assert_eq!(QuadraticTrajectory::approximately_when_distance_passes(100,
+                                                                       -1,
+                                                                       (half, &approaching),
+                                                                       (half + Rational64::from_integer(1), &waiting)),
+               after(ticks));
+    assert_eq!(QuadraticTrajectory::try_approximately_when_distance_passes(100,
+                                                                           -1,
+                                                                           (half, &approaching),
+                                                                           (Rational64::from_integer(1), &waiting)),
+               Err(TrajectoryError::FractionalTicks));
+
+    let cooling = ExponentialApproach::new(1000, 200, 1 << 10);
+    assert_eq!(cooling.approximately_when_reaches(half, half, 300),
+               after(cooling.approximately_when_reaches(0, 0, 300).unwrap()));
+  }
+
+  #[test]
+  fn exponential_half_lives() {
+    let cooling = ExponentialApproach::new(1000, 200, 1 << 10);
+    assert!(cooling.value_after(1 << 10).includes(&Range::exactly(600)));
+    assert!(cooling.value_after(2 << 10).includes(&Range::exactly(400)));
+    let updated = cooling.updated_by(1 << 10);
+    assert_eq!(updated.evaluate(), 600);
+    assert_eq!(updated.target(), 200);
+  }
+
+  #[test]
+  fn exponential_reaches() {
+    // The difference of 800 drops to 100 after exactly 3 half-lives.
+    let cooling = ExponentialApproach::new(1000, 200, 1 << 10);
+    let time = cooling.approximately_when_reaches(0, 0, 300).expect("it should cool past 300");
+    assert!(time >= 3 << 10);
+    assert!(time <= (3 << 10) + 2);
+    assert!(cooling.value_after(time).max() <= 300);
+    assert_eq!(cooling.approximately_when_reaches(0, 0, 200), None);
+    assert_eq!(cooling.approximately_when_reaches(0, 0, 1200), None);
+    assert_eq!(cooling.approximately_when_reaches(0, 5, 1000), Some(5));
+    assert_eq!(cooling.approximately_when_reaches(5, 0, 300), None);
+
+    let mut charging = ExponentialApproach::decay(0, 1 << 10);
+    charging.set_target(1000);
+    assert_eq!(charging.evaluate(), 0);
+    let time = charging.approximately_when_reaches(100, 100, 750).expect("it should charge past 750");
+    assert!(time >= 100 + (2 << 10));
+    assert!(time <= 100 + (2 << 10) + 2);
+
+    // Differences this big take more half-lives to vanish than the decay factor resolves.
+    let huge = ExponentialApproach::decay(1 << 62, 1 << 10);
+    let time = huge.approximately_when_reaches(0, 0, 1).expect("it should decay to 1");
+    assert!(time >= 62 << 10);
+    assert!(time <= 64 << 10);
+    assert!(huge.value_after(time).max() <= 1);
+  }
+
+  #[test]
+  fn exponential_limits() {
+    let mut extreme = ExponentialApproach::new(Coordinate::max_value(), 0, 1 << 10);
+    extreme.set_target(1);
+    assert_eq!(extreme.evaluate(), Coordinate::max_value());
+    extreme.add_value(-1);
+    assert_eq!(extreme.evaluate(), Coordinate::max_value() - 1);
+  }
+
+  #[test]
+  #[should_panic (expected = "the distance from the target must fit in a Coordinate")]
+  fn exponential_too_far_from_target() {
+    ExponentialApproach::new(Coordinate::max_value(), -1, 1 << 10);
+  }
+
+  #[test]
+  #[should_panic (expected = "the distance from the target must fit in a Coordinate")]
+  fn exponential_target_too_far() {
+    let mut extreme = ExponentialApproach::decay(Coordinate::max_value(), 1 << 10);
+    extreme.set_target(-1);
   }
 }
@@ -0,0 +1,277 @@
+// A ready-made connection layer for the networked stewards.
+//
+// The networked stewards (simply_synchronized and authoritative) accept any Read/Write pair,
+// and write one bincode message at a time, flushing after each one.
+// FramedWriter turns each flush into one length-prefixed frame (optionally compressed),
+// and FramedReader turns the frames back into a plain byte stream, so neither steward
+// needs to know about the framing.
+//
+// Before any frames are exchanged, handshake() checks that both sides are speaking
+// the same protocol with the same Basics. Otherwise, a client built from a different
+// version of the game would fail in confusing ways (or panic on an unknown EventId)
+// the first time it received a message.
+
+use std::io::{self, Read, Write, BufReader, BufWriter};
+use std::error::Error;
+use std::fmt;
+use std::net::TcpStream;
+use {Basics, ColumnId, EventId};
+use implementation_support::list_of_types::{column_ids, event_ids};
+use bincode;
+
+/// Changes whenever the format of frames or of the handshake changes.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Clone, Debug)]
+pub struct Options {
+  /// Whether to compress frames. Compression is only used if both sides ask for it,
+  /// and is never used if this crate was built without the "compression" feature.
+  pub compression: bool,
+  /// Frames longer than this are treated as a corrupted connection, rather than allocating a huge buffer.
+  pub maximum_frame_length: u32,
+}
+
+impl Default for Options {
+  fn default() -> Options {
+    Options {
+      compression: false,
+      maximum_frame_length: 1 << 26,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub enum HandshakeError {
+  Io(io::Error),
+  Malformed(bincode::internal::Error),
+  ProtocolMismatch { local: u32, remote: u32 },
+  /// The two sides were built with different Basics::IncludedTypes.
+  DifferentTypes {
+    only_local_columns: Vec<ColumnId>,
+    only_remote_columns: Vec<ColumnId>,
+    only_local_events: Vec<EventId>,
+    only_remote_events: Vec<EventId>,
+  },
+}
+
+impl fmt::Display for HandshakeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      HandshakeError::Io(ref error) => write!(f, "connection failed during handshake: {}", error),
+      HandshakeError::Malformed(ref error) => write!(f, "malformed handshake: {}", error),
+      HandshakeError::ProtocolMismatch { local, remote } => write!(f, "protocol version mismatch: local {}, remote {}", local, remote),
+      HandshakeError::DifferentTypes { ref only_local_columns, ref only_remote_columns, ref only_local_events, ref only_remote_events } => {
+        write!(f, "the two sides were built with different types: columns only here {:?}, columns only there {:?}, events only here {:?}, events only there {:?}",
+               only_local_columns, only_remote_columns, only_local_events, only_remote_events)
+      }
+    }
+  }
+}
+
+impl Error for HandshakeError {
+  fn description(&self) -> &str {
+    match *self {
+      HandshakeError::Io(_) => "connection failed during handshake",
+      HandshakeError::Malformed(_) => "malformed handshake",
+      HandshakeError::ProtocolMismatch { .. } => "protocol version mismatch",
+      HandshakeError::DifferentTypes { .. } => "the two sides were built with different types",
+    }
+  }
+  fn cause(&self) -> Option<&Error> {
+    match *self {
+      HandshakeError::Io(ref error) => Some(error),
+      HandshakeError::Malformed(ref error) => Some(&**error),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for HandshakeError {
+  fn from(error: io::Error) -> HandshakeError {
+    HandshakeError::Io(error)
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Hello {
+  protocol: u32,
+  column_ids: Vec<ColumnId>,
+  event_ids: Vec<EventId>,
+  compression: bool,
+}
+
+#[cfg(feature = "compression")]
+fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+  use flate2::Compression;
+  use flate2::write::DeflateEncoder;
+  let mut encoder = DeflateEncoder::new(Vec::new(), Compression::Default);
+  try!(encoder.write_all(data));
+  encoder.finish()
+}
+
+#[cfg(feature = "compression")]
+fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+  use flate2::read::DeflateDecoder;
+  let mut result = Vec::new();
+  try!(DeflateDecoder::new(data).read_to_end(&mut result));
+  Ok(result)
+}
+
+#[cfg(not(feature = "compression"))]
+fn compress(_: &[u8]) -> io::Result<Vec<u8>> {
+  unreachable!()
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_: &[u8]) -> io::Result<Vec<u8>> {
+  unreachable!()
+}
+
+/// Buffers everything written to it, and sends it as a single frame whenever it is flushed.
+pub struct FramedWriter<W: Write> {
+  inner: W,
+  buffer: Vec<u8>,
+  compression: bool,
+}
+
+impl<W: Write> FramedWriter<W> {
+  pub fn new(inner: W) -> FramedWriter<W> {
+    FramedWriter {
+      inner: inner,
+      buffer: Vec::new(),
+      compression: false,
+    }
+  }
+  pub fn get_ref(&self) -> &W {
+    &self.inner
+  }
+}
+
+impl<W: Write> Write for FramedWriter<W> {
+  fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+    self.buffer.extend_from_slice(data);
+    Ok(data.len())
+  }
+  fn flush(&mut self) -> io::Result<()> {
+    if !self.buffer.is_empty() {
+      let frame = if self.compression {
+        try!(compress(&self.buffer))
+      } else {
+        ::std::mem::replace(&mut self.buffer, Vec::new())
+      };
+      self.buffer.clear();
+      let length = frame.len() as u32;
+      try!(self.inner.write_all(&[(length >> 24) as u8, (length >> 16) as u8, (length >> 8) as u8, length as u8]));
+      try!(self.inner.write_all(&frame));
+    }
+    self.inner.flush()
+  }
+}
+
+/// Reads the frames sent by a FramedWriter, and provides their contents as a continuous stream.
+///
+/// The end of the underlying stream is treated as the end of the data, even if it occurs in the middle of a frame.
+pub struct FramedReader<R: Read> {
+  inner: R,
+  frame: Vec<u8>,
+  position: usize,
+  compression: bool,
+  maximum_frame_length: u32,
+}
+
+impl<R: Read> FramedReader<R> {
+  pub fn new(inner: R, maximum_frame_length: u32) -> FramedReader<R> {
+    FramedReader {
+      inner: inner,
+      frame: Vec::new(),
+      position: 0,
+      compression: false,
+      maximum_frame_length: maximum_frame_length,
+    }
+  }
+  pub fn get_ref(&self) -> &R {
+    &self.inner
+  }
+
+  fn next_frame(&mut self) -> io::Result<bool> {
+    let mut header = [0u8; 4];
+    match self.inner.read_exact(&mut header) {
+      Ok(()) => (),
+      Err(ref error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+      Err(error) => return Err(error),
+    }
+    let length = header.iter().fold(0u32, |length, &byte| (length << 8) | byte as u32);
+    if length > self.maximum_frame_length {
+      return Err(io::Error::new(io::ErrorKind::InvalidData, format!("frame of {} bytes is longer than the maximum of {}", length, self.maximum_frame_length)));
+    }
+    let mut frame = vec![0u8; length as usize];
+    try!(self.inner.read_exact(&mut frame));
+    self.frame = if self.compression { try!(decompress(&frame)) } else { frame };
+    self.position = 0;
+    Ok(true)
+  }
+}
+
+impl<R: Read> Read for FramedReader<R> {
+  fn read(&mut self, destination: &mut [u8]) -> io::Result<usize> {
+    while self.position == self.frame.len() {
+      if destination.is_empty() || !try!(self.next_frame()) {
+        return Ok(0);
+      }
+    }
+    let amount = ::std::cmp::min(destination.len(), self.frame.len() - self.position);
+    destination[..amount].copy_from_slice(&self.frame[self.position..self.position + amount]);
+    self.position += amount;
+    Ok(amount)
+  }
+}
+
+fn only_in<T: Clone + PartialEq>(these: &[T], those: &[T]) -> Vec<T> {
+  these.iter().filter(|id| !those.contains(id)).cloned().collect()
+}
+
+/// Checks that the other side uses the same protocol and Basics, and wraps the connection in frames.
+///
+/// Both sides must call this (with the same Basics) before passing the results to a networked steward.
+pub fn handshake<B: Basics, R: Read, W: Write>(reader: R, writer: W, options: &Options) -> Result<(FramedReader<R>, FramedWriter<W>), HandshakeError> {
+  let mut reader = FramedReader::new(reader, options.maximum_frame_length);
+  let mut writer = FramedWriter::new(writer);
+  let mut local = Hello {
+    protocol: PROTOCOL_VERSION,
+    column_ids: column_ids::<B>(),
+    event_ids: event_ids::<B>(),
+    compression: options.compression && cfg!(feature = "compression"),
+  };
+  local.column_ids.sort();
+  local.event_ids.sort();
+  try!(bincode::serialize_into(&mut writer, &local, bincode::Infinite).map_err(HandshakeError::Malformed));
+  try!(writer.flush());
+  let remote: Hello = try!(bincode::deserialize_from(&mut reader, bincode::Infinite).map_err(HandshakeError::Malformed));
+  if remote.protocol != local.protocol {
+    return Err(HandshakeError::ProtocolMismatch {
+      local: local.protocol,
+      remote: remote.protocol,
+    });
+  }
+  if remote.column_ids != local.column_ids || remote.event_ids != local.event_ids {
+    return Err(HandshakeError::DifferentTypes {
+      only_local_columns: only_in(&local.column_ids, &remote.column_ids),
+      only_remote_columns: only_in(&remote.column_ids, &local.column_ids),
+      only_local_events: only_in(&local.event_ids, &remote.event_ids),
+      only_remote_events: only_in(&remote.event_ids, &local.event_ids),
+    });
+  }
+  let compression = local.compression && remote.compression;
+  reader.compression = compression;
+  writer.compression = compression;
+  Ok((reader, writer))
+}
+
+/// Performs the handshake over a TCP connection, returning a buffered reader and writer
+/// that can be passed directly to simply_synchronized or authoritative.
+pub fn tcp<B: Basics>(stream: TcpStream, options: &Options) -> Result<(FramedReader<BufReader<TcpStream>>, FramedWriter<BufWriter<TcpStream>>), HandshakeError> {
+  // every message is sent as soon as it is flushed, so Nagle's algorithm would only add latency
+  try!(stream.set_nodelay(true));
+  let reader = BufReader::new(try!(stream.try_clone()));
+  handshake::<B, _, _>(reader, BufWriter::new(stream), options)
+}
@@ -0,0 +1,151 @@
+//! Randomized stress tests for TimeSteward simulations.
+//!
+//! This is the same workload that the TimeSteward's own tests use ("putting it through its paces"):
+//! fiat events are inserted and removed at random times (often in the past),
+//! snapshots are taken, and the steward is occasionally reloaded from an old snapshot.
+//! Running it against a crossverified::Many checks that several stewards agree
+//! on your simulation, which catches nondeterminism in your events and predictors
+//! as well as bugs in the stewards.
+//!
+//! Workloads implement quickcheck::Arbitrary, so when a workload fails,
+//! quickcheck shrinks it to a much shorter one that still fails.
+
+use std::fmt::Debug;
+use quickcheck::{Arbitrary, Gen, QuickCheck, StdGen};
+use rand::{self, Rng};
+use {Basics, TimeSteward, IncrementalTimeSteward, TimeStewardFromConstants, TimeStewardFromSnapshot,
+     DeterministicRandomId, FiatEventOperationError, SnapshotEntry};
+
+/// Describes how to make random fiat events for a particular simulation.
+///
+/// Values of this type are the fiat events themselves (or a description of them);
+/// it's often simplest to make it an enum with one variant for each type of fiat event.
+pub trait FiatEvents: Clone + Debug + Send + 'static {
+  type Basics: Basics;
+  fn constants <G: Gen> (generator: &mut G)-><Self::Basics as Basics>::Constants;
+  /// Generates the time of a fiat event. Times should be spread out enough
+  /// that many events are inserted before events that have already been computed.
+  fn time <G: Gen> (generator: &mut G)-><Self::Basics as Basics>::Time;
+  fn generate <G: Gen> (generator: &mut G)->Self;
+  fn insert <Steward: TimeSteward <Basics = Self::Basics>> (&self, steward: &mut Steward, time: <Self::Basics as Basics>::Time, id: DeterministicRandomId)->Result <(), FiatEventOperationError>;
+  /// Returns simpler versions of this event, for shrinking failing workloads.
+  fn shrink (&self)->Box <Iterator <Item = Self>> {
+    Box::new (::std::iter::empty())
+  }
+}
+
+#[derive (Clone, Debug)]
+pub enum Operation <F: FiatEvents> {
+  InsertFiatEvent (<F::Basics as Basics>::Time, DeterministicRandomId, F),
+  /// Removes one of the fiat events inserted so far (chosen by the index, modulo the number of them).
+  /// If `forget` is false, the same event may be removed again later, which should fail harmlessly.
+  RemoveFiatEvent {which: usize, forget: bool},
+  /// Takes a snapshot at the time the steward has computed up to, and keeps it for later reloading.
+  Snapshot,
+  /// Replaces the steward with one constructed from one of the snapshots taken so far.
+  Reload (usize),
+  Step,
+}
+
+#[derive (Clone, Debug)]
+pub struct Workload <F: FiatEvents> {
+  pub constants: <F::Basics as Basics>::Constants,
+  pub operations: Vec <Operation <F>>,
+}
+
+impl <F: FiatEvents> Arbitrary for Operation <F> {
+  fn arbitrary <G: Gen> (generator: &mut G)->Self {
+    match generator.gen_range::<u32> (0, 16) {
+      0 => Operation::InsertFiatEvent (F::time (generator), DeterministicRandomId::new (& generator.gen::<u64>()), F::generate (generator)),
+      1 => Operation::Snapshot,
+      3 => Operation::Reload (generator.gen()),
+      5 => Operation::RemoveFiatEvent {which: generator.gen(), forget: generator.gen()},
+      _ => Operation::Step,
+    }
+  }
+  fn shrink (&self)->Box <Iterator <Item = Self>> {
+    match *self {
+      Operation::InsertFiatEvent (ref time, id, ref event) => {
+        let time = time.clone();
+        Box::new (event.shrink().map (move | event | Operation::InsertFiatEvent (time.clone(), id, event)))
+      },
+      _ => Box::new (::std::iter::empty()),
+    }
+  }
+}
+
+impl <F: FiatEvents> Arbitrary for Workload <F> {
+  fn arbitrary <G: Gen> (generator: &mut G)->Self {
+    let length = generator.size();
+    Workload {
+      constants: F::constants (generator),
+      operations: (0..length).map (| _ | Operation::arbitrary (generator)).collect(),
+    }
+  }
+  fn shrink (&self)->Box <Iterator <Item = Self>> {
+    let constants = self.constants.clone();
+    Box::new (self.operations.shrink().map (move | operations | Workload {constants: constants.clone(), operations: operations}))
+  }
+}
+
+/// Runs a workload against a steward. Failures are reported by panicking,
+/// which is what crossverified stewards do when they disagree.
+///
+/// Every snapshot taken is also iterated in full, so that crossverified stewards compare every field.
+#[allow (unused_must_use)]
+pub fn run <Steward, F> (workload: &Workload <F>)
+where Steward: IncrementalTimeSteward + TimeStewardFromConstants + TimeStewardFromSnapshot <Basics = F::Basics>,
+      F: FiatEvents,
+      for <'a> &'a Steward::Snapshot: IntoIterator <Item = SnapshotEntry <'a, F::Basics>>
+{
+  let mut steward: Steward = Steward::from_constants (workload.constants.clone());
+  let mut snapshots: Vec <Steward::Snapshot> = Vec::new();
+  let mut fiat_events: Vec <(<F::Basics as Basics>::Time, DeterministicRandomId)> = Vec::new();
+  for operation in workload.operations.iter() {
+    match *operation {
+      Operation::InsertFiatEvent (ref time, id, ref event) => {
+        event.insert (&mut steward, time.clone(), id);
+        fiat_events.push ((time.clone(), id));
+      },
+      Operation::RemoveFiatEvent {which, forget} => if !fiat_events.is_empty() {
+        let which = which % fiat_events.len();
+        let (time, id) = fiat_events [which].clone();
+        steward.remove_fiat_event (&time, id);
+        if forget {fiat_events.remove (which);}
+      },
+      Operation::Snapshot => if let Some (limit) = steward.updated_until_before() {
+        if let Some (snapshot) = steward.snapshot_before (&limit) {
+          (&snapshot).into_iter().count();
+          snapshots.push (snapshot);
+        }
+      },
+      Operation::Reload (which) => if !snapshots.is_empty() {
+        steward = Steward::from_snapshot::<Steward::Snapshot> (&snapshots [which % snapshots.len()]);
+      },
+      Operation::Step => steward.step(),
+    }
+  }
+}
+
+/// Runs `tests` random workloads of `length` operations each against Steward
+/// (usually a crossverified::Many of the stewards you want to compare).
+///
+/// If one fails, this panics with the smallest failing workload that quickcheck could find.
+pub fn stress_test <Steward, F> (tests: usize, length: usize)
+where Steward: IncrementalTimeSteward + TimeStewardFromConstants + TimeStewardFromSnapshot <Basics = F::Basics>,
+      F: FiatEvents,
+      for <'a> &'a Steward::Snapshot: IntoIterator <Item = SnapshotEntry <'a, F::Basics>>
+{
+  fn property <Steward, F> (workload: Workload <F>)->bool
+  where Steward: IncrementalTimeSteward + TimeStewardFromConstants + TimeStewardFromSnapshot <Basics = F::Basics>,
+        F: FiatEvents,
+        for <'a> &'a Steward::Snapshot: IntoIterator <Item = SnapshotEntry <'a, F::Basics>>
+  {
+    run::<Steward, F> (&workload);
+    true
+  }
+  QuickCheck::new()
+    .tests (tests)
+    .gen (StdGen::new (rand::thread_rng(), length))
+    .quickcheck (property::<Steward, F> as fn (Workload <F>)->bool);
+}
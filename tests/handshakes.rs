@@ -46,6 +46,8 @@ type TimeStewardTypes = (ColumnType<Philosopher>,
                          EventType<Initialize>,
                          EventType<Tweak>,
                          EventType<TweakUnsafe>,
+                         EventType<TweakDivergent>,
+                         EventType<TweakNondeterministic>,
                          EventType<Shake>,
                          PredictorType<Shaker>);
 
@@ -140,6 +142,36 @@ time_steward_event! (
   }
 );
 
+// Unlike TweakUnsafe, which differs on every thread, this only differs on threads that set DIVERGES.
+thread_local! {static DIVERGES: ::std::cell::Cell<bool> = ::std::cell::Cell::new(false);}
+
+time_steward_event! (
+  struct TweakDivergent {}, Basics, EventId (0x5be8a3ad7b0c4f12),
+  | &self, m | {
+    let now = *m.now();
+    let awaken_time = now + if DIVERGES.with (| value | value.get()) {5} else {2};
+    m.set::<Philosopher>(get_philosopher_id(0),
+                             Some(Philosopher {
+                               time_when_next_initiates_handshake: awaken_time,
+                             }));
+  }
+);
+
+// This differs every time it runs, so it even differs between stewards in the same thread.
+thread_local! {static EXECUTIONS: ::std::cell::Cell<i64> = ::std::cell::Cell::new(0);}
+
+time_steward_event! (
+  struct TweakNondeterministic {}, Basics, EventId (0x7c1d9e04f2a6b358),
+  | &self, m | {
+    let now = *m.now();
+    let executions = EXECUTIONS.with (| value | {value.set (value.get() + 1); value.get()});
+    m.set::<Philosopher>(get_philosopher_id(0),
+                             Some(Philosopher {
+                               time_when_next_initiates_handshake: now + 1 + executions,
+                             }));
+  }
+);
+
 #[test]
 pub fn handshakes_simple() {
   type Steward = crossverified::Steward<Basics, inefficient_flat::Steward<Basics>, memoized_flat::Steward<Basics>>;
@@ -189,6 +221,57 @@ pub fn handshakes_reloading() {
   // panic!("anyway")
 }
 
+#[test]
+pub fn handshakes_json_reloading() {
+  type Steward = crossverified::Steward<Basics, amortized::Steward<Basics>, memoized_flat::Steward<Basics>>;
+  let mut stew: Steward = Steward::from_constants(());
+
+  stew.insert_fiat_event(0,
+                       DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                       Initialize::new())
+    .unwrap();
+
+  for increment in 1..11 {
+    let snapshot = stew.snapshot_before(&(increment * 100i64)).unwrap();
+    let mut writer: Vec<u8> = Vec::with_capacity(128);
+    time_steward::export_snapshot_json:: <Basics, <Steward as TimeSteward>::Snapshot,_> (&snapshot, &mut writer).unwrap();
+    println!("{}", String::from_utf8(writer.clone()).unwrap());
+    use std::io::Cursor;
+    let imported = time_steward::import_snapshot_json:: <Basics, _> (&mut Cursor::new(writer.clone())).unwrap();
+    display_snapshot(&imported);
+
+    // exporting again must reproduce the same document exactly
+    let mut rewriter: Vec<u8> = Vec::with_capacity(128);
+    time_steward::export_snapshot_json:: <Basics, time_steward::FiatSnapshot<Basics>,_> (&imported, &mut rewriter).unwrap();
+    assert_eq!(writer, rewriter);
+
+    use time_steward::MomentaryAccessor;
+    stew = Steward::from_snapshot::<time_steward::FiatSnapshot<Basics>>(&imported);
+    display_snapshot(&stew.snapshot_before(imported.now()).unwrap());
+  }
+}
+
+#[test]
+pub fn handshakes_json_unknown_column() {
+  type Steward = amortized::Steward<Basics>;
+  let mut stew: Steward = Steward::from_constants(());
+  stew.insert_fiat_event(0,
+                       DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                       Initialize::new())
+    .unwrap();
+  let snapshot = stew.snapshot_before(&100i64).unwrap();
+  let mut writer: Vec<u8> = Vec::with_capacity(128);
+  time_steward::export_snapshot_json:: <Basics, <Steward as TimeSteward>::Snapshot,_> (&snapshot, &mut writer).unwrap();
+
+  // a hand-edited document with a mistyped column id
+  let known = format!("\"column_id\": {}", Philosopher::column_id().0);
+  let document = String::from_utf8(writer).unwrap();
+  assert!(document.contains(&known));
+  let mistyped = document.replace(&known, &format!("\"column_id\": {}", Philosopher::column_id().0 ^ 1));
+  use std::io::Cursor;
+  assert!(time_steward::import_snapshot_json:: <Basics, _> (&mut Cursor::new(mistyped.into_bytes())).is_err());
+}
+
 #[test]
 fn handshakes_retroactive() {
   type Steward = crossverified::Steward<Basics, amortized::Steward<Basics>, flat_to_inefficient_full::Steward<Basics, memoized_flat::Steward <Basics> >>;
@@ -208,6 +291,121 @@ fn handshakes_retroactive() {
 
 }
 
+#[test]
+fn handshakes_recording() {
+  use time_steward::stewards::recorded;
+  let mut stew: recorded::Steward<Basics, amortized::Steward<Basics>, Vec<u8>> =
+    recorded::Steward::new((), 0, 4, Vec::new());
+
+  stew.insert_fiat_event(0,
+                       DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                       Initialize::new())
+    .unwrap();
+
+  for increment in 1..21 {
+    let time = increment * 100i64;
+    if increment % 3 == 0 {
+      stew.insert_fiat_event(time, DeterministicRandomId::new(&increment), Tweak::new()).unwrap();
+    }
+    if increment == 7 || increment == 16 {
+      stew.remove_fiat_event(&(time - 100), DeterministicRandomId::new(&(increment - 1))).unwrap();
+    }
+    stew.snapshot_before(&time);
+    stew.settle_before(time);
+  }
+  let log = stew.finish();
+
+  let mut replayed: amortized::Steward<Basics> = recorded::replay(&mut &log[..]).unwrap();
+  display_snapshot(&replayed.snapshot_before(&2000).unwrap());
+
+  // a truncated log still replays the part that exists
+  recorded::replay::<Basics, amortized::Steward<Basics>, _>(&mut &log[..log.len() / 2]).unwrap();
+
+  // a corrupted event id is reported as a malformed log, not a panic
+  use time_steward::Event;
+  let initialize = Initialize::event_id().0;
+  let initialize_bytes: Vec<u8> = (0..8).map(|byte| (initialize >> (byte * 8)) as u8).collect();
+  let mut corrupted = log.clone();
+  let position = corrupted.windows(8).position(|window| window == &initialize_bytes[..]).unwrap();
+  corrupted[position] ^= 1;
+  match recorded::replay::<Basics, amortized::Steward<Basics>, _>(&mut &corrupted[..]) {
+    Err(recorded::ReplayError::Malformed(_)) => {}
+    _ => panic!("a corrupted event id should be a malformed log"),
+  }
+}
+
+// A small recorded log: the usual philosophers, plus a Tweak at each of the given times.
+fn recorded_log(tweak_times: &[Time]) -> Vec<u8> {
+  use time_steward::stewards::recorded;
+  let mut stew: recorded::Steward<Basics, amortized::Steward<Basics>, Vec<u8>> =
+    recorded::Steward::new((), 0, 100, Vec::new());
+  stew.insert_fiat_event(0,
+                       DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                       Initialize::new())
+    .unwrap();
+  for time in tweak_times {
+    stew.insert_fiat_event(*time, DeterministicRandomId::new(time), Tweak::new()).unwrap();
+  }
+  stew.settle_before(1000);
+  stew.finish()
+}
+
+#[test]
+fn inspection_of_recorded_logs() {
+  use time_steward::stewards::recorded;
+  use time_steward::support::inspection;
+  let plain = recorded_log(&[]);
+  let tweaked = recorded_log(&[450]);
+  let mut first: amortized::Steward<Basics> = recorded::replay(&mut &plain[..]).unwrap();
+  let mut again: amortized::Steward<Basics> = recorded::replay(&mut &plain[..]).unwrap();
+  let mut second: amortized::Steward<Basics> = recorded::replay(&mut &tweaked[..]).unwrap();
+
+  let snapshot = first.snapshot_before(&1000).unwrap();
+  let statistics = inspection::snapshot_statistics(&snapshot);
+  assert_eq!(statistics.rows, HOW_MANY_PHILOSOPHERS as usize);
+  assert_eq!(statistics.fields, HOW_MANY_PHILOSOPHERS as usize);
+  assert_eq!(statistics.rows_per_column.get(&Philosopher::column_id()), Some(&(HOW_MANY_PHILOSOPHERS as usize)));
+  let events = inspection::events_per_chunk(&mut first, 0..10);
+  assert_eq!(events.len(), 10);
+  assert!(events[0] > 0);
+
+  assert!(inspection::first_divergent_chunk(&mut first, &mut again, 0..10).is_none());
+  let divergence = inspection::first_divergent_chunk(&mut first, &mut second, 0..10).unwrap();
+  assert_eq!(divergence.chunk, 4);
+  // the Tweak only happened in the second run
+  assert!(divergence.differences.iter().any(|difference| difference.first.is_none() && difference.second.is_some()));
+}
+
+#[test]
+fn inspection_commands() {
+  use time_steward::support::inspection;
+  use std::io::Write;
+  let directory = ::std::env::temp_dir();
+  let plain_path = directory.join("time_steward_inspection_commands_plain.log");
+  let tweaked_path = directory.join("time_steward_inspection_commands_tweaked.log");
+  ::std::fs::File::create(&plain_path).unwrap().write_all(&recorded_log(&[])).unwrap();
+  ::std::fs::File::create(&tweaked_path).unwrap().write_all(&recorded_log(&[450])).unwrap();
+  let (plain, tweaked) = (plain_path.to_str().unwrap(), tweaked_path.to_str().unwrap());
+  let run = |arguments: &[&str]| {
+    let arguments: Vec<String> = arguments.iter().map(|argument| argument.to_string()).collect();
+    inspection::run::<Basics, amortized::Steward<Basics>>(&arguments)
+  };
+
+  let row = get_philosopher_id(0);
+  let row = format!("{{\"data\":[{},{}]}}", row.data()[0], row.data()[1]);
+  let column = Philosopher::column_id().0.to_string();
+  assert_eq!(run(&["stats", plain, "1000"]), Ok(()));
+  assert_eq!(run(&["step", plain, "1000"]), Ok(()));
+  assert_eq!(run(&["field", plain, "1000", &row, &column]), Ok(()));
+  assert_eq!(run(&["compare", plain, tweaked, "1000"]), Ok(()));
+
+  assert_eq!(run(&["frobnicate", plain]), Err(inspection::USAGE.to_string()));
+  assert_eq!(run(&["stats", plain]), Err(inspection::USAGE.to_string()));
+  assert!(run(&["stats", plain, "not a time"]).is_err());
+  assert!(run(&["stats", "time_steward_inspection_commands_missing.log", "1000"]).is_err());
+  assert!(run(&["compare", plain, tweaked, "1000", "extra"]).is_err());
+}
+
 #[test]
 fn local_synchronization_test() {
   use time_steward::stewards::simply_synchronized;
@@ -260,6 +458,50 @@ fn local_synchronization_test() {
   stew_1.finish();
 }
 
+#[test]
+fn local_synchronization_many_peers() {
+  use time_steward::stewards::simply_synchronized;
+  use std::net::{TcpListener, TcpStream};
+  use std::io::{BufReader, BufWriter};
+  const HOW_MANY_PEERS: usize = 4;
+  let listeners: Vec<TcpListener> = (0..HOW_MANY_PEERS).map(|_| TcpListener::bind(("127.0.0.1", 0)).unwrap()).collect();
+  let ports: Vec<u16> = listeners.iter().map(|listener| listener.local_addr().unwrap().port()).collect();
+  let threads: Vec<_> = listeners.into_iter().enumerate().map(|(index, listener)| {
+    let ports = ports.clone();
+    ::std::thread::spawn(move || {
+      let mut stew: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+        simply_synchronized::Steward::new_without_peers(DeterministicRandomId::new(&(index as u32)), 0, 4, ());
+      // each peer connects to the peers before it, and accepts connections from the peers after it
+      for port in ports[..index].iter() {
+        let stream = TcpStream::connect(("127.0.0.1", *port)).unwrap();
+        stew.add_peer(BufReader::new(stream.try_clone().unwrap()), BufWriter::new(stream));
+      }
+      for _ in index + 1..HOW_MANY_PEERS {
+        let stream = listener.accept().unwrap().0;
+        stew.add_peer(BufReader::new(stream.try_clone().unwrap()), BufWriter::new(stream));
+      }
+      if index == 0 {
+        stew.insert_fiat_event(0,
+                             DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                             Initialize::new())
+          .unwrap();
+      }
+      for increment in 1..21 {
+        let time = increment * 100i64;
+        if increment % (index as i64 + 3) == 0 {
+          stew.insert_fiat_event(time, DeterministicRandomId::new(&increment), Tweak::new()).unwrap();
+        }
+        stew.snapshot_before(&time);
+        stew.settle_before(time);
+      }
+      stew.finish();
+    })
+  }).collect();
+  for thread in threads {
+    thread.join().unwrap();
+  }
+}
+
 #[test]
 #[should_panic (expected = "event occurred this way locally")]
 fn local_synchronization_failure() {
@@ -317,3 +559,728 @@ fn local_synchronization_failure() {
   }
   stew_1.finish();
 }
+
+#[test]
+fn local_synchronization_failure_reported() {
+  use time_steward::stewards::simply_synchronized;
+  use std::net::{TcpListener, TcpStream};
+  use std::io::{BufReader, BufWriter};
+  use std::rc::Rc;
+  use std::cell::RefCell;
+  fn run(mut stew: simply_synchronized::Steward<Basics, amortized::Steward<Basics>>, interval: i64) -> Vec<simply_synchronized::Desynchronization<Basics>> {
+    let reports = Rc::new(RefCell::new(Vec::new()));
+    let reports_hack = reports.clone();
+    stew.on_desynchronization(move |desynchronization| reports_hack.borrow_mut().push(desynchronization));
+    for increment in 1..21 {
+      let time = increment * 100i64;
+      if increment % interval == 0 {
+        stew.insert_fiat_event(time,
+                             DeterministicRandomId::new(&increment),
+                             TweakUnsafe::new())
+          .unwrap();
+      }
+      stew.snapshot_before(&time);
+      stew.settle_before(time);
+    }
+    stew.finish();
+    let result = reports.borrow().clone();
+    result
+  }
+  let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+  let port = listener.local_addr().unwrap().port();
+  let other = ::std::thread::spawn(move || {
+    let end_0 = listener.accept().unwrap().0;
+    let mut stew_0: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+      simply_synchronized::Steward::new(DeterministicRandomId::new(&0u32),
+                                        0,
+                                        4,
+                                        (),
+                                        BufReader::new(end_0.try_clone().unwrap()),
+                                        BufWriter::new(end_0));
+    stew_0.insert_fiat_event(0,
+                         DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                         Initialize::new())
+      .unwrap();
+    run(stew_0, 3)
+  });
+  let end_1 = TcpStream::connect(("127.0.0.1", port)).unwrap();
+  let stew_1: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+    simply_synchronized::Steward::new(DeterministicRandomId::new(&1u32),
+                                      0,
+                                      4,
+                                      (),
+                                      BufReader::new(end_1.try_clone().unwrap()),
+                                      BufWriter::new(end_1));
+  let reports_1 = run(stew_1, 4);
+  let reports_0 = other.join().unwrap();
+
+  assert_eq!(reports_0.len(), 1);
+  assert_eq!(reports_1.len(), 1);
+  assert_eq!(reports_0[0].peer, DeterministicRandomId::new(&1u32));
+  assert_eq!(reports_1[0].peer, DeterministicRandomId::new(&0u32));
+  assert_eq!(reports_0[0].chunk, reports_1[0].chunk);
+  assert!(!reports_1[0].differences.is_empty());
+  assert!(reports_0[0].complete && reports_1[0].complete);
+  // each side sees the other's details as remote
+  for (mine, theirs) in reports_0[0].differences.iter().zip(reports_1[0].differences.iter()) {
+    assert_eq!(mine.time, theirs.time);
+    assert_eq!(mine.local, theirs.remote);
+    assert_eq!(mine.remote, theirs.local);
+  }
+}
+
+#[test]
+fn local_synchronization_one_of_many_diverges() {
+  use time_steward::stewards::simply_synchronized;
+  use std::net::{TcpListener, TcpStream};
+  use std::io::{BufReader, BufWriter};
+  use std::rc::Rc;
+  use std::cell::RefCell;
+  const HOW_MANY_PEERS: usize = 3;
+  const DIVERGENT: usize = 2;
+  let listeners: Vec<TcpListener> = (0..HOW_MANY_PEERS).map(|_| TcpListener::bind(("127.0.0.1", 0)).unwrap()).collect();
+  let ports: Vec<u16> = listeners.iter().map(|listener| listener.local_addr().unwrap().port()).collect();
+  let threads: Vec<_> = listeners.into_iter().enumerate().map(|(index, listener)| {
+    let ports = ports.clone();
+    ::std::thread::spawn(move || {
+      DIVERGES.with(|value| value.set(index == DIVERGENT));
+      let mut stew: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+        simply_synchronized::Steward::new_without_peers(DeterministicRandomId::new(&(index as u32)), 0, 4, ());
+      for port in ports[..index].iter() {
+        let stream = TcpStream::connect(("127.0.0.1", *port)).unwrap();
+        stew.add_peer(BufReader::new(stream.try_clone().unwrap()), BufWriter::new(stream));
+      }
+      for _ in index + 1..HOW_MANY_PEERS {
+        let stream = listener.accept().unwrap().0;
+        stew.add_peer(BufReader::new(stream.try_clone().unwrap()), BufWriter::new(stream));
+      }
+      let reports = Rc::new(RefCell::new(Vec::new()));
+      let reports_hack = reports.clone();
+      stew.on_desynchronization(move |desynchronization| reports_hack.borrow_mut().push(desynchronization));
+      if index == 0 {
+        stew.insert_fiat_event(0,
+                             DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                             Initialize::new())
+          .unwrap();
+        stew.insert_fiat_event(500, DeterministicRandomId::new(&5u32), TweakDivergent::new()).unwrap();
+      }
+      for increment in 1..21 {
+        let time = increment * 100i64;
+        stew.snapshot_before(&time);
+        stew.settle_before(time);
+      }
+      stew.finish();
+      assert!(stew.dropped_peers().is_empty());
+      let result = reports.borrow().clone();
+      result
+    })
+  }).collect();
+  let reports: Vec<Vec<simply_synchronized::Desynchronization<Basics>>> =
+    threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+
+  let divergent_id = DeterministicRandomId::new(&(DIVERGENT as u32));
+  // the divergent peer has a desynchronization in flight with each of the others at once
+  assert_eq!(reports[DIVERGENT].len(), HOW_MANY_PEERS - 1);
+  for (index, peer_reports) in reports.iter().enumerate() {
+    for report in peer_reports {
+      assert!(report.complete);
+      assert!(!report.differences.is_empty());
+      // the stride is 4, and the divergent event is at 500
+      assert_eq!(report.chunk, 125);
+    }
+    if index != DIVERGENT {
+      assert_eq!(peer_reports.len(), 1);
+      assert_eq!(peer_reports[0].peer, divergent_id);
+    }
+  }
+}
+
+#[test]
+fn local_synchronization_late_join() {
+  use time_steward::stewards::simply_synchronized;
+  use std::net::{TcpListener, TcpStream};
+  use std::io::{BufReader, BufWriter};
+  let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+  let port = listener.local_addr().unwrap().port();
+  ::std::thread::spawn(move || {
+    let mut stew_0: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+      simply_synchronized::Steward::new_without_peers(DeterministicRandomId::new(&0u32), 0, 4, ());
+    stew_0.insert_fiat_event(0,
+                         DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                         Initialize::new())
+      .unwrap();
+
+    for increment in 1..21 {
+      let time = increment * 100i64;
+      if increment == 10 {
+        let end_0 = listener.accept().unwrap().0;
+        stew_0.add_late_peer(BufReader::new(end_0.try_clone().unwrap()), BufWriter::new(end_0));
+      }
+      if increment % 3 == 0 {
+        stew_0.insert_fiat_event(time, DeterministicRandomId::new(&increment), Tweak::new())
+          .unwrap();
+      }
+      stew_0.snapshot_before(&time);
+      stew_0.settle_before(time);
+    }
+    stew_0.finish();
+  });
+  let end_1 = TcpStream::connect(("127.0.0.1", port)).unwrap();
+  let mut stew_1: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+    simply_synchronized::Steward::join(DeterministicRandomId::new(&1u32),
+                                       BufReader::new(end_1.try_clone().unwrap()),
+                                       BufWriter::new(end_1)).unwrap();
+  assert!(stew_1.valid_since() > 0);
+
+  for increment in 10..21 {
+    let time = increment * 100i64;
+    if increment % 4 == 0 {
+      stew_1.insert_fiat_event(time, DeterministicRandomId::new(&increment), Tweak::new()).unwrap();
+    }
+    display_snapshot(&stew_1.snapshot_before(&time).unwrap());
+    stew_1.settle_before(time);
+  }
+  stew_1.finish();
+}
+
+#[test]
+fn local_synchronization_reconnect() {
+  use time_steward::stewards::simply_synchronized;
+  use std::net::{TcpListener, TcpStream, Shutdown};
+  use std::io::{BufReader, BufWriter};
+  let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+  let port = listener.local_addr().unwrap().port();
+  ::std::thread::spawn(move || {
+    let end_0 = listener.accept().unwrap().0;
+    let mut stew_0: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+      simply_synchronized::Steward::new(DeterministicRandomId::new(&0u32),
+                                        0,
+                                        4,
+                                        (),
+                                        BufReader::new(end_0.try_clone().unwrap()),
+                                        BufWriter::new(end_0.try_clone().unwrap()));
+    stew_0.insert_fiat_event(0,
+                         DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                         Initialize::new())
+      .unwrap();
+
+    for increment in 1..21 {
+      let time = increment * 100i64;
+      if increment == 10 {
+        end_0.shutdown(Shutdown::Both).unwrap();
+        // fiat events inserted while disconnected are delivered after reconnecting
+        stew_0.insert_fiat_event(time, DeterministicRandomId::new(&(increment + 1000)), Tweak::new())
+          .unwrap();
+        while stew_0.disconnected_peers().is_empty() {
+          stew_0.snapshot_before(&time);
+        }
+        assert_eq!(stew_0.disconnected_peers(), vec![DeterministicRandomId::new(&1u32)]);
+        let replacement = listener.accept().unwrap().0;
+        assert!(stew_0.reconnect_peer(DeterministicRandomId::new(&1u32),
+                                      BufReader::new(replacement.try_clone().unwrap()),
+                                      BufWriter::new(replacement)));
+        assert!(stew_0.disconnected_peers().is_empty());
+      }
+      if increment % 3 == 0 {
+        stew_0.insert_fiat_event(time, DeterministicRandomId::new(&increment), Tweak::new())
+          .unwrap();
+      }
+      stew_0.snapshot_before(&time);
+      stew_0.settle_before(time);
+    }
+    stew_0.finish();
+  });
+  let end_1 = TcpStream::connect(("127.0.0.1", port)).unwrap();
+  let mut stew_1: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+    simply_synchronized::Steward::new(DeterministicRandomId::new(&1u32),
+                                      0,
+                                      4,
+                                      (),
+                                      BufReader::new(end_1.try_clone().unwrap()),
+                                      BufWriter::new(end_1));
+
+  for increment in 1..21 {
+    let time = increment * 100i64;
+    if increment == 10 {
+      while stew_1.disconnected_peers().is_empty() {
+        stew_1.snapshot_before(&time);
+      }
+      let replacement = TcpStream::connect(("127.0.0.1", port)).unwrap();
+      assert!(stew_1.reconnect_peer(DeterministicRandomId::new(&0u32),
+                                    BufReader::new(replacement.try_clone().unwrap()),
+                                    BufWriter::new(replacement)));
+    }
+    if increment % 4 == 0 {
+      stew_1.insert_fiat_event(time, DeterministicRandomId::new(&increment), Tweak::new()).unwrap();
+    }
+    stew_1.snapshot_before(&time);
+    stew_1.settle_before(time);
+  }
+  stew_1.finish();
+}
+
+#[test]
+fn local_synchronization_finish_after_disconnect() {
+  use time_steward::stewards::simply_synchronized;
+  use std::net::{TcpListener, TcpStream, Shutdown};
+  use std::io::{BufReader, BufWriter};
+  let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+  let port = listener.local_addr().unwrap().port();
+  let other = ::std::thread::spawn(move || {
+    let end_0 = listener.accept().unwrap().0;
+    let mut stew_0: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+      simply_synchronized::Steward::new(DeterministicRandomId::new(&0u32),
+                                        0,
+                                        4,
+                                        (),
+                                        BufReader::new(end_0.try_clone().unwrap()),
+                                        BufWriter::new(end_0));
+    stew_0.insert_fiat_event(0,
+                         DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                         Initialize::new())
+      .unwrap();
+    stew_0.snapshot_before(&100);
+    stew_0.settle_before(100);
+    // the peer hangs up without finishing, so this must not wait for it
+    stew_0.finish();
+  });
+  let end_1 = TcpStream::connect(("127.0.0.1", port)).unwrap();
+  let mut stew_1: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+    simply_synchronized::Steward::new(DeterministicRandomId::new(&1u32),
+                                      0,
+                                      4,
+                                      (),
+                                      BufReader::new(end_1.try_clone().unwrap()),
+                                      BufWriter::new(end_1.try_clone().unwrap()));
+  stew_1.snapshot_before(&100);
+  end_1.shutdown(Shutdown::Both).unwrap();
+  other.join().unwrap();
+}
+
+#[test]
+fn authoritative_server() {
+  use time_steward::stewards::authoritative;
+  use std::net::{TcpListener, TcpStream};
+  use std::io::{BufReader, BufWriter};
+  use std::rc::Rc;
+  use std::cell::RefCell;
+  type Server = authoritative::Server<Basics, amortized::Steward<Basics>>;
+  type Client = authoritative::Client<Basics, amortized::Steward<Basics>>;
+  fn json<S: TimeSteward<Basics = Basics>>(steward: &mut S, time: Time) -> Vec<u8>
+    where for<'a> &'a S::Snapshot: IntoIterator<Item = time_steward::SnapshotEntry<'a, Basics>>
+  {
+    let mut writer = Vec::new();
+    time_steward::export_snapshot_json::<Basics, S::Snapshot, _>(&steward.snapshot_before(&time).unwrap(), &mut writer).unwrap();
+    writer
+  }
+
+  let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+  let port = listener.local_addr().unwrap().port();
+  // only the server may initialize the philosophers
+  let mut server: Server = Server::new((), |submission, _| {
+    if submission.event::<Initialize>().is_some() {
+      authoritative::Verdict::Reject
+    } else {
+      authoritative::Verdict::Accept
+    }
+  });
+  let mut clients: Vec<Client> = Vec::new();
+  for index in 0..2 {
+    let client_end = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let server_end = listener.accept().unwrap().0;
+    server.add_client(DeterministicRandomId::new(&(index as u32)),
+                      BufReader::new(server_end.try_clone().unwrap()),
+                      BufWriter::new(server_end));
+    clients.push(Client::new((),
+                             BufReader::new(client_end.try_clone().unwrap()),
+                             BufWriter::new(client_end)));
+  }
+  let rejections = Rc::new(RefCell::new(Vec::new()));
+  let handler_rejections = rejections.clone();
+  clients[1].on_rejection(move |time, id| handler_rejections.borrow_mut().push((time, id)));
+
+  server.insert_fiat_event(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), Initialize::new()).unwrap();
+  clients[0].insert_fiat_event(100, DeterministicRandomId::new(&1u32), Tweak::new()).unwrap();
+  clients[1].insert_fiat_event(200, DeterministicRandomId::new(&2u32), Initialize::new()).unwrap();
+  clients[1].insert_fiat_event(300, DeterministicRandomId::new(&3u32), Tweak::new()).unwrap();
+
+  for _ in 0..1000 {
+    let expected = json(&mut server, 1000);
+    if rejections.borrow().len() == 1 && clients.iter_mut().all(|client| json(client, 1000) == expected) {
+      break;
+    }
+    ::std::thread::sleep(::std::time::Duration::from_millis(10));
+  }
+  assert_eq!(*rejections.borrow(), vec![(200, DeterministicRandomId::new(&2u32))]);
+  let expected = json(&mut server, 1000);
+  for client in clients.iter_mut() {
+    assert_eq!(json(client, 1000), expected);
+  }
+
+  // the accepted events are the only ones that happened
+  let mut reference: amortized::Steward<Basics> = amortized::Steward::from_constants(());
+  reference.insert_fiat_event(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), Initialize::new()).unwrap();
+  reference.insert_fiat_event(100, DeterministicRandomId::new(&(DeterministicRandomId::new(&1u32), DeterministicRandomId::new(&0u32))), Tweak::new()).unwrap();
+  reference.insert_fiat_event(300, DeterministicRandomId::new(&(DeterministicRandomId::new(&3u32), DeterministicRandomId::new(&1u32))), Tweak::new()).unwrap();
+  assert_eq!(json(&mut reference, 1000), expected);
+}
+
+#[test]
+fn authoritative_client_malformed_messages() {
+  use time_steward::FiatEventOperationError;
+  use time_steward::stewards::authoritative::{self, ServerMessageError};
+  use std::io::Cursor;
+  type Client = authoritative::Client<Basics, amortized::Steward<Basics>>;
+  // the same shape as the messages a Server sends
+  #[derive (Serialize)]
+  enum ToClient {
+    InsertFiatEvent(Time, DeterministicRandomId, EventId, Vec<u8>),
+    RemoveFiatEvent(Time, DeterministicRandomId),
+  }
+  fn json<S: TimeSteward<Basics = Basics>>(steward: &mut S, time: Time) -> Vec<u8>
+    where for<'a> &'a S::Snapshot: IntoIterator<Item = time_steward::SnapshotEntry<'a, Basics>>
+  {
+    let mut writer = Vec::new();
+    time_steward::export_snapshot_json::<Basics, S::Snapshot, _>(&steward.snapshot_before(&time).unwrap(), &mut writer).unwrap();
+    writer
+  }
+
+  let mut stream = Vec::new();
+  for message in vec![ToClient::InsertFiatEvent(100, DeterministicRandomId::new(&1u32), EventId(0x1234), Vec::new()),
+                      ToClient::RemoveFiatEvent(200, DeterministicRandomId::new(&2u32)),
+                      ToClient::InsertFiatEvent(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), EventId(0xd5e73d8ba6ec59a2), bincode::serialize(&Initialize::new(), bincode::Infinite).unwrap())] {
+    bincode::serialize_into(&mut stream, &message, bincode::Infinite).unwrap();
+  }
+  let mut client: Client = Client::new((), Cursor::new(stream), ::std::io::sink());
+  // the client keeps applying the messages after the malformed ones
+  let mut reference: amortized::Steward<Basics> = amortized::Steward::from_constants(());
+  reference.insert_fiat_event(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), Initialize::new()).unwrap();
+  let expected = json(&mut reference, 1000);
+
+  let mut errors = Vec::new();
+  for _ in 0..1000 {
+    match client.receive() {
+      Err(error) => errors.push(error),
+      Ok(()) => {
+        if errors.len() == 2 && json(&mut client, 1000) == expected {
+          break;
+        }
+        ::std::thread::sleep(::std::time::Duration::from_millis(10));
+      }
+    }
+  }
+  assert_eq!(errors.len(), 2);
+  match errors[0] {
+    ServerMessageError::UnknownEvent(event_id) => assert_eq!(event_id, EventId(0x1234)),
+    ref other => panic!("unexpected error {:?}", other),
+  }
+  match errors[1] {
+    ServerMessageError::OperationRejected(time, id, error) => {
+      assert_eq!((time, id), (200, DeterministicRandomId::new(&2u32)));
+      assert_eq!(error, FiatEventOperationError::InvalidInput);
+    }
+    ref other => panic!("unexpected error {:?}", other),
+  }
+  assert_eq!(json(&mut client, 1000), expected);
+}
+
+#[test]
+fn synchronization_maximum_lateness() {
+  use time_steward::{FiatEventOperationError, ValidSince};
+  use time_steward::stewards::simply_synchronized::{self, LatenessPolicy};
+  type Steward = simply_synchronized::Steward<Basics, amortized::Steward<Basics>>;
+
+  let mut rejecting: Steward = Steward::new_without_peers(DeterministicRandomId::new(&0u32), 0, 4, ());
+  rejecting.set_maximum_lateness(50, LatenessPolicy::Reject);
+  rejecting.insert_fiat_event(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), Initialize::new()).unwrap();
+  rejecting.snapshot_before(&200);
+  assert_eq!(rejecting.valid_since(), ValidSince::Before(150));
+  assert_eq!(rejecting.insert_fiat_event(100, DeterministicRandomId::new(&1u32), Tweak::new()),
+             Err(FiatEventOperationError::TooLate));
+  rejecting.insert_fiat_event(160, DeterministicRandomId::new(&2u32), Tweak::new()).unwrap();
+  // an earlier snapshot doesn't move the window back
+  rejecting.snapshot_before(&170);
+  assert_eq!(rejecting.remove_fiat_event(&160, DeterministicRandomId::new(&2u32)), Ok(()));
+
+  let mut clamping: Steward = Steward::new_without_peers(DeterministicRandomId::new(&0u32), 0, 4, ());
+  clamping.set_maximum_lateness(50, LatenessPolicy::Clamp);
+  clamping.insert_fiat_event(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), Initialize::new()).unwrap();
+  assert_eq!(clamping.earliest_allowed(), None);
+  clamping.snapshot_before(&200);
+  let earliest = clamping.earliest_allowed().unwrap();
+  assert_eq!(earliest, 150);
+  clamping.insert_fiat_event(100, DeterministicRandomId::new(&1u32), Tweak::new()).unwrap();
+  assert_eq!(clamping.remove_fiat_event(&100, DeterministicRandomId::new(&1u32)),
+             Err(FiatEventOperationError::TooLate));
+  assert_eq!(clamping.remove_fiat_event(&earliest, DeterministicRandomId::new(&1u32)), Ok(()));
+  // events that are late enough aren't moved
+  clamping.insert_fiat_event(175, DeterministicRandomId::new(&2u32), Tweak::new()).unwrap();
+  assert_eq!(clamping.remove_fiat_event(&175, DeterministicRandomId::new(&2u32)), Ok(()));
+}
+
+#[test]
+fn local_synchronization_uneven_lateness_windows() {
+  use time_steward::stewards::simply_synchronized::{self, LatenessPolicy};
+  use std::net::{TcpListener, TcpStream};
+  use std::io::{BufReader, BufWriter};
+  let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+  let port = listener.local_addr().unwrap().port();
+  // peer 0 races far ahead, so its window settles chunks that peer 1 still inserts events into;
+  // those chunks must not be compared until peer 1 has settled them too
+  let other = ::std::thread::spawn(move || {
+    let end_0 = listener.accept().unwrap().0;
+    let mut stew_0: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+      simply_synchronized::Steward::new(DeterministicRandomId::new(&0u32),
+                                        0,
+                                        4,
+                                        (),
+                                        BufReader::new(end_0.try_clone().unwrap()),
+                                        BufWriter::new(end_0));
+    stew_0.set_maximum_lateness(50, LatenessPolicy::Reject);
+    stew_0.insert_fiat_event(0,
+                         DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                         Initialize::new())
+      .unwrap();
+    stew_0.snapshot_before(&2000);
+    stew_0.finish();
+  });
+  let end_1 = TcpStream::connect(("127.0.0.1", port)).unwrap();
+  let mut stew_1: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+    simply_synchronized::Steward::new(DeterministicRandomId::new(&1u32),
+                                      0,
+                                      4,
+                                      (),
+                                      BufReader::new(end_1.try_clone().unwrap()),
+                                      BufWriter::new(end_1));
+  stew_1.set_maximum_lateness(50, LatenessPolicy::Reject);
+  for increment in 1..21 {
+    let time = increment * 100i64;
+    if increment % 4 == 0 {
+      stew_1.insert_fiat_event(time, DeterministicRandomId::new(&increment), Tweak::new()).unwrap();
+    }
+    stew_1.snapshot_before(&time);
+  }
+  stew_1.finish();
+  other.join().unwrap();
+}
+
+fn synchronize_over_transport(options: time_steward::support::transport::Options) {
+  use time_steward::stewards::simply_synchronized;
+  use time_steward::support::transport;
+  use std::net::{TcpListener, TcpStream};
+  let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+  let port = listener.local_addr().unwrap().port();
+  let server_options = options.clone();
+  ::std::thread::spawn(move || {
+    let (reader, writer) = transport::tcp::<Basics>(listener.accept().unwrap().0, &server_options).unwrap();
+    let mut stew_0: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+      simply_synchronized::Steward::new(DeterministicRandomId::new(&0u32), 0, 4, (), reader, writer);
+    stew_0.insert_fiat_event(0,
+                         DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                         Initialize::new())
+      .unwrap();
+
+    for increment in 1..21 {
+      let time = increment * 100i64;
+      if increment % 3 == 0 {
+        stew_0.insert_fiat_event(time, DeterministicRandomId::new(&increment), Tweak::new())
+          .unwrap();
+      }
+      stew_0.snapshot_before(&time);
+      stew_0.settle_before(time);
+    }
+    stew_0.finish();
+  });
+  let (reader, writer) = transport::tcp::<Basics>(TcpStream::connect(("127.0.0.1", port)).unwrap(), &options).unwrap();
+  let mut stew_1: simply_synchronized::Steward<Basics, amortized::Steward<Basics>> =
+    simply_synchronized::Steward::new(DeterministicRandomId::new(&1u32), 0, 4, (), reader, writer);
+
+  for increment in 1..21 {
+    let time = increment * 100i64;
+    if increment % 4 == 0 {
+      stew_1.insert_fiat_event(time, DeterministicRandomId::new(&increment), Tweak::new()).unwrap();
+    }
+    stew_1.snapshot_before(&time);
+    stew_1.settle_before(time);
+  }
+  stew_1.finish();
+}
+
+#[test]
+fn local_synchronization_over_transport() {
+  synchronize_over_transport(Default::default());
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn local_synchronization_over_compressed_transport() {
+  synchronize_over_transport(time_steward::support::transport::Options { compression: true, ..Default::default() });
+}
+
+#[test]
+pub fn handshakes_many_crossverified() {
+  type Steward = crossverified::Many<Basics, (inefficient_flat::Steward<Basics>, memoized_flat::Steward<Basics>, amortized::Steward<Basics>)>;
+  let mut stew: Steward = Steward::from_constants(());
+
+  stew.insert_fiat_event(0,
+                       DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                       Initialize::new())
+    .unwrap();
+
+  for increment in 1..21 {
+    if increment % 3 == 0 {
+      stew.insert_fiat_event(increment * 100 - 50, DeterministicRandomId::new(&increment), Tweak::new()).unwrap();
+    }
+    let snapshot: <Steward as TimeSteward>::Snapshot = stew.snapshot_before(&(increment * 100i64)).unwrap();
+    assert_eq!(snapshot.divergence(), None);
+    display_snapshot(&snapshot);
+  }
+}
+
+#[test]
+fn crossverified_first_divergence() {
+  type Steward = crossverified::Many<Basics, (amortized::Steward<Basics>, amortized::Steward<Basics>)>;
+  let mut stew: Steward = Steward::from_constants(());
+  stew.begin_checks(0, 4);
+  stew.insert_fiat_event(0,
+                       DeterministicRandomId::new(&0x32e1570766e768a7u64),
+                       Initialize::new())
+    .unwrap();
+  for increment in 1..21 {
+    if increment % 3 == 0 {
+      stew.insert_fiat_event(increment * 100 - 50, DeterministicRandomId::new(&increment), Tweak::new()).unwrap();
+    }
+  }
+  stew.snapshot_before(&2000);
+  assert_eq!(stew.first_divergence(400), None);
+}
+
+#[test]
+fn crossverified_nondeterministic_divergence() {
+  type Steward = crossverified::Many<Basics, (amortized::Steward<Basics>, amortized::Steward<Basics>)>;
+  let mut stew: Steward = Steward::from_constants(());
+  stew.begin_checks(0, 4);
+  // without Initialize, the only philosopher is the one the event creates, so nothing else can diverge
+  stew.insert_fiat_event(500, DeterministicRandomId::new(&1u32), TweakNondeterministic::new()).unwrap();
+
+  let divergence = stew.first_divergence(200).unwrap();
+  assert_eq!(divergence.chunk, 125);
+  assert_eq!(divergence.time.base, 500);
+  assert_eq!(divergence.details.len(), 2);
+  for details in divergence.details.iter() {
+    assert!(details.as_ref().unwrap().contains("EventId(0x7c1d9e04f2a6b358)"));
+  }
+  assert!(divergence.details[0] != divergence.details[1]);
+
+  let snapshot = stew.snapshot_before(&501).unwrap();
+  let divergence = snapshot.divergence().unwrap();
+  assert_eq!(divergence.now, 501);
+  assert_eq!(divergence.fields.len(), 1);
+  let field = &divergence.fields[0];
+  assert_eq!((field.id.row_id, field.id.column_id), (get_philosopher_id(0), Philosopher::column_id()));
+  assert_eq!(field.versions.len(), 2);
+  let versions: Vec<&(String, time_steward::ExtendedTime<Basics>)> = field.versions.iter().map(|version| version.as_ref().unwrap()).collect();
+  // both were written by the same event, with different data
+  assert_eq!(versions[0].1.base, 500);
+  assert_eq!(versions[0].1, versions[1].1);
+  assert!(versions[0].0 != versions[1].0);
+  for version in versions.iter() {
+    assert!(version.0.contains("time_when_next_initiates_handshake"));
+  }
+}
+
+#[test]
+fn amortized_dependency_graph() {
+  use time_steward::Event;
+  let mut stew: amortized::Steward<Basics> = amortized::Steward::from_constants(());
+  stew.insert_fiat_event(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), Initialize::new()).unwrap();
+  stew.snapshot_before(&500);
+
+  let graph = stew.dependency_graph(&0, &500);
+  let initialize = graph.events.first().unwrap();
+  assert_eq!(initialize.time.base, 0);
+  assert_eq!(initialize.event_id, Some(Initialize::event_id()));
+  assert_eq!(initialize.status, amortized::EventStatus::Valid);
+  assert_eq!(initialize.changed.len(), HOW_MANY_PHILOSOPHERS as usize);
+  assert!(graph.events.iter().filter(|event| event.event_id == Some(Shake::event_id())).all(|event| event.scheduled_by.is_some()));
+  assert!(!graph.predictions.is_empty());
+
+  let field = time_steward::FieldId::new(get_philosopher_id(0), Philosopher::column_id());
+  assert!(graph.fields.contains(&field));
+  let (_, predictions) = graph.dependents(field);
+  assert!(predictions.iter().any(|prediction| prediction.row_id == get_philosopher_id(0)));
+
+  stew.insert_fiat_event(50, DeterministicRandomId::new(&1u64), Tweak::new()).unwrap();
+  let graph = stew.dependency_graph(&50, &51);
+  assert!(graph.events.iter().any(|event| event.event_id == Some(Tweak::event_id()) && event.status == amortized::EventStatus::NotExecuted));
+
+  let mut dot = Vec::new();
+  stew.dependency_graph(&0, &500).write_dot(&mut dot).unwrap();
+  let dot = String::from_utf8(dot).unwrap();
+  assert!(dot.starts_with("digraph"));
+  assert!(dot.contains("shape=diamond"));
+}
+
+#[test]
+fn steward_statistics() {
+  use std::rc::Rc;
+  use std::cell::RefCell;
+  use time_steward::{StewardAction, StewardStatistics};
+  let actions = Rc::new(RefCell::new(Vec::new()));
+  let recorded = actions.clone();
+  let mut stew: amortized::Steward<Basics> = amortized::Steward::from_constants(());
+  stew.set_observer(Some(Box::new(move |action: StewardAction, _: &StewardStatistics| recorded.borrow_mut().push(action))));
+  stew.insert_fiat_event(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), Initialize::new()).unwrap();
+  let _first = stew.snapshot_before(&500).unwrap();
+  let before = stew.statistics();
+  assert!(before.events_executed > 0);
+  assert!(before.predictions_made > 0);
+  assert_eq!(before.events_invalidated, 0);
+  assert!(before.history_bytes > 0);
+
+  for increment in 1..5 {
+    stew.insert_fiat_event(increment * 100, DeterministicRandomId::new(&increment), Tweak::new()).unwrap();
+  }
+  stew.snapshot_before(&500).unwrap();
+  let after = stew.statistics();
+  assert!(after.events_invalidated > 0);
+  assert!(after.predictions_discarded > 0);
+  assert!(after.snapshots_updated > 0);
+  let total = after.events_executed + after.events_invalidated + after.events_rerun + after.predictions_made +
+              after.predictions_discarded + after.snapshots_updated;
+  assert_eq!(actions.borrow().len() as u64, total);
+
+  let mut flat: memoized_flat::Steward<Basics> = memoized_flat::Steward::from_constants(());
+  flat.insert_fiat_event(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), Initialize::new()).unwrap();
+  flat.snapshot_before(&500).unwrap();
+  let statistics = flat.statistics();
+  assert_eq!(statistics.events_executed, before.events_executed);
+  assert_eq!(statistics.events_invalidated, 0);
+}
+
+#[test]
+fn scheduled_events_agree() {
+  use time_steward::{Event, Predictor, EventSource};
+  fn scheduled<S: TimeStewardFromConstants<Basics = Basics>>() -> Vec<time_steward::ScheduledEvent<Basics>> {
+    let mut stew = S::from_constants(());
+    stew.insert_fiat_event(0, DeterministicRandomId::new(&0x32e1570766e768a7u64), Initialize::new()).unwrap();
+    stew.insert_fiat_event(1000, DeterministicRandomId::new(&1u64), Tweak::new()).unwrap();
+    stew.snapshot_before(&5).unwrap();
+    stew.scheduled_events(&5, &2000)
+  }
+  let events = scheduled::<amortized::Steward<Basics>>();
+  assert_eq!(events, scheduled::<memoized_flat::Steward<Basics>>());
+  assert_eq!(events, scheduled::<inefficient_flat::Steward<Basics>>());
+  assert_eq!(events, scheduled::<crossverified::Many<Basics, (amortized::Steward<Basics>, memoized_flat::Steward<Basics>, inefficient_flat::Steward<Basics>)>>());
+
+  let last = events.last().unwrap();
+  assert_eq!((last.time.base, last.event_id, last.source), (1000, Tweak::event_id(), EventSource::Fiat));
+  for event in events.iter().take(events.len() - 1) {
+    assert_eq!(event.event_id, Shake::event_id());
+    match event.source {
+      EventSource::Predicted { predictor_id, .. } => assert_eq!(predictor_id, Shaker::predictor_id()),
+      EventSource::Fiat => panic!("Shake events should be predicted"),
+    }
+  }
+  assert!(events.windows(2).all(|pair| pair[0].time < pair[1].time));
+}
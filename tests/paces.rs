@@ -2,6 +2,7 @@
 #[macro_use]
 extern crate time_steward as steward;
 extern crate rand;
+extern crate quickcheck;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
@@ -129,3 +130,25 @@ fn amortized_cross_memoized_flat_to_inefficient_full() {
     >,_> (&mut generator);
   }
 }
+
+#[derive (Clone, Debug)]
+struct PacesFiatEvents;
+impl steward::testing::FiatEvents for PacesFiatEvents {
+  type Basics = Basics;
+  fn constants <G: quickcheck::Gen> (generator: &mut G)->DeterministicRandomId {RowId::new (& generator.gen::<u64>())}
+  fn time <G: quickcheck::Gen> (generator: &mut G)->DeterministicRandomId {RowId::new (& generator.gen::<u64>())}
+  fn generate <G: quickcheck::Gen> (_: &mut G)->Self {PacesFiatEvents}
+  fn insert <Steward: steward::TimeSteward <Basics = Basics>> (&self, stew: &mut Steward, time: DeterministicRandomId, id: DeterministicRandomId)->Result <(), steward::FiatEventOperationError> {
+    stew.insert_fiat_event (time, id, FiatEvent::new())
+  }
+}
+
+#[test]
+fn stress_test_amortized_cross_memoized_flat_cross_inefficient_flat() {
+  use steward::stewards::{amortized, memoized_flat, inefficient_flat, crossverified};
+  steward::testing::stress_test:: <crossverified::Many <Basics, (
+    amortized::Steward <Basics>,
+    memoized_flat::Steward <Basics>,
+    inefficient_flat::Steward <Basics>,
+  )>, PacesFiatEvents> (5, 1000);
+}
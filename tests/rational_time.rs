@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate time_steward;
+
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+
+use time_steward::{TimeSteward, TimeStewardFromConstants, Accessor, DeterministicRandomId, Column, ColumnId, RowId, PredictorId, EventId,
+     ColumnType, EventType, PredictorType};
+use time_steward::stewards::{amortized, simply_synchronized, recorded};
+use time_steward::support::rational::Rational64;
+
+// Stewards that work in strides, running with a dense time instead of integer ticks.
+
+time_steward_basics!(struct Basics {
+  type Time = Rational64;
+  type Constants = ();
+  type IncludedTypes = (ColumnType<Countdown>, EventType<Start>, EventType<Tick>, PredictorType<Ticker>);
+});
+
+#[derive (Clone, PartialEq, Eq, Serialize, Deserialize, Debug)]
+struct Countdown {
+  remaining: i64,
+  next: Rational64,
+}
+impl Column for Countdown {
+  type FieldType = Self;
+  fn column_id() -> ColumnId {
+    ColumnId(0x3f6a1c92d04e87b5)
+  }
+}
+
+fn countdown_id() -> RowId {
+  DeterministicRandomId::new(&0x91b2e05d7c43a6f8u64)
+}
+
+// a third of a time unit, which no stride used here divides evenly
+fn interval() -> Rational64 {
+  Rational64::new(1, 3)
+}
+
+time_steward_event! (
+  struct Start {remaining: i64}, Basics, EventId (0x5d08c7e3a19f42b6),
+  | &self, m | {
+    let next = *m.now() + interval();
+    m.set::<Countdown>(countdown_id(), Some(Countdown {remaining: self.remaining, next: next}));
+  }
+);
+
+time_steward_event! (
+  struct Tick {}, Basics, EventId (0xe2749b1f06c3d85a),
+  | &self, m | {
+    let remaining = m.get::<Countdown>(countdown_id()).unwrap().remaining - 1;
+    let next = *m.now() + interval();
+    m.set::<Countdown>(countdown_id(), Some(Countdown {remaining: remaining, next: next}));
+  }
+);
+
+time_steward_predictor! (
+  struct Ticker, Basics, PredictorId(0x84c1f37a2e5d096b), watching Countdown,
+  | pa, whodunnit | {
+  let countdown = pa.get::<Countdown>(whodunnit).unwrap().clone();
+  if countdown.remaining > 0 {
+    pa.predict_at_time(countdown.next, Tick::new());
+  }
+});
+
+fn countdown<S: TimeSteward<Basics = Basics>>(steward: &mut S, time: Rational64) -> Countdown
+  where S::Snapshot: Accessor<Basics = Basics>
+{
+  steward.snapshot_before(&time).unwrap().get::<Countdown>(countdown_id()).unwrap().clone()
+}
+
+#[test]
+fn rational_amortized() {
+  let mut stew: amortized::Steward<Basics> = amortized::Steward::from_constants(());
+  stew.insert_fiat_event(Rational64::new(1, 2), DeterministicRandomId::new(&0u32), Start::new(20)).unwrap();
+  // the ticks are at 1/2 + k/3, so the 13th is at 29/6 and the 14th is at 31/6
+  assert_eq!(countdown(&mut stew, Rational64::from_integer(5)),
+             Countdown {remaining: 7, next: Rational64::new(31, 6)});
+  // snapshots are from before the events at their time
+  assert_eq!(countdown(&mut stew, Rational64::new(29, 6)).remaining, 8);
+  assert_eq!(countdown(&mut stew, Rational64::from_integer(10)),
+             Countdown {remaining: 0, next: Rational64::new(15, 2)});
+}
+
+#[test]
+fn rational_simply_synchronized() {
+  type Steward = simply_synchronized::Steward<Basics, amortized::Steward<Basics>>;
+  let mut stew: Steward = Steward::new_without_peers(DeterministicRandomId::new(&0u32), Rational64::zero(), Rational64::new(1, 4), ());
+  stew.insert_fiat_event(Rational64::new(1, 2), DeterministicRandomId::new(&0u32), Start::new(20)).unwrap();
+  assert_eq!(countdown(&mut stew, Rational64::from_integer(5)).remaining, 7);
+  stew.settle_before(Rational64::from_integer(5));
+  assert_eq!(stew.insert_fiat_event(Rational64::new(9, 2), DeterministicRandomId::new(&1u32), Start::new(20)),
+             Err(time_steward::FiatEventOperationError::InvalidTime));
+  // restarting between the ticks at 31/6 and 33/6 replaces the predicted tick
+  stew.insert_fiat_event(Rational64::new(16, 3), DeterministicRandomId::new(&1u32), Start::new(2)).unwrap();
+  assert_eq!(countdown(&mut stew, Rational64::from_integer(10)),
+             Countdown {remaining: 0, next: Rational64::new(19, 3)});
+}
+
+#[test]
+fn rational_recording() {
+  let mut stew: recorded::Steward<Basics, amortized::Steward<Basics>, Vec<u8>> =
+    recorded::Steward::new((), Rational64::zero(), Rational64::new(1, 4), Vec::new());
+  stew.insert_fiat_event(Rational64::new(1, 2), DeterministicRandomId::new(&0u32), Start::new(20)).unwrap();
+  stew.snapshot_before(&Rational64::from_integer(3));
+  stew.settle_before(Rational64::from_integer(3));
+  stew.insert_fiat_event(Rational64::new(10, 3), DeterministicRandomId::new(&1u32), Start::new(5)).unwrap();
+  stew.snapshot_before(&Rational64::from_integer(10));
+  stew.settle_before(Rational64::from_integer(10));
+  let log = stew.finish();
+
+  let mut replayed: amortized::Steward<Basics> = recorded::replay(&mut &log[..]).unwrap();
+  let mut reference: amortized::Steward<Basics> = amortized::Steward::from_constants(());
+  reference.insert_fiat_event(Rational64::new(1, 2), DeterministicRandomId::new(&0u32), Start::new(20)).unwrap();
+  reference.insert_fiat_event(Rational64::new(10, 3), DeterministicRandomId::new(&1u32), Start::new(5)).unwrap();
+  for &time in [Rational64::new(7, 2), Rational64::new(11, 3), Rational64::from_integer(10)].iter() {
+    assert_eq!(countdown(&mut replayed, time), countdown(&mut reference, time));
+  }
+  assert_eq!(countdown(&mut replayed, Rational64::from_integer(10)),
+             Countdown {remaining: 0, next: Rational64::new(16, 3)});
+}